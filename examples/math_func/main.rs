@@ -38,7 +38,7 @@ fn main() {
             .build()
             .unwrap();
 
-        evolution.run();
+        evolution.run().unwrap();
 
         let best_found = evolution.current_best();
 
@@ -47,7 +47,7 @@ fn main() {
 
     let mut evolution = evolution_builder.build().unwrap();
 
-    evolution.run();
+    evolution.run().unwrap();
 
     evolution.time_digest();
 
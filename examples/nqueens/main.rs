@@ -40,33 +40,25 @@ impl Fitness<IntPerm> for NQueensFitness {
 fn main() {
     let file_name = "examples/nqueens/Config.toml";
 
-    let config = read_config(file_name).expect("Failed to read config file");
+    let config = read_experiment_config(file_name).expect("Failed to read config file");
 
-    if let GeneCod::IntPerm = config.gene_cod {
-        let crossover = PartiallyMappedCrossover {
-            crossover_rate: 0.8,
-        };
-
-        let mutation = SwapMutation::new(0.02);
-
-        let max_colisions = config.dimension as f64 * (config.dimension as f64 - 1.0);
+    if let GeneCod::IntPerm = config.config.gene_cod {
+        let max_colisions = config.config.dimension as f64 * (config.config.dimension as f64 - 1.0);
 
         let fitness = NQueensFitness {
             c_max: max_colisions,
         };
 
-        let evolution_builder = EvolutionBuilder::from_config(config.into())
+        // The operators, the elitism and the title come from the config file.
+        let evolution_builder = config
+            .builder::<IntPerm>()
+            .unwrap()
             .with_fitness(fitness)
-            .with_selection(TournamentSelection::default())
-            .with_crossover(crossover.clone())
-            .with_mutation(mutation.clone())
-            .with_elitism(10)
-            .with_stop_condition(move |best_fitness, _, _| best_fitness == max_colisions)
-            .with_title("NQueens".to_string());
+            .with_stop_condition(move |best_fitness, _, _| best_fitness == max_colisions);
 
         let mut evolution = evolution_builder.build().unwrap();
 
-        evolution.run();
+        evolution.run().unwrap();
 
         evolution
             .plot_chart("NQueens.png", "NQueens Problem")
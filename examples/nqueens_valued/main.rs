@@ -34,7 +34,7 @@ fn main() {
         let mut experiment =
             ExperimentRunner::new(format!("{}Queens", dimension), 30, evolution_builder);
 
-        experiment.run();
+        experiment.run().unwrap();
 
         let avg_max_iterations = experiment
             .experiment_results
@@ -38,7 +38,7 @@ fn main() {
         .build().unwrap();
 
     // The run method of the Evolution will run the evolution until the stop condition is met
-    evolution.run();
+    evolution.run().unwrap();
 
     // After the evolution is done, we can get the best individual and its fitness:
     let best = evolution.current_best();
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use evolutionary::prelude::*;
+
+// Finds an expression for the function below from its values in a few points.
+fn target(x: f64) -> f64 {
+    x * x * x + x * x + x
+}
+
+#[derive(Clone)]
+struct RegressionFitness {
+    primitives: Arc<PrimitiveSet<f64>>,
+    points: Vec<(f64, f64)>,
+}
+
+impl Fitness<Tree<f64>> for RegressionFitness {
+    // The sum of the absolute errors, to be minimized.
+    fn calculate_fitness(&self, individual: &Tree<f64>) -> f64 {
+        let error: f64 = self
+            .points
+            .iter()
+            .map(|&(x, y)| (individual.evaluate(&self.primitives, &[x]) - y).abs())
+            .sum();
+
+        if error.is_finite() {
+            error
+        } else {
+            f64::MAX
+        }
+    }
+}
+
+fn main() {
+    let primitives = Arc::new(
+        PrimitiveSet::<f64>::new()
+            .with_function("add", 2, |args| args[0] + args[1])
+            .with_function("sub", 2, |args| args[0] - args[1])
+            .with_function("mul", 2, |args| args[0] * args[1])
+            // Protected division, so a division by zero doesn't spoil the whole tree.
+            .with_function("div", 2, |args| {
+                if args[1].abs() < 1e-9 {
+                    1.0
+                } else {
+                    args[0] / args[1]
+                }
+            })
+            .with_variable("x")
+            .with_ephemeral_constant(|u| (u * 10.0).round() - 5.0),
+    );
+
+    let points = (-10..=10)
+        .map(|i| i as f64 / 10.0)
+        .map(|x| (x, target(x)))
+        .collect();
+
+    let fitness = RegressionFitness {
+        primitives: primitives.clone(),
+        points,
+    };
+
+    // The dimension is the maximum depth of the trees of the initial population.
+    let mut evolution =
+        EvolutionBuilder::new(200, 6, GeneCod::Tree, TreeConfig::new(primitives.clone()))
+            .with_fitness(fitness)
+            .with_selection(TournamentSelection::default())
+            .with_crossover(SubtreeCrossover::default())
+            .with_mutation(SubtreeMutation::new(primitives.clone()))
+            .with_title("Symbolic Regression".to_string())
+            .minimize()
+            .with_stop_condition(|best_fitness, iterations, _| {
+                best_fitness < 1e-6 || iterations >= 200
+            })
+            .with_elitism(5)
+            .build()
+            .unwrap();
+
+    evolution.run().unwrap();
+
+    let best = evolution.current_best();
+    println!("Best found: {}", best.to_expression(&primitives));
+    println!("Error: {}", best.get_fitness());
+    evolution.time_digest();
+}
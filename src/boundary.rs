@@ -0,0 +1,152 @@
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{objective::Objective, population::Individual};
+
+/// # Boundary Handler
+///
+/// What the operators do with a gene that they push out of its bounds, like the
+/// [`GaussianMutation`](crate::prelude::GaussianMutation) or the
+/// [`BlendCrossover`](crate::prelude::BlendCrossover) can. It's set in the
+/// [`EvolutionBuilder`](crate::EvolutionBuilder) and passed to the operators in the
+/// [`Context`](crate::Context).
+///
+/// - `Clamp`: the gene is moved to the closest bound. It's the default.
+/// - `Reflect`: the gene is mirrored back into the bounds by as much as it exceeded them.
+/// - `Wrap`: the bounds are treated as periodic, so exceeding the upper bound comes back from the
+///   lower one.
+/// - `RandomReinit`: the gene is replaced by a random value within the bounds.
+/// - `Resample`: the operator draws the gene again, up to `max_attempts` times, and clamps it if
+///   it's still out of the bounds. The operators without randomness, like the
+///   [`ArithmeticCrossover`](crate::prelude::ArithmeticCrossover), just clamp.
+/// - `Penalty { coefficient }`: the gene is kept out of the bounds, and the fitness of the
+///   individual gets worse by `coefficient` times the distance of its genes to their bounds, see
+///   [`Individual::bounds_violation`].
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+/// use rand::SeedableRng;
+///
+/// let mut rng = EvolutionRng::seed_from_u64(0);
+///
+/// let reflected = BoundaryHandler::Reflect.repair(1.25, (0.0, 1.0), &mut rng, |_| 0.0);
+/// let wrapped = BoundaryHandler::Wrap.repair(1.25, (0.0, 1.0), &mut rng, |_| 0.0);
+///
+/// assert_eq!(reflected, 0.75);
+/// assert_eq!(wrapped, 0.25);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum BoundaryHandler {
+    #[default]
+    Clamp,
+    Reflect,
+    Wrap,
+    RandomReinit,
+    Resample { max_attempts: usize },
+    Penalty { coefficient: f64 },
+}
+
+impl BoundaryHandler {
+    /// Returns the gene `value` handled to fit in `(min, max)`. The genes within the bounds are
+    /// returned as they are. `resample` draws a new value for the gene, for the `Resample`
+    /// handler.
+    pub fn repair<R: Rng + ?Sized>(
+        &self,
+        value: f64,
+        (min, max): (f64, f64),
+        rng: &mut R,
+        mut resample: impl FnMut(&mut R) -> f64,
+    ) -> f64 {
+        if (min..=max).contains(&value) {
+            return value;
+        }
+
+        let width = max - min;
+
+        match *self {
+            BoundaryHandler::Clamp => value.clamp(min, max),
+            BoundaryHandler::Reflect if width > 0.0 => {
+                let offset = (value - min).rem_euclid(2.0 * width);
+
+                if offset > width {
+                    max - (offset - width)
+                } else {
+                    min + offset
+                }
+            }
+            BoundaryHandler::Wrap if width > 0.0 => min + (value - min).rem_euclid(width),
+            BoundaryHandler::Reflect | BoundaryHandler::Wrap => min,
+            BoundaryHandler::RandomReinit => rng.gen_range(min..=max),
+            BoundaryHandler::Resample { max_attempts } => (0..max_attempts)
+                .map(|_| resample(rng))
+                .find(|value| (min..=max).contains(value))
+                .unwrap_or_else(|| value.clamp(min, max)),
+            BoundaryHandler::Penalty { .. } => value,
+        }
+    }
+
+    /// Returns the `fitness` of the individual made worse by its bounds violation, for the
+    /// `Penalty` handler. The other handlers return the fitness as it is.
+    pub fn penalize<T: Individual>(
+        &self,
+        fitness: f64,
+        individual: &T,
+        objective: Objective,
+    ) -> f64 {
+        match *self {
+            BoundaryHandler::Penalty { coefficient } => {
+                fitness - objective.oriented(coefficient * individual.bounds_violation())
+            }
+            _ => fitness,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use crate::prelude::*;
+
+    fn repair(handler: BoundaryHandler, value: f64) -> f64 {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+
+        handler.repair(value, (0.0, 1.0), &mut rng, |rng| rng.gen_range(-1.0..1.0))
+    }
+
+    #[test]
+    fn repairs_the_genes_out_of_the_bounds() {
+        assert_eq!(repair(BoundaryHandler::Clamp, -0.5), 0.0);
+        assert_eq!(repair(BoundaryHandler::Reflect, -0.25), 0.25);
+        assert_eq!(repair(BoundaryHandler::Reflect, 2.5), 0.5);
+        assert_eq!(repair(BoundaryHandler::Wrap, -0.25), 0.75);
+        assert_eq!(repair(BoundaryHandler::Penalty { coefficient: 1.0 }, 1.5), 1.5);
+
+        for handler in [
+            BoundaryHandler::RandomReinit,
+            BoundaryHandler::Resample { max_attempts: 100 },
+            BoundaryHandler::Resample { max_attempts: 0 },
+        ] {
+            assert!((0.0..=1.0).contains(&repair(handler, 3.0)));
+        }
+
+        for handler in [BoundaryHandler::Reflect, BoundaryHandler::Wrap] {
+            assert_eq!(repair(handler, 0.5), 0.5);
+        }
+    }
+
+    #[test]
+    fn penalizes_by_the_objective() {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut individual = Real::generate_member(2, &(0.0, 1.0).into(), &mut rng);
+        individual.chromosome = vec![1.5, -1.0];
+
+        let penalty = BoundaryHandler::Penalty { coefficient: 2.0 };
+
+        assert_eq!(penalty.penalize(10.0, &individual, Objective::Maximize), 7.0);
+        assert_eq!(penalty.penalize(10.0, &individual, Objective::Minimize), 13.0);
+        assert_eq!(BoundaryHandler::Clamp.penalize(10.0, &individual, Objective::Maximize), 10.0);
+    }
+}
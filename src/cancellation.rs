@@ -0,0 +1,45 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// # Cancellation Token
+///
+/// Aborts running evolutions from another thread, like a UI or a service handling a request.
+/// Pass a clone of it to the [`EvolutionBuilder`](crate::EvolutionBuilder) with
+/// `with_cancellation` and call [`cancel`](CancellationToken::cancel) to stop every evolution
+/// built with it.
+///
+/// The evolution checks it between the generations and between the fitness evaluations. A
+/// generation cancelled while being evaluated is discarded, so the population, the best
+/// individual and the metrics are the ones of the last complete generation.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// let token = CancellationToken::new();
+/// let handle = token.clone();
+///
+/// std::thread::spawn(move || handle.cancel()).join().unwrap();
+///
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the evolutions to stop. It can't be undone.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
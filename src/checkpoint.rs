@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::constraints::ConstraintState;
+use crate::metrics::{Diversity, Steps};
+use crate::{population::Individual, rng::EvolutionRng, Evolution};
+
+pub(crate) type CheckpointWriter<T> = fn(&Evolution<T>, &Path) -> Result<(), Box<dyn Error>>;
+
+/// Where and how often an evolution writes its checkpoints while running.
+pub(crate) struct CheckpointConfig<T: Individual> {
+    pub every: u32,
+    pub path: PathBuf,
+    pub write: CheckpointWriter<T>,
+}
+
+impl<T: Individual> Clone for CheckpointConfig<T> {
+    fn clone(&self) -> Self {
+        Self {
+            every: self.every,
+            path: self.path.clone(),
+            write: self.write,
+        }
+    }
+}
+
+/// The state of an evolution saved to disk: the current population with its fitness, the metrics
+/// history, the random number generator and the weight of the adaptive penalty.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Checkpoint<T> {
+    pub population: Vec<T>,
+    #[serde(with = "float")]
+    pub best_fitnesses: Vec<f64>,
+    #[serde(with = "float")]
+    pub avg_fitnesses: Vec<f64>,
+    #[serde(default, with = "float")]
+    pub worst_fitnesses: Vec<f64>,
+    #[serde(default, with = "float")]
+    pub median_fitnesses: Vec<f64>,
+    #[serde(default, with = "float")]
+    pub percentile_fitnesses: Vec<Vec<f64>>,
+    #[serde(default, with = "float")]
+    pub fitness_std_devs: Vec<f64>,
+    pub iterations: u32,
+    pub gens_without_improvement: u32,
+    #[serde(default)]
+    pub best_generation: u32,
+    #[serde(default)]
+    pub best_evaluations: u64,
+    #[serde(default)]
+    pub evaluations: u64,
+    #[serde(default)]
+    pub generation_evaluations: Vec<u64>,
+    #[serde(default)]
+    pub cache_hits: u64,
+    #[serde(default)]
+    pub cache_misses: u64,
+    /// The cached fitness by chromosome hash, from the least to the most recently used.
+    #[serde(default, with = "float")]
+    pub cache: Vec<(u64, f64)>,
+    #[serde(default)]
+    pub diversities: Vec<Diversity>,
+    /// The nanoseconds spent in each step.
+    #[serde(default)]
+    pub step_times: BTreeMap<Steps, u128>,
+    pub rng: EvolutionRng,
+    #[serde(default)]
+    pub constraints: Option<ConstraintState>,
+}
+
+impl<T: Serialize> Checkpoint<T> {
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        // Writes to a temporary file first so a crash while writing never leaves a corrupted
+        // checkpoint behind.
+        let tmp_path = path.with_extension("tmp");
+
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+
+        std::fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+}
+
+impl<T: DeserializeOwned> Checkpoint<T> {
+    pub fn read(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+}
+
+/// Writes the floats of a checkpoint losslessly. JSON has no NaN nor infinity, which `serde_json`
+/// writes as `null` and then can't read back, so they're written as the strings `"NaN"`, `"inf"`
+/// and `"-inf"`. Use it with `#[serde(with = "crate::checkpoint::float")]` on a float, or an
+/// `Option`, a `Vec` or a keyed pair of them.
+pub(crate) mod float {
+    use std::fmt;
+
+    use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+    use serde::{Deserialize, Serialize, Serializer};
+
+    /// A float written losslessly.
+    pub struct Float(f64);
+
+    impl Serialize for Float {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self.0 {
+                value if value.is_finite() => serializer.serialize_f64(value),
+                value if value.is_nan() => serializer.serialize_str("NaN"),
+                value if value > 0.0 => serializer.serialize_str("inf"),
+                _ => serializer.serialize_str("-inf"),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Float {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(FloatVisitor).map(Float)
+        }
+    }
+
+    struct FloatVisitor;
+
+    impl Visitor<'_> for FloatVisitor {
+        type Value = f64;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a number, \"NaN\", \"inf\" or \"-inf\"")
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<f64, E> {
+            Ok(value)
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<f64, E> {
+            Ok(value as f64)
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<f64, E> {
+            Ok(value as f64)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<f64, E> {
+            match value {
+                "NaN" => Ok(f64::NAN),
+                "inf" => Ok(f64::INFINITY),
+                "-inf" => Ok(f64::NEG_INFINITY),
+                _ => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+            }
+        }
+    }
+
+    /// A value made of floats, with its floats written losslessly.
+    pub trait Lossless: Sized {
+        type Repr: Serialize + DeserializeOwned;
+
+        fn to_repr(&self) -> Self::Repr;
+
+        fn from_repr(repr: Self::Repr) -> Self;
+    }
+
+    impl Lossless for f64 {
+        type Repr = Float;
+
+        fn to_repr(&self) -> Float {
+            Float(*self)
+        }
+
+        fn from_repr(repr: Float) -> Self {
+            repr.0
+        }
+    }
+
+    impl<V: Lossless> Lossless for Option<V> {
+        type Repr = Option<V::Repr>;
+
+        fn to_repr(&self) -> Self::Repr {
+            self.as_ref().map(V::to_repr)
+        }
+
+        fn from_repr(repr: Self::Repr) -> Self {
+            repr.map(V::from_repr)
+        }
+    }
+
+    impl<V: Lossless> Lossless for Vec<V> {
+        type Repr = Vec<V::Repr>;
+
+        fn to_repr(&self) -> Self::Repr {
+            self.iter().map(V::to_repr).collect()
+        }
+
+        fn from_repr(repr: Self::Repr) -> Self {
+            repr.into_iter().map(V::from_repr).collect()
+        }
+    }
+
+    impl<V: Lossless> Lossless for (u64, V) {
+        type Repr = (u64, V::Repr);
+
+        fn to_repr(&self) -> Self::Repr {
+            (self.0, self.1.to_repr())
+        }
+
+        fn from_repr(repr: Self::Repr) -> Self {
+            (repr.0, V::from_repr(repr.1))
+        }
+    }
+
+    pub fn serialize<V, S>(value: &V, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        V: Lossless,
+        S: Serializer,
+    {
+        value.to_repr().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, V, D>(deserializer: D) -> Result<V, D::Error>
+    where
+        V: Lossless,
+        D: Deserializer<'de>,
+    {
+        V::Repr::deserialize(deserializer).map(V::from_repr)
+    }
+}
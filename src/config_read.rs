@@ -1,18 +1,424 @@
+use serde::Serialize;
 use serde_derive::Deserialize;
+use std::error::Error;
 use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::config_registry::OperatorRegistry;
+use crate::crossover::*;
+use crate::experiment_runner::ExperimentRunner;
+use crate::mutation::*;
+use crate::plotting::{ChartOptions, ChartScale, ChartSeries};
+use crate::replacement::*;
+use crate::selection::*;
 use crate::{
     evolution::EvolutionConfig,
-    population::{Bin, GeneCod, IntPerm},
+    evolution_builder::EvolutionBuilder,
+    objective::Objective,
+    population::{Bin, Bounds, GeneCod, Int, IntPerm, Real},
+    stop_condition::StopCondition,
 };
 
+/// The `[config]` section of a config file. The genes of the `Real` and `Int` individuals are
+/// bounded by either the `range`, `[min, max]` for every gene, or the `bounds`, one `[min, max]`
+/// for each gene. The `elitism`, the `seed` and the `objective`, `'Maximize'` or `'Minimize'`,
+/// are optional:
+///
+/// ```toml
+/// [config]
+/// runs = 100
+/// gene_cod = 'Real'
+/// population_size = 30
+/// dimension = 2
+/// bounds = [[0.0, 24.0], [0.0, 16.0]]
+/// elitism = 2
+/// seed = 42
+/// objective = 'Minimize'
+/// ```
 #[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct RawConfig {
     pub runs: i32,
     pub gene_cod: GeneCod,
     pub range: Option<Vec<f64>>,
+    pub bounds: Option<Vec<(f64, f64)>>,
     pub population_size: u32,
     pub dimension: u32,
+    pub elitism: Option<u32>,
+    pub seed: Option<u64>,
+    pub objective: Option<Objective>,
+}
+
+impl RawConfig {
+    /// Returns the `bounds` of the config, or else its `range`.
+    pub fn bounds(&self) -> Result<Bounds<f64>, String> {
+        match (&self.bounds, &self.range) {
+            (Some(bounds), _) => Ok(bounds.clone().into()),
+            (None, Some(range)) if range.len() == 2 => Ok(Bounds::Uniform(range[0], range[1])),
+            (None, Some(range)) => Err(format!(
+                "The range must be [min, max], got {} values",
+                range.len()
+            )),
+            (None, None) => Err("The config has no range nor bounds".to_string()),
+        }
+    }
+}
+
+/// The `[selection]` section of a config file: the `name` of the selection and its fields.
+///
+/// ```toml
+/// [selection]
+/// name = 'Tournament'
+/// k = 3
+/// kp = 0.9
+/// ```
+///
+/// The roulette and the stochastic universal sampling take their `scaling` as in
+/// `scaling = { Linear = { c = 1.5 } }`, see [`FitnessScaling`].
+#[derive(Clone, Deserialize)]
+#[serde(tag = "name")]
+pub enum SelectionConfig {
+    Tournament(TournamentSelection),
+    Roulette(RouletteSelection),
+    StochasticUniversalSampling(StochasticUniversalSamplingSelection),
+    Rank,
+    DoNothing,
+}
+
+/// The `[crossover]` section of a config file: the `name` of the crossover and its fields, which
+/// default to the ones of the crossover. Not every crossover works with every [`GeneCod`], see
+/// [`OperatorRegistry`].
+///
+/// ```toml
+/// [crossover]
+/// name = 'NPoints'
+/// crossover_rate = 0.9
+/// n_points = 2
+/// ```
+#[derive(Clone, Deserialize)]
+#[serde(tag = "name")]
+pub enum CrossoverConfig {
+    NPoints(NPointsCrossover),
+    Uniform(UniformCrossover),
+    Arithmetic(ArithmeticCrossover),
+    Blend(BlendCrossover),
+    Linear(LinearCrossover),
+    SimulatedBinary(SimulatedBinaryCrossover),
+    Cycle(CycleCrossover),
+    Ordered(OrderedCrossover),
+    PartiallyMapped(PartiallyMappedCrossover),
+    DoNothing,
+}
+
+/// The `[mutation]` section of a config file: the `name` of the mutation and its fields, which
+/// default to the ones of the mutation. Not every mutation works with every [`GeneCod`], see
+/// [`OperatorRegistry`].
+///
+/// ```toml
+/// [mutation]
+/// name = 'Gaussian'
+/// mutation_rate = 0.1
+/// sigma = 0.5
+/// ```
+#[derive(Clone, Deserialize)]
+#[serde(tag = "name")]
+pub enum MutationConfig {
+    BitFlip(BitFlipMutation),
+    RandomResetting(RandomResettingMutation),
+    Delta(DeltaMutation),
+    Gaussian(GaussianMutation),
+    Swap(SwapMutation),
+    Insertion(InsertionMutation),
+    DoNothing,
+}
+
+/// The `[replacement]` section of a config file: the `name` of the replacement and its fields.
+///
+/// ```toml
+/// [replacement]
+/// name = 'SteadyState'
+/// offsprings = 4
+/// target = 'Oldest'
+/// ```
+#[derive(Clone, Deserialize)]
+#[serde(tag = "name")]
+pub enum ReplacementConfig {
+    Generational,
+    GenerationGap(GenerationGapReplacement),
+    MuPlusLambda(MuPlusLambdaReplacement),
+    MuCommaLambda(MuCommaLambdaReplacement),
+    SteadyState(SteadyStateReplacement),
+}
+
+/// The `[stop]` section of a config file. The evolution stops once any of the conditions set is
+/// met, see [`StopCondition`]. The `time_limit` is in seconds.
+///
+/// ```toml
+/// [stop]
+/// max_generations = 1000
+/// target_fitness = 0.0
+/// ```
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StopConfig {
+    pub max_generations: Option<u32>,
+    pub max_evaluations: Option<u64>,
+    pub time_limit: Option<f64>,
+    pub target_fitness: Option<f64>,
+    pub stagnation: Option<u32>,
+    pub diversity_collapse: Option<f64>,
+}
+
+impl TryFrom<&StopConfig> for StopCondition {
+    type Error = String;
+
+    fn try_from(config: &StopConfig) -> Result<Self, Self::Error> {
+        let conditions: Vec<StopCondition> = [
+            config.max_generations.map(StopCondition::MaxGenerations),
+            config.max_evaluations.map(StopCondition::MaxEvaluations),
+            config
+                .time_limit
+                .map(|seconds| StopCondition::TimeLimit(Duration::from_secs_f64(seconds))),
+            config.target_fitness.map(StopCondition::TargetFitness),
+            config.stagnation.map(StopCondition::Stagnation),
+            config.diversity_collapse.map(StopCondition::DiversityCollapse),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        match conditions.len() {
+            0 => Err("The stop section has no condition".to_string()),
+            1 => Ok(conditions.into_iter().next().unwrap()),
+            _ => Ok(StopCondition::any(conditions)),
+        }
+    }
+}
+
+/// The `[output]` section of a config file, for the [`ExperimentRunner`]. The results go to
+/// `results/<name>`, `experiment` by default. The `title` is the one of the evolution and of its
+/// chart, and the `chart` is its path, as in [`ChartOptions`]. The `csv` is the directory the
+/// runs are written to, see [`ExperimentRunner::write_csv`], and the `json_lines` the file, see
+/// [`ExperimentRunner::write_json_lines`].
+///
+/// ```toml
+/// [output]
+/// name = 'tsp'
+/// title = 'TSP'
+/// chart = 'results/tsp/fitness.svg'
+/// scale = 'Log'
+/// series = ['Best', 'Median', 'Worst']
+/// csv = 'results/tsp'
+/// ```
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OutputConfig {
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub chart: Option<PathBuf>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub scale: Option<ChartScale>,
+    pub series: Option<Vec<ChartSeries>>,
+    pub csv: Option<PathBuf>,
+    pub json_lines: Option<PathBuf>,
+}
+
+/// # Experiment Config
+///
+/// A whole config file: the `[config]` section, see [`RawConfig`], and the optional
+/// `[selection]`, `[crossover]`, `[mutation]`, `[replacement]`, `[stop]` and `[output]`
+/// sections. [`ExperimentConfig::builder`] turns it into an [`EvolutionBuilder`], so only the
+/// fitness is left to the code and the experiment can be changed without recompiling.
+///
+/// # Example
+///
+/// ```no_run
+/// use evolutionary::prelude::*;
+/// # #[derive(Clone)]
+/// # struct YourFitness;
+/// # impl Fitness<IntPerm> for YourFitness {
+/// #    fn calculate_fitness(&self, individual: &IntPerm) -> f64 { 0.0 }
+/// # }
+///
+/// // [config]
+/// // runs = 30
+/// // gene_cod = 'IntPerm'
+/// // population_size = 30
+/// // dimension = 64
+/// // elitism = 2
+/// //
+/// // [selection]
+/// // name = 'Tournament'
+/// // k = 3
+/// //
+/// // [crossover]
+/// // name = 'PartiallyMapped'
+/// // crossover_rate = 0.8
+/// //
+/// // [mutation]
+/// // name = 'Swap'
+/// // mutation_rate = 0.02
+/// //
+/// // [stop]
+/// // max_generations = 1000
+/// let config = read_experiment_config("Config.toml").unwrap();
+///
+/// let builder = config.builder::<IntPerm>().unwrap().with_fitness(YourFitness);
+///
+/// let mut runner = config.runner(builder).unwrap();
+/// runner.run().unwrap();
+/// config.write_results(&runner).unwrap();
+/// ```
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentConfig {
+    pub config: RawConfig,
+    pub selection: Option<SelectionConfig>,
+    pub crossover: Option<CrossoverConfig>,
+    pub mutation: Option<MutationConfig>,
+    pub replacement: Option<ReplacementConfig>,
+    pub stop: Option<StopConfig>,
+    #[serde(default)]
+    pub output: OutputConfig,
+}
+
+impl ExperimentConfig {
+    /// Returns an [`EvolutionBuilder`] with the population, the operators, the elitism, the seed,
+    /// the objective, the stop condition and the title of the config. The sections that are
+    /// missing are left unset, as the fitness, to be set in the code.
+    ///
+    /// Fails when the `gene_cod` of the config isn't the one of `T`, or when an operator doesn't
+    /// work with it or has an invalid parameter, like a rate out of `[0, 1]`.
+    pub fn builder<T: OperatorRegistry>(&self) -> Result<EvolutionBuilder<T>, String> {
+        if self.config.gene_cod != T::GENE_COD {
+            return Err(format!(
+                "The config is for {:?} genes, not {:?}",
+                self.config.gene_cod,
+                T::GENE_COD
+            ));
+        }
+
+        let mut builder = EvolutionBuilder::from_config(T::evolution_config(self.config.clone())?);
+
+        if let Some(elitism) = self.config.elitism {
+            builder = builder.with_elitism(elitism);
+        }
+        if let Some(seed) = self.config.seed {
+            builder = builder.with_seed(seed);
+        }
+        if let Some(objective) = self.config.objective {
+            builder = builder.with_objective(objective);
+        }
+        if let Some(title) = &self.output.title {
+            builder = builder.with_title(title.clone());
+        }
+
+        if let Some(selection) = &self.selection {
+            builder = match selection.clone() {
+                SelectionConfig::Tournament(selection) => builder.with_selection(selection),
+                SelectionConfig::Roulette(selection) => builder.with_selection(selection),
+                SelectionConfig::StochasticUniversalSampling(selection) => {
+                    builder.with_selection(selection)
+                }
+                SelectionConfig::Rank => builder.with_selection(RankSelection),
+                SelectionConfig::DoNothing => builder.with_selection(DoNothingSelection),
+            };
+        }
+        if let Some(crossover) = &self.crossover {
+            builder = T::with_crossover(builder, crossover)?;
+        }
+        if let Some(mutation) = &self.mutation {
+            builder = T::with_mutation(builder, mutation)?;
+        }
+        if let Some(replacement) = &self.replacement {
+            builder = match replacement.clone() {
+                ReplacementConfig::Generational => {
+                    builder.with_replacement(GenerationalReplacement)
+                }
+                ReplacementConfig::GenerationGap(replacement) => {
+                    builder.with_replacement(replacement)
+                }
+                ReplacementConfig::MuPlusLambda(replacement) => {
+                    builder.with_replacement(replacement)
+                }
+                ReplacementConfig::MuCommaLambda(replacement) => {
+                    builder.with_replacement(replacement)
+                }
+                ReplacementConfig::SteadyState(replacement) => {
+                    builder.with_replacement(replacement)
+                }
+            };
+        }
+        if let Some(stop) = &self.stop {
+            builder = builder.with_stop(stop.try_into()?);
+        }
+
+        builder.check_operators()?;
+
+        Ok(builder)
+    }
+
+    /// Returns an [`ExperimentRunner`] of the `builder` with the runs, the name and the chart of
+    /// the config.
+    pub fn runner<T: OperatorRegistry>(
+        &self,
+        builder: EvolutionBuilder<T>,
+    ) -> Result<ExperimentRunner<T>, String> {
+        let runs = u32::try_from(self.config.runs)
+            .map_err(|_| format!("The runs can't be negative, got {}", self.config.runs))?;
+        let output = &self.output;
+        let name = output.name.clone().unwrap_or("experiment".to_string());
+
+        let mut runner = ExperimentRunner::new(name.clone(), runs, builder);
+
+        let customized = output.chart.is_some()
+            || output.width.is_some()
+            || output.height.is_some()
+            || output.scale.is_some()
+            || output.series.is_some();
+        if customized {
+            let path = output
+                .chart
+                .clone()
+                .unwrap_or(PathBuf::from(format!("results/{}/{}.png", name, name)));
+            let mut options = ChartOptions::new(path, output.title.as_ref().unwrap_or(&name));
+
+            if let (Some(width), Some(height)) = (output.width, output.height) {
+                options = options.with_size(width, height);
+            } else if output.width.is_some() || output.height.is_some() {
+                return Err("The chart needs both the width and the height".to_string());
+            }
+            if let Some(scale) = output.scale {
+                options = options.with_scale(scale);
+            }
+            if let Some(series) = &output.series {
+                options = options.with_series(series.clone());
+            }
+
+            runner = runner.with_chart_options(options);
+        }
+
+        Ok(runner)
+    }
+
+    /// Writes the runs of the `runner` to the `csv` directory and to the `json_lines` file of the
+    /// config, when they are set.
+    pub fn write_results<T>(&self, runner: &ExperimentRunner<T>) -> Result<(), Box<dyn Error>>
+    where
+        T: OperatorRegistry,
+        T::Gene: Serialize,
+    {
+        if let Some(dir) = &self.output.csv {
+            runner.write_csv(dir)?;
+        }
+        if let Some(path) = &self.output.json_lines {
+            runner.write_json_lines(path)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl Into<EvolutionConfig<Bin>> for RawConfig {
@@ -37,6 +443,58 @@ impl Into<EvolutionConfig<IntPerm>> for RawConfig {
     }
 }
 
+impl TryFrom<RawConfig> for EvolutionConfig<Real> {
+    type Error = String;
+
+    fn try_from(config: RawConfig) -> Result<Self, Self::Error> {
+        Ok(EvolutionConfig {
+            dimension: config.dimension,
+            population_size: config.population_size,
+            range: config.bounds()?,
+            gene_cod: GeneCod::Real,
+        })
+    }
+}
+
+impl TryFrom<RawConfig> for EvolutionConfig<Int> {
+    type Error = String;
+
+    fn try_from(config: RawConfig) -> Result<Self, Self::Error> {
+        let range = match config.bounds()? {
+            Bounds::Uniform(min, max) => Bounds::Uniform(to_int(min)?, to_int(max)?),
+            Bounds::PerGene(bounds) => Bounds::PerGene(
+                bounds
+                    .iter()
+                    .map(|&(min, max)| Ok((to_int(min)?, to_int(max)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+        };
+
+        Ok(EvolutionConfig {
+            dimension: config.dimension,
+            population_size: config.population_size,
+            range,
+            gene_cod: GeneCod::Int,
+        })
+    }
+}
+
+fn to_int(value: f64) -> Result<i64, String> {
+    if value.fract() == 0.0 {
+        Ok(value as i64)
+    } else {
+        Err(format!("The bounds of Int genes must be integers, got {}", value))
+    }
+}
+
+/// Reads a whole config file, see [`ExperimentConfig`].
+pub fn read_experiment_config(file_name: &str) -> Result<ExperimentConfig, String> {
+    let contents = fs::read_to_string(file_name)
+        .map_err(|error| format!("Failed to read config file: {} <{}>", error, file_name))?;
+
+    toml::from_str(&contents).map_err(|error| format!("Failed to parse config file: {}", error))
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Data {
     config: RawConfig,
@@ -62,3 +520,152 @@ pub fn read_config(file_name: &str) -> Option<RawConfig> {
         None => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Data, ExperimentConfig, RawConfig};
+    use crate::{
+        evolution::EvolutionConfig,
+        fitness::Fitness,
+        population::{Bin, Bounds, Int, IntPerm, Real},
+    };
+
+    fn parse(bounds: &str) -> RawConfig {
+        let contents = format!(
+            "[config]\nruns = 10\ngene_cod = 'Real'\npopulation_size = 10\ndimension = 2\n{}",
+            bounds
+        );
+
+        toml::from_str::<Data>(&contents).unwrap().config
+    }
+
+    #[test]
+    fn reads_the_bounds() {
+        let config: EvolutionConfig<Real> =
+            parse("bounds = [[0.0, 24.0], [0.0, 16.0]]").try_into().unwrap();
+        assert_eq!(config.range, vec![(0.0, 24.0), (0.0, 16.0)].into());
+
+        let config: EvolutionConfig<Int> = parse("range = [-5.0, 5.0]").try_into().unwrap();
+        assert_eq!(config.range, Bounds::Uniform(-5, 5));
+
+        let config: Result<EvolutionConfig<Int>, _> = parse("range = [0.0, 0.5]").try_into();
+        assert!(config.is_err());
+
+        let config: Result<EvolutionConfig<Real>, _> = parse("").try_into();
+        assert!(config.is_err());
+    }
+
+    #[derive(Clone)]
+    struct SumFitness;
+
+    impl Fitness<Real> for SumFitness {
+        fn calculate_fitness(&self, individual: &Real) -> f64 {
+            individual.chromosome.iter().sum()
+        }
+    }
+
+    const FULL: &str = "
+        [config]
+        runs = 3
+        gene_cod = 'Real'
+        population_size = 20
+        dimension = 4
+        range = [-1.0, 1.0]
+        elitism = 2
+        seed = 7
+        objective = 'Minimize'
+
+        [selection]
+        name = 'Tournament'
+        k = 3
+
+        [crossover]
+        name = 'SimulatedBinary'
+        eta = 10.0
+
+        [mutation]
+        name = 'Gaussian'
+        sigma = 0.2
+
+        [replacement]
+        name = 'SteadyState'
+        offsprings = 4
+        target = 'Oldest'
+
+        [stop]
+        max_generations = 15
+        target_fitness = -100.0
+
+        [output]
+        name = 'config_test'
+        scale = 'Log'
+        series = ['Best', 'Median']
+    ";
+
+    fn experiment(contents: &str) -> ExperimentConfig {
+        toml::from_str(contents).unwrap()
+    }
+
+    #[test]
+    fn builds_the_evolution() {
+        let config = experiment(FULL);
+        let builder = config.builder::<Real>().unwrap().with_fitness(SumFitness);
+
+        let mut evolution = builder.build().unwrap();
+        evolution.run().unwrap();
+
+        assert_eq!(evolution.metrics.iterations, 15);
+        assert!(evolution.current_best_fitness() < 0.0);
+        assert!(evolution.current_best().chromosome.iter().all(|gene| gene.abs() <= 1.0));
+
+        assert!(config.runner(builder).is_ok());
+    }
+
+    #[test]
+    fn checks_the_operators() {
+        let error = |contents: &str| match experiment(contents).builder::<Bin>() {
+            Ok(_) => panic!("The config should fail"),
+            Err(error) => error,
+        };
+        let bin = "[config]\nruns = 1\ngene_cod = 'Bin'\npopulation_size = 10\ndimension = 8\n";
+
+        assert_eq!(
+            error(&format!("{}[crossover]\nname = 'PartiallyMapped'", bin)),
+            "The PartiallyMapped crossover doesn't work with Bin genes"
+        );
+        assert_eq!(
+            error(&format!("{}[mutation]\nname = 'Gaussian'", bin)),
+            "The Gaussian mutation doesn't work with Bin genes"
+        );
+        assert_eq!(error(&format!("{}[stop]", bin)), "The stop section has no condition");
+        assert_eq!(
+            error(&format!("{}[crossover]\nname = 'Uniform'\ncrossover_rate = 1.5", bin)),
+            "The crossover rate must be in [0, 1], got 1.5"
+        );
+        assert_eq!(
+            error(&format!("{}[mutation]\nname = 'BitFlip'\nmutation_rate = -0.1", bin)),
+            "The mutation rate must be in [0, 1], got -0.1"
+        );
+        assert!(experiment(bin).builder::<IntPerm>().is_err());
+        assert!(experiment(FULL).builder::<Int>().is_err());
+
+        let builder = experiment(&format!("{}[mutation]\nname = 'BitFlip'", bin)).builder::<Bin>();
+        assert!(builder.is_ok());
+
+        // Unknown operators and fields are rejected when parsing.
+        let parse = |contents: String| toml::from_str::<ExperimentConfig>(&contents);
+        assert!(parse(format!("{}[mutation]\nname = 'Flip'", bin)).is_err());
+        assert!(parse(format!("{}[mutation]\nname = 'BitFlip'\nmutation_rat = 0.1", bin)).is_err());
+        assert!(parse(format!("{}[output]\nchart_path = 'a.png'", bin)).is_err());
+        assert!(parse(format!("{}elitsm = 2", bin)).is_err());
+        assert!(parse(format!("{}[mutaton]\nname = 'BitFlip'", bin)).is_err());
+
+        assert_eq!(
+            error(&format!("{}[selection]\nname = 'Tournament'\nk = 1", bin)),
+            "The tournament size must be at least 2, got 1"
+        );
+
+        let negative = experiment(&bin.replace("runs = 1", "runs = -1"));
+        assert!(negative.runner(negative.builder::<Bin>().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::config_read::{CrossoverConfig, MutationConfig, RawConfig};
+use crate::crossover::*;
+use crate::mutation::*;
+use crate::{
+    evolution::EvolutionConfig,
+    evolution_builder::EvolutionBuilder,
+    population::{Bin, GeneCod, Individual, Int, IntPerm, Real},
+};
+
+/// # Operator Registry
+///
+/// Turns the `[config]`, `[crossover]` and `[mutation]` sections of a config file into the
+/// population and the operators of an individual, see
+/// [`ExperimentConfig::builder`](crate::config_read::ExperimentConfig::builder). The selections
+/// and the replacements work with every individual.
+///
+/// - `Bin`: the `NPoints` and `Uniform` crossovers and the `BitFlip` and `Swap` mutations.
+/// - `Int`: the `NPoints` and `Uniform` crossovers and the `RandomResetting` and `Swap`
+///   mutations.
+/// - `Real`: the `NPoints`, `Uniform`, `Arithmetic`, `Blend`, `Linear` and `SimulatedBinary`
+///   crossovers and the `RandomResetting`, `Delta`, `Gaussian` and `Swap` mutations.
+/// - `IntPerm`: the `Cycle`, `Ordered` and `PartiallyMapped` crossovers and the `Swap` and
+///   `Insertion` mutations.
+///
+/// Besides `DoNothing`, which works with all of them. The `Tree` individuals aren't registered, as
+/// their primitives are defined in the code.
+pub trait OperatorRegistry: Individual {
+    const GENE_COD: GeneCod;
+
+    fn evolution_config(config: RawConfig) -> Result<EvolutionConfig<Self>, String>;
+
+    fn with_crossover(
+        builder: EvolutionBuilder<Self>,
+        crossover: &CrossoverConfig,
+    ) -> Result<EvolutionBuilder<Self>, String>;
+
+    fn with_mutation(
+        builder: EvolutionBuilder<Self>,
+        mutation: &MutationConfig,
+    ) -> Result<EvolutionBuilder<Self>, String>;
+}
+
+impl OperatorRegistry for Bin {
+    const GENE_COD: GeneCod = GeneCod::Bin;
+
+    fn evolution_config(config: RawConfig) -> Result<EvolutionConfig<Self>, String> {
+        Ok(config.into())
+    }
+
+    fn with_crossover(
+        builder: EvolutionBuilder<Self>,
+        crossover: &CrossoverConfig,
+    ) -> Result<EvolutionBuilder<Self>, String> {
+        match crossover.clone() {
+            CrossoverConfig::NPoints(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::Uniform(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::DoNothing => Ok(builder.with_crossover(DoNothingCrossover)),
+            crossover => Err(unsupported("crossover", crossover_name(&crossover), Self::GENE_COD)),
+        }
+    }
+
+    fn with_mutation(
+        builder: EvolutionBuilder<Self>,
+        mutation: &MutationConfig,
+    ) -> Result<EvolutionBuilder<Self>, String> {
+        match mutation.clone() {
+            MutationConfig::BitFlip(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::Swap(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::DoNothing => Ok(builder.with_mutation(DoNothingMutation)),
+            mutation => Err(unsupported("mutation", mutation_name(&mutation), Self::GENE_COD)),
+        }
+    }
+}
+
+impl OperatorRegistry for Int {
+    const GENE_COD: GeneCod = GeneCod::Int;
+
+    fn evolution_config(config: RawConfig) -> Result<EvolutionConfig<Self>, String> {
+        config.try_into()
+    }
+
+    fn with_crossover(
+        builder: EvolutionBuilder<Self>,
+        crossover: &CrossoverConfig,
+    ) -> Result<EvolutionBuilder<Self>, String> {
+        match crossover.clone() {
+            CrossoverConfig::NPoints(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::Uniform(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::DoNothing => Ok(builder.with_crossover(DoNothingCrossover)),
+            crossover => Err(unsupported("crossover", crossover_name(&crossover), Self::GENE_COD)),
+        }
+    }
+
+    fn with_mutation(
+        builder: EvolutionBuilder<Self>,
+        mutation: &MutationConfig,
+    ) -> Result<EvolutionBuilder<Self>, String> {
+        match mutation.clone() {
+            MutationConfig::RandomResetting(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::Swap(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::DoNothing => Ok(builder.with_mutation(DoNothingMutation)),
+            mutation => Err(unsupported("mutation", mutation_name(&mutation), Self::GENE_COD)),
+        }
+    }
+}
+
+impl OperatorRegistry for Real {
+    const GENE_COD: GeneCod = GeneCod::Real;
+
+    fn evolution_config(config: RawConfig) -> Result<EvolutionConfig<Self>, String> {
+        config.try_into()
+    }
+
+    fn with_crossover(
+        builder: EvolutionBuilder<Self>,
+        crossover: &CrossoverConfig,
+    ) -> Result<EvolutionBuilder<Self>, String> {
+        match crossover.clone() {
+            CrossoverConfig::NPoints(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::Uniform(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::Arithmetic(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::Blend(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::Linear(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::SimulatedBinary(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::DoNothing => Ok(builder.with_crossover(DoNothingCrossover)),
+            crossover => Err(unsupported("crossover", crossover_name(&crossover), Self::GENE_COD)),
+        }
+    }
+
+    fn with_mutation(
+        builder: EvolutionBuilder<Self>,
+        mutation: &MutationConfig,
+    ) -> Result<EvolutionBuilder<Self>, String> {
+        match mutation.clone() {
+            MutationConfig::RandomResetting(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::Delta(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::Gaussian(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::Swap(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::DoNothing => Ok(builder.with_mutation(DoNothingMutation)),
+            mutation => Err(unsupported("mutation", mutation_name(&mutation), Self::GENE_COD)),
+        }
+    }
+}
+
+impl OperatorRegistry for IntPerm {
+    const GENE_COD: GeneCod = GeneCod::IntPerm;
+
+    fn evolution_config(config: RawConfig) -> Result<EvolutionConfig<Self>, String> {
+        Ok(config.into())
+    }
+
+    fn with_crossover(
+        builder: EvolutionBuilder<Self>,
+        crossover: &CrossoverConfig,
+    ) -> Result<EvolutionBuilder<Self>, String> {
+        match crossover.clone() {
+            CrossoverConfig::Cycle(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::Ordered(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::PartiallyMapped(crossover) => Ok(builder.with_crossover(crossover)),
+            CrossoverConfig::DoNothing => Ok(builder.with_crossover(DoNothingCrossover)),
+            crossover => Err(unsupported("crossover", crossover_name(&crossover), Self::GENE_COD)),
+        }
+    }
+
+    fn with_mutation(
+        builder: EvolutionBuilder<Self>,
+        mutation: &MutationConfig,
+    ) -> Result<EvolutionBuilder<Self>, String> {
+        match mutation.clone() {
+            MutationConfig::Swap(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::Insertion(mutation) => Ok(builder.with_mutation(mutation)),
+            MutationConfig::DoNothing => Ok(builder.with_mutation(DoNothingMutation)),
+            mutation => Err(unsupported("mutation", mutation_name(&mutation), Self::GENE_COD)),
+        }
+    }
+}
+
+fn unsupported(operator: &str, name: &str, gene_cod: GeneCod) -> String {
+    format!("The {} {} doesn't work with {:?} genes", name, operator, gene_cod)
+}
+
+fn crossover_name(crossover: &CrossoverConfig) -> &'static str {
+    match crossover {
+        CrossoverConfig::NPoints(_) => "NPoints",
+        CrossoverConfig::Uniform(_) => "Uniform",
+        CrossoverConfig::Arithmetic(_) => "Arithmetic",
+        CrossoverConfig::Blend(_) => "Blend",
+        CrossoverConfig::Linear(_) => "Linear",
+        CrossoverConfig::SimulatedBinary(_) => "SimulatedBinary",
+        CrossoverConfig::Cycle(_) => "Cycle",
+        CrossoverConfig::Ordered(_) => "Ordered",
+        CrossoverConfig::PartiallyMapped(_) => "PartiallyMapped",
+        CrossoverConfig::DoNothing => "DoNothing",
+    }
+}
+
+fn mutation_name(mutation: &MutationConfig) -> &'static str {
+    match mutation {
+        MutationConfig::BitFlip(_) => "BitFlip",
+        MutationConfig::RandomResetting(_) => "RandomResetting",
+        MutationConfig::Delta(_) => "Delta",
+        MutationConfig::Gaussian(_) => "Gaussian",
+        MutationConfig::Swap(_) => "Swap",
+        MutationConfig::Insertion(_) => "Insertion",
+        MutationConfig::DoNothing => "DoNothing",
+    }
+}
@@ -0,0 +1,394 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use dyn_clone::DynClone;
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{objective::Objective, population::Individual};
+
+/// Trait that defines the constraints of a problem. An individual is feasible when all its
+/// violations are 0, and the larger a violation, the further it is from satisfying the constraint.
+/// How the infeasible individuals are treated is set by the [`ConstraintHandling`] passed along
+/// with the constraints to the [`EvolutionBuilder`](crate::EvolutionBuilder).
+///
+/// Example:
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// // The genes must add up to at most 1.
+/// #[derive(Clone)]
+/// struct MaxSum;
+///
+/// impl Constraints<Real> for MaxSum {
+///     fn violations(&self, individual: &Real) -> Vec<f64> {
+///         let sum: f64 = individual.chromosome.iter().sum();
+///
+///         vec![(sum - 1.0).max(0.0)]
+///     }
+/// }
+/// ```
+pub trait Constraints<T: Individual>: 'static + DynClone + Send + Sync {
+    /// Returns how much the individual violates each constraint, 0 for the satisfied ones.
+    fn violations(&self, individual: &T) -> Vec<f64>;
+
+    /// Repairs the individual before it's evaluated, making it feasible or closer to it. The
+    /// repaired individual replaces the original in the population. Does nothing by default.
+    fn repair(&self, _individual: &mut T) {}
+}
+
+/// # Constraint Handling
+///
+/// How the evolution treats the infeasible individuals, given the total violation `v` of their
+/// [`Constraints`].
+///
+/// - `StaticPenalty { coefficient }`: the fitness gets worse by `coefficient * v`.
+/// - `DynamicPenalty { c, alpha, beta }`: the penalty grows with the generation `t`, as
+///   `(c * t)^alpha * sum(v_i^beta)`, from Joines and Houck. Usually `c = 0.5`, `alpha = 2` and
+///   `beta = 2`.
+/// - `AdaptivePenalty { initial, increase, decrease, window }`: the fitness gets worse by
+///   `lambda * v`, where `lambda` starts at `initial` and is multiplied by `increase` when the
+///   best individual was infeasible in all the last `window` generations, or divided by
+///   `decrease` when it was feasible in all of them, from Hadj-Alouane and Bean.
+/// - `FeasibilityRules`: Deb's rules. A feasible individual is better than an infeasible one,
+///   two feasible individuals are compared by fitness and two infeasible ones by their violation.
+///   It's the default.
+/// - `StochasticRanking { pf }`: Runarsson and Yao's ranking. The [`RankSelection`] sorts the
+///   population comparing the adjacent infeasible individuals by fitness with probability `pf`,
+///   usually 0.45, and by violation otherwise. Everywhere else, the feasibility rules are used.
+///
+/// The feasibility rules and the stochastic ranking keep the fitness as it is, and are applied by
+/// the [`TournamentSelection`], the [`RankSelection`], the elitism and the best individual of the
+/// evolution. The fitness-proportional selections only see the fitness, so they should be used
+/// with the penalties.
+///
+/// [`RankSelection`]: crate::prelude::RankSelection
+/// [`TournamentSelection`]: crate::prelude::TournamentSelection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ConstraintHandling {
+    StaticPenalty {
+        coefficient: f64,
+    },
+    DynamicPenalty {
+        c: f64,
+        alpha: f64,
+        beta: f64,
+    },
+    AdaptivePenalty {
+        initial: f64,
+        increase: f64,
+        decrease: f64,
+        window: usize,
+    },
+    #[default]
+    FeasibilityRules,
+    StochasticRanking {
+        pf: f64,
+    },
+}
+
+impl ConstraintHandling {
+    /// Returns if the individuals are compared with the feasibility rules instead of by fitness.
+    pub fn uses_feasibility(&self) -> bool {
+        matches!(
+            self,
+            ConstraintHandling::FeasibilityRules | ConstraintHandling::StochasticRanking { .. }
+        )
+    }
+
+    /// Returns if the penalty of an individual changes over the generations, so its fitness must
+    /// be calculated again even if it didn't change.
+    pub(crate) fn depends_on_generation(&self) -> bool {
+        matches!(
+            self,
+            ConstraintHandling::DynamicPenalty { .. } | ConstraintHandling::AdaptivePenalty { .. }
+        )
+    }
+
+    /// Compares two individuals by how good they are: returns `Ordering::Greater` when `a` is
+    /// better than `b`.
+    pub fn compare<T: Individual>(&self, objective: Objective, a: &T, b: &T) -> Ordering {
+        if self.uses_feasibility() {
+            compare_feasibility(objective, a, b)
+        } else {
+            objective.compare(a.get_fitness(), b.get_fitness())
+        }
+    }
+}
+
+/// Compares two individuals with Deb's feasibility rules: returns `Ordering::Greater` when `a` is
+/// better than `b`.
+pub(crate) fn compare_feasibility<T: Individual>(objective: Objective, a: &T, b: &T) -> Ordering {
+    let (violation_a, violation_b) = (a.get_violation(), b.get_violation());
+
+    if violation_a == 0.0 && violation_b == 0.0 {
+        objective.compare(a.get_fitness(), b.get_fitness())
+    } else {
+        violation_b.partial_cmp(&violation_a).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Sorts the population with Runarsson and Yao's stochastic ranking, returning the indexes from
+/// the best to the worst.
+pub(crate) fn stochastic_ranking<T: Individual, R: Rng + ?Sized>(
+    population: &[T],
+    objective: Objective,
+    pf: f64,
+    rng: &mut R,
+) -> Vec<usize> {
+    let mut ranking: Vec<usize> = (0..population.len()).collect();
+
+    for _ in 0..population.len() {
+        let mut swapped = false;
+
+        for j in 0..population.len().saturating_sub(1) {
+            let (a, b) = (&population[ranking[j]], &population[ranking[j + 1]]);
+            let feasible = a.get_violation() == 0.0 && b.get_violation() == 0.0;
+
+            let worse = if feasible || rng.gen_bool(pf) {
+                objective.compare(a.get_fitness(), b.get_fitness()) == Ordering::Less
+            } else {
+                a.get_violation() > b.get_violation()
+            };
+
+            if worse {
+                ranking.swap(j, j + 1);
+                swapped = true;
+            }
+        }
+
+        if !swapped {
+            break;
+        }
+    }
+
+    ranking
+}
+
+/// The state of the adaptive penalty, saved in the checkpoints.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ConstraintState {
+    #[serde(with = "crate::checkpoint::float")]
+    pub lambda: f64,
+    pub best_was_feasible: Vec<bool>,
+}
+
+/// The constraints of an evolution with their handling, and the state of the adaptive penalty.
+pub(crate) struct ConstraintHandler<T: Individual> {
+    constraints: Box<dyn Constraints<T>>,
+    handling: ConstraintHandling,
+    lambda: f64,
+    best_was_feasible: VecDeque<bool>,
+}
+
+impl<T: Individual> Clone for ConstraintHandler<T> {
+    fn clone(&self) -> Self {
+        Self {
+            constraints: dyn_clone::clone_box(self.constraints.as_ref()),
+            handling: self.handling,
+            lambda: self.lambda,
+            best_was_feasible: self.best_was_feasible.clone(),
+        }
+    }
+}
+
+impl<T: Individual> ConstraintHandler<T> {
+    pub(crate) fn new(constraints: Box<dyn Constraints<T>>, handling: ConstraintHandling) -> Self {
+        let lambda = match handling {
+            ConstraintHandling::AdaptivePenalty { initial, .. } => initial,
+            _ => 0.0,
+        };
+
+        Self {
+            constraints,
+            handling,
+            lambda,
+            best_was_feasible: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn handling(&self) -> ConstraintHandling {
+        self.handling
+    }
+
+    pub(crate) fn state(&self) -> ConstraintState {
+        ConstraintState {
+            lambda: self.lambda,
+            best_was_feasible: self.best_was_feasible.iter().copied().collect(),
+        }
+    }
+
+    pub(crate) fn restore(&mut self, state: ConstraintState) {
+        self.lambda = state.lambda;
+        self.best_was_feasible = state.best_was_feasible.into();
+    }
+
+    /// Repairs the individual and stores its violation, returning the `fitness` penalized as the
+    /// handling says. `generation` starts at 1 for the initial population.
+    pub(crate) fn apply(
+        &self,
+        individual: &mut T,
+        fitness: impl FnOnce(&T) -> f64,
+        objective: Objective,
+        generation: u32,
+    ) -> f64 {
+        self.constraints.repair(individual);
+
+        let violations: Vec<f64> = self
+            .constraints
+            .violations(individual)
+            .into_iter()
+            .map(|violation| violation.max(0.0))
+            .collect();
+        let violation: f64 = violations.iter().sum();
+
+        individual.set_violation(violation);
+
+        let penalty = match self.handling {
+            ConstraintHandling::StaticPenalty { coefficient } => coefficient * violation,
+            ConstraintHandling::DynamicPenalty { c, alpha, beta } => {
+                (c * generation as f64).powf(alpha)
+                    * violations.iter().map(|v| v.powf(beta)).sum::<f64>()
+            }
+            ConstraintHandling::AdaptivePenalty { .. } => self.lambda * violation,
+            ConstraintHandling::FeasibilityRules | ConstraintHandling::StochasticRanking { .. } => {
+                0.0
+            }
+        };
+
+        fitness(individual) - objective.oriented(penalty)
+    }
+
+    /// Updates the weight of the adaptive penalty with the feasibility of the best individual of
+    /// the generation.
+    pub(crate) fn update(&mut self, best_is_feasible: bool) {
+        if let ConstraintHandling::AdaptivePenalty {
+            increase,
+            decrease,
+            window,
+            ..
+        } = self.handling
+        {
+            self.best_was_feasible.push_back(best_is_feasible);
+
+            if self.best_was_feasible.len() > window {
+                self.best_was_feasible.pop_front();
+            }
+
+            if self.best_was_feasible.len() == window {
+                if self.best_was_feasible.iter().all(|&feasible| feasible) {
+                    self.lambda /= decrease;
+                } else if self.best_was_feasible.iter().all(|&feasible| !feasible) {
+                    self.lambda *= increase;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::{compare_feasibility, stochastic_ranking, ConstraintHandler};
+    use crate::prelude::*;
+    use std::cmp::Ordering;
+
+    #[derive(Clone)]
+    struct MaxSum;
+
+    impl Constraints<Real> for MaxSum {
+        fn violations(&self, individual: &Real) -> Vec<f64> {
+            vec![individual.chromosome.iter().sum::<f64>() - 1.0]
+        }
+    }
+
+    fn individual(fitness: f64, violation: f64) -> Real {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut individual = Real::generate_member(1, &(0.0, 1.0).into(), &mut rng);
+
+        individual.set_fitness(fitness);
+        individual.set_violation(violation);
+        individual
+    }
+
+    fn penalized(handling: ConstraintHandling, genes: Vec<f64>, generation: u32) -> f64 {
+        let handler = ConstraintHandler::new(Box::new(MaxSum), handling);
+        let mut real = individual(0.0, 0.0);
+        real.chromosome = genes;
+
+        handler.apply(&mut real, |_| 10.0, Objective::Maximize, generation)
+    }
+
+    #[test]
+    fn follows_the_feasibility_rules() {
+        let objective = Objective::Minimize;
+
+        let feasible = individual(5.0, 0.0);
+        let better_feasible = individual(1.0, 0.0);
+        let infeasible = individual(0.0, 2.0);
+        let less_infeasible = individual(100.0, 1.0);
+
+        assert_eq!(compare_feasibility(objective, &feasible, &infeasible), Ordering::Greater);
+        assert_eq!(compare_feasibility(objective, &better_feasible, &feasible), Ordering::Greater);
+        assert_eq!(
+            compare_feasibility(objective, &less_infeasible, &infeasible),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn penalizes_the_violation() {
+        let genes = vec![1.0, 1.5];
+
+        let static_penalty = ConstraintHandling::StaticPenalty { coefficient: 2.0 };
+
+        assert_eq!(penalized(static_penalty, genes.clone(), 1), 7.0);
+        assert_eq!(penalized(static_penalty, vec![0.5], 1), 10.0);
+        assert_eq!(penalized(ConstraintHandling::FeasibilityRules, genes.clone(), 1), 10.0);
+
+        let dynamic = ConstraintHandling::DynamicPenalty {
+            c: 0.5,
+            alpha: 2.0,
+            beta: 2.0,
+        };
+        assert_eq!(penalized(dynamic, genes.clone(), 2), 10.0 - 1.5 * 1.5);
+        assert_eq!(penalized(dynamic, genes, 4), 10.0 - 4.0 * 1.5 * 1.5);
+    }
+
+    #[test]
+    fn adapts_the_penalty_weight() {
+        let handling = ConstraintHandling::AdaptivePenalty {
+            initial: 1.0,
+            increase: 2.0,
+            decrease: 4.0,
+            window: 2,
+        };
+        let mut handler = ConstraintHandler::new(Box::new(MaxSum), handling);
+
+        handler.update(false);
+        assert_eq!(handler.lambda, 1.0);
+        handler.update(false);
+        assert_eq!(handler.lambda, 2.0);
+        handler.update(true);
+        assert_eq!(handler.lambda, 2.0);
+        handler.update(true);
+        assert_eq!(handler.lambda, 0.5);
+    }
+
+    #[test]
+    fn stochastic_ranking_without_fitness_comparisons_sorts_by_violation() {
+        let population = vec![
+            individual(1.0, 3.0),
+            individual(2.0, 0.0),
+            individual(3.0, 1.0),
+            individual(4.0, 0.0),
+        ];
+        let mut rng = EvolutionRng::seed_from_u64(0);
+
+        let ranking = stochastic_ranking(&population, Objective::Maximize, 0.0, &mut rng);
+
+        assert_eq!(ranking, vec![3, 1, 2, 0]);
+    }
+}
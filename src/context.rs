@@ -0,0 +1,105 @@
+use std::cmp::Ordering;
+
+use crate::{
+    boundary::BoundaryHandler, constraints::ConstraintHandling, evolution::Evaluator,
+    objective::Objective, population::Individual, rng::EvolutionRng, Fitness,
+};
+
+/// # Context
+///
+/// What the genetic operators know about the evolution they're part of. It's passed to every
+/// [`Selection`](crate::Selection), [`Crossover`](crate::Crossover) and
+/// [`Mutation`](crate::Mutation) call.
+///
+/// - `rng`: the random number generator of the evolution. All the randomness must come from it
+///   so the evolution can be reproduced from its seed, see [`EvolutionRng`].
+/// - `objective`: whether the fitness is maximized or minimized, see [`Objective`].
+/// - `fitness`: the fitness function of the evolution, for the operators that evaluate the
+///   individuals they create, like the [`LinearCrossover`](crate::crossover::LinearCrossover).
+///   It's `None` when there's no single fitness, as in the
+///   [`Nsga2`](crate::multi_objective::Nsga2).
+/// - `boundary_handler`: what to do with the genes that the operators push out of their bounds,
+///   see [`BoundaryHandler`].
+/// - `constraint_handling`: how the infeasible individuals are treated, when the evolution has
+///   [`Constraints`](crate::Constraints). Compare the individuals with [`Context::compare`] so
+///   the feasibility rules are respected.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+/// use rand::SeedableRng;
+///
+/// let mut rng = EvolutionRng::seed_from_u64(0);
+/// let mut context = Context::new(&mut rng, Objective::Minimize);
+///
+/// let mut population = vec![Bin::new(vec![true, false])];
+/// SwapMutation::default().mutate(&mut population, &mut context);
+/// ```
+pub struct Context<'a, T: Individual> {
+    pub rng: &'a mut EvolutionRng,
+    pub objective: Objective,
+    pub fitness: Option<&'a dyn Fitness<T>>,
+    pub boundary_handler: BoundaryHandler,
+    pub constraint_handling: Option<ConstraintHandling>,
+    pub(crate) evaluator: Option<&'a Evaluator<'a, T>>,
+}
+
+impl<'a, T: Individual> Context<'a, T> {
+    pub fn new(rng: &'a mut EvolutionRng, objective: Objective) -> Self {
+        Self {
+            rng,
+            objective,
+            fitness: None,
+            boundary_handler: BoundaryHandler::default(),
+            constraint_handling: None,
+            evaluator: None,
+        }
+    }
+
+    pub fn with_fitness(mut self, fitness: &'a dyn Fitness<T>) -> Self {
+        self.fitness = Some(fitness);
+        self
+    }
+
+    pub fn with_boundary_handler(mut self, boundary_handler: BoundaryHandler) -> Self {
+        self.boundary_handler = boundary_handler;
+        self
+    }
+
+    pub fn with_constraint_handling(mut self, constraint_handling: ConstraintHandling) -> Self {
+        self.constraint_handling = Some(constraint_handling);
+        self
+    }
+
+    pub(crate) fn with_evaluator(mut self, evaluator: &'a Evaluator<'a, T>) -> Self {
+        self.evaluator = Some(evaluator);
+        self
+    }
+
+    /// Compares two individuals by how good they are: returns `Ordering::Greater` when `a` is
+    /// better than `b`. It's the comparison of their fitness, unless the constraint handling uses
+    /// the feasibility rules.
+    pub fn compare(&self, a: &T, b: &T) -> Ordering {
+        match self.constraint_handling {
+            Some(handling) => handling.compare(self.objective, a, b),
+            None => self.objective.compare(a.get_fitness(), b.get_fitness()),
+        }
+    }
+
+    /// Calculates and sets the fitness of the individual, or returns `None` if there is no
+    /// fitness. Within an evolution, it's calculated as the evolution does, through its fitness
+    /// cache and penalized by its constraints, and counts as an evaluation. Otherwise it's only
+    /// penalized by the boundary handler.
+    pub fn evaluate(&self, individual: &mut T) -> Option<f64> {
+        if let Some(evaluator) = self.evaluator {
+            return Some(evaluator.evaluate_offspring(individual));
+        }
+
+        let fitness = self.fitness?.calculate_fitness(individual);
+        let fitness = self.boundary_handler.penalize(fitness, individual, self.objective);
+
+        individual.set_fitness(fitness);
+        Some(fitness)
+    }
+}
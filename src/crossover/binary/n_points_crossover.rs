@@ -1,43 +1,50 @@
-use rand::{Rng, thread_rng};
+use rand::Rng;
 use rand::prelude::IteratorRandom;
-use rayon::iter::ParallelIterator;
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
 use rayon::prelude::ParallelSliceMut;
 use crate::{Crossover, Individual};
 use crate::crossover::NPointsCrossover;
 use crate::population::Bin;
+use crate::context::Context;
+use crate::utils::check_probability;
 
 impl Crossover<Bin> for NPointsCrossover {
-    fn crossover(&self, population: &mut Vec<Bin>) {
-        population.par_chunks_mut(2).for_each_init(
-            || thread_rng(),
-            |mut rng, chunk| {
-                if rng.gen_bool(self.crossover_rate) {
-                    let mut parent1 = chunk[0].clone();
-                    let mut parent2 = chunk[1].clone();
+    fn crossover(&self, population: &mut Vec<Bin>, context: &mut Context<Bin>) {
+        let streams = context.rng.streams();
 
-                    let len = parent1.get_chromosome().len();
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
 
-                    let mut points: Vec<usize> = (0..len).choose_multiple(&mut rng, self.n_points);
+            if chunk.len() == 2 && rng.gen_bool(self.crossover_rate) {
+                let mut parent1 = chunk[0].clone();
+                let mut parent2 = chunk[1].clone();
 
-                    points.sort();
+                let len = parent1.get_chromosome().len();
 
-                    let mut start = 0;
+                let mut points: Vec<usize> = (0..len).choose_multiple(&mut rng, self.n_points);
 
-                    for point in points {
-                        NPointsCrossover::swap_sections(
-                            &mut parent1.get_mut_chromosome(),
-                            &mut parent2.get_mut_chromosome(),
-                            start,
-                            point,
-                        );
+                points.sort();
 
-                        start = point;
-                    }
+                let mut start = 0;
 
-                    chunk[0] = parent1;
-                    chunk[1] = parent2;
+                for point in points {
+                    NPointsCrossover::swap_sections(
+                        &mut parent1.get_mut_chromosome(),
+                        &mut parent2.get_mut_chromosome(),
+                        start,
+                        point,
+                    );
+
+                    start = point;
                 }
-            },
-        );
+
+                chunk[0] = parent1;
+                chunk[1] = parent2;
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)
     }
 }
@@ -1,8 +1,9 @@
 use crate::{Crossover, Individual};
+use crate::context::Context;
 
 #[derive(Clone)]
 pub struct DoNothingCrossover;
 
 impl<T: Individual> Crossover<T> for DoNothingCrossover {
-    fn crossover(&self, _population: &mut Vec<T>) {}
+    fn crossover(&self, _population: &mut Vec<T>, _context: &mut Context<T>) {}
 }
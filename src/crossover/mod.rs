@@ -1,3 +1,4 @@
+use crate::context::Context;
 use crate::population::Individual;
 
 use dyn_clone::DynClone;
@@ -5,6 +6,7 @@ use dyn_clone::DynClone;
 mod binary;
 mod permuted;
 mod real;
+mod tree;
 
 mod do_nothing_crossover;
 mod uniform_crossover;
@@ -14,6 +16,7 @@ mod n_points_crossover;
 pub use binary::*;
 pub use permuted::*;
 pub use real::*;
+pub use tree::*;
 
 pub use do_nothing_crossover::*;
 pub use uniform_crossover::*;
@@ -31,13 +34,27 @@ pub use n_points_crossover::*;
 /// struct DoNothingCrossover;
 ///
 /// impl<T: Individual> Crossover<T> for DoNothingCrossover {
-///     fn crossover(&self, population: &mut Vec<T>) {
+///     fn crossover(&self, population: &mut Vec<T>, context: &mut Context<T>) {
 ///         // Do nothing
 ///     }
 /// }
 /// ```
 pub trait Crossover<T: Individual>: 'static + DynClone + Send + Sync {
     /// Method that performs the crossover. It receives a mutable reference to the population and
-    /// should modify it in place.
-    fn crossover(&self, population: &mut Vec<T>);
+    /// should modify it in place. All the randomness must come from `context.rng` so the evolution
+    /// can be reproduced from its seed.
+    fn crossover(&self, population: &mut Vec<T>, context: &mut Context<T>);
+
+    /// Checks the parameters of the crossover, like its rate. It's called when the evolution is
+    /// built, so an invalid parameter is reported instead of panicking during the run.
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Whether the crossover evaluates the offsprings with the fitness of the [`Context`]. Such
+    /// crossovers can't be used without a single fitness, as in the
+    /// [`Nsga2`](crate::multi_objective::Nsga2).
+    fn needs_fitness(&self) -> bool {
+        false
+    }
 }
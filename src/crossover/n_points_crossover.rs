@@ -1,3 +1,5 @@
+use serde_derive::Deserialize;
+
 // N Points Crossover works for Binary, Integer and Reals but not for Permuted Integers, so the
 // implementations are in each module.
 
@@ -6,7 +8,8 @@
 /// Generates `n` random points to "crossover" the sections between the parents. This can be
 /// a `Single-Point Crossover` using `n_points = 1` a `Two-Points Crossover` using `n_points = 2`
 /// or any number you'd like.
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct NPointsCrossover {
     /// The probability of crossover occurring.
     pub crossover_rate: f64,
@@ -1,13 +1,19 @@
 use std::collections::HashMap;
 
-use rand::{thread_rng, Rng};
-use rayon::{prelude::ParallelIterator, slice::ParallelSliceMut};
+use rand::Rng;
+use rayon::{
+    prelude::{IndexedParallelIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
+use serde_derive::Deserialize;
 
-use crate::population::IntPerm;
+use crate::{context::Context, population::IntPerm};
 
 use super::Crossover;
+use crate::utils::check_probability;
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct CycleCrossover {
     pub crossover_rate: f64,
 }
@@ -50,21 +56,26 @@ impl CycleCrossover {
 }
 
 impl Crossover<IntPerm> for CycleCrossover {
-    fn crossover(&self, population: &mut Vec<IntPerm>) {
-        population.par_chunks_mut(2).for_each_init(
-            || thread_rng(),
-            |rng, chunk| {
-                if rng.gen_bool(self.crossover_rate) {
-                    let mut parent1 = chunk[0].clone();
-                    let mut parent2 = chunk[1].clone();
-
-                    CycleCrossover::cx_crossover(&mut parent1.chromosome, &mut parent2.chromosome);
-
-                    chunk[0] = parent1;
-                    chunk[1] = parent2;
-                }
-            },
-        );
+    fn crossover(&self, population: &mut Vec<IntPerm>, context: &mut Context<IntPerm>) {
+        let streams = context.rng.streams();
+
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
+
+            if chunk.len() == 2 && rng.gen_bool(self.crossover_rate) {
+                let mut parent1 = chunk[0].clone();
+                let mut parent2 = chunk[1].clone();
+
+                CycleCrossover::cx_crossover(&mut parent1.chromosome, &mut parent2.chromosome);
+
+                chunk[0] = parent1;
+                chunk[1] = parent2;
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)
     }
 }
 
@@ -1,11 +1,16 @@
 use std::collections::{HashSet, VecDeque};
 
-use rand::{thread_rng, Rng};
-use rayon::{prelude::ParallelIterator, slice::ParallelSliceMut};
+use rand::Rng;
+use rayon::{
+    prelude::{IndexedParallelIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
+use serde_derive::Deserialize;
 
-use crate::{population::IntPerm, Individual};
+use crate::{context::Context, population::IntPerm, Individual};
 
 use super::Crossover;
+use crate::utils::check_probability;
 
 /// # Ordered Crossover (OX)
 ///
@@ -13,7 +18,8 @@ use super::Crossover;
 /// It maintains the relative order of the elements in the parent chromosomes. With the `crossover_rate`
 /// probability it chooses two points in the parents, maintain the elements between the points and
 /// fill the remaining positions with the elements of the other parent in the order they appear.
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct OrderedCrossover {
     pub crossover_rate: f64,
 }
@@ -66,31 +72,36 @@ impl OrderedCrossover {
 }
 
 impl Crossover<IntPerm> for OrderedCrossover {
-    fn crossover(&self, population: &mut Vec<IntPerm>) {
-        population.par_chunks_mut(2).for_each_init(
-            || thread_rng(),
-            |rng, chunk| {
-                if rng.gen_bool(self.crossover_rate) {
-                    let mut parent1 = chunk[0].clone();
-                    let mut parent2 = chunk[1].clone();
-
-                    let len = parent1.get_chromosome().len();
-
-                    let start = rng.gen_range(0..len);
-                    let end = rng.gen_range(start..len);
-
-                    OrderedCrossover::apply_ox(
-                        &mut parent1.chromosome,
-                        &mut parent2.chromosome,
-                        start,
-                        end,
-                    );
-
-                    chunk[0] = parent1;
-                    chunk[1] = parent2;
-                }
-            },
-        );
+    fn crossover(&self, population: &mut Vec<IntPerm>, context: &mut Context<IntPerm>) {
+        let streams = context.rng.streams();
+
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
+
+            if chunk.len() == 2 && rng.gen_bool(self.crossover_rate) {
+                let mut parent1 = chunk[0].clone();
+                let mut parent2 = chunk[1].clone();
+
+                let len = parent1.get_chromosome().len();
+
+                let start = rng.gen_range(0..len);
+                let end = rng.gen_range(start..len);
+
+                OrderedCrossover::apply_ox(
+                    &mut parent1.chromosome,
+                    &mut parent2.chromosome,
+                    start,
+                    end,
+                );
+
+                chunk[0] = parent1;
+                chunk[1] = parent2;
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)
     }
 }
 
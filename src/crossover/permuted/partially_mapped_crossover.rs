@@ -1,13 +1,19 @@
 use std::collections::HashMap;
 
-use rand::{thread_rng, Rng};
-use rayon::{prelude::ParallelIterator, slice::ParallelSliceMut};
+use rand::Rng;
+use rayon::{
+    prelude::{IndexedParallelIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
+use serde_derive::Deserialize;
 
-use crate::{population::IntPerm, Individual};
+use crate::{context::Context, population::IntPerm, Individual};
 
 use super::Crossover;
+use crate::utils::check_probability;
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct PartiallyMappedCrossover {
     pub crossover_rate: f64,
 }
@@ -57,31 +63,36 @@ impl PartiallyMappedCrossover {
 }
 
 impl Crossover<IntPerm> for PartiallyMappedCrossover {
-    fn crossover(&self, population: &mut Vec<IntPerm>) {
-        population.par_chunks_mut(2).for_each_init(
-            || thread_rng(),
-            |rng, chunk| {
-                if rng.gen_bool(self.crossover_rate) {
-                    let mut parent1 = chunk[0].clone();
-                    let mut parent2 = chunk[1].clone();
-
-                    let len = parent1.get_chromosome().len();
-
-                    let start = rng.gen_range(0..len);
-                    let end = rng.gen_range(start..len);
-
-                    PartiallyMappedCrossover::pmx_matching(
-                        &mut parent1.chromosome,
-                        &mut parent2.chromosome,
-                        start,
-                        end,
-                    );
-
-                    chunk[0] = parent1;
-                    chunk[1] = parent2;
-                }
-            },
-        );
+    fn crossover(&self, population: &mut Vec<IntPerm>, context: &mut Context<IntPerm>) {
+        let streams = context.rng.streams();
+
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
+
+            if chunk.len() == 2 && rng.gen_bool(self.crossover_rate) {
+                let mut parent1 = chunk[0].clone();
+                let mut parent2 = chunk[1].clone();
+
+                let len = parent1.get_chromosome().len();
+
+                let start = rng.gen_range(0..len);
+                let end = rng.gen_range(start..len);
+
+                PartiallyMappedCrossover::pmx_matching(
+                    &mut parent1.chromosome,
+                    &mut parent2.chromosome,
+                    start,
+                    end,
+                );
+
+                chunk[0] = parent1;
+                chunk[1] = parent2;
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)
     }
 }
 
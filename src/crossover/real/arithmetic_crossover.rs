@@ -1,16 +1,22 @@
+use serde_derive::Deserialize;
 use crate::population::Real;
 use crate::{Crossover, Individual};
-use rand::{thread_rng, Rng};
-use rayon::iter::ParallelIterator;
+use crate::context::Context;
+use rand::Rng;
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
 use rayon::prelude::ParallelSliceMut;
+use crate::utils::check_probability;
 
 /// # Arithmetic Crossover
 ///
 /// The arithmetic crossover creates the offsprings by computing a weighted average of two parent
 /// chromosomes. It does so based on the alpha value, where the offspring will be
 /// O1 = [p11 * alpha + p21 * (1 - alpha), p12 * alpha + p22 * (1 - alpha), ...], and the
-/// O2 = [p21 * alpha + p11 * (1 - alpha), p22 * alpha + p12 * (1 - alpha), ...].
-#[derive(Clone)]
+/// O2 = [p21 * alpha + p11 * (1 - alpha), p22 * alpha + p12 * (1 - alpha), ...]. With an `alpha`
+/// out of `[0, 1]` the genes can leave their bounds, and are handled by the boundary handler of the
+/// [`Context`].
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ArithmeticCrossover {
     pub crossover_rate: f64,
     pub alpha: f64,
@@ -36,21 +42,35 @@ impl ArithmeticCrossover {
 }
 
 impl Crossover<Real> for ArithmeticCrossover {
-    fn crossover(&self, population: &mut Vec<Real>) {
-        population.par_chunks_mut(2).for_each_init(
-            || thread_rng(),
-            |rng, chunk| {
-                if rng.gen_bool(self.crossover_rate) {
-                    let parent1 = chunk[0].clone();
-                    let parent2 = chunk[1].clone();
-
-                    chunk[0].chromosome =
-                        self.get_offspring(parent1.get_chromosome(), parent2.get_chromosome());
-                    chunk[1].chromosome =
-                        self.get_offspring(parent2.get_chromosome(), parent1.get_chromosome());
+    fn crossover(&self, population: &mut Vec<Real>, context: &mut Context<Real>) {
+        let streams = context.rng.streams();
+        let boundary_handler = context.boundary_handler;
+
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
+
+            if chunk.len() == 2 && rng.gen_bool(self.crossover_rate) {
+                let parent1 = chunk[0].clone();
+                let parent2 = chunk[1].clone();
+
+                chunk[0].chromosome =
+                    self.get_offspring(parent1.get_chromosome(), parent2.get_chromosome());
+                chunk[1].chromosome =
+                    self.get_offspring(parent2.get_chromosome(), parent1.get_chromosome());
+
+                for offspring in chunk.iter_mut() {
+                    for i in 0..offspring.chromosome.len() {
+                        let (gene, bounds) = (offspring.chromosome[i], offspring.range.get(i));
+                        offspring.chromosome[i] =
+                            boundary_handler.repair(gene, bounds, &mut rng, |_| gene);
+                    }
                 }
-            },
-        );
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)
     }
 }
 
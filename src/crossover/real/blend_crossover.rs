@@ -1,19 +1,25 @@
-use rand::{Rng, thread_rng};
-use rayon::iter::ParallelIterator;
+use rand::Rng;
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
 use rayon::prelude::ParallelSliceMut;
+use serde_derive::Deserialize;
 use crate::{Crossover, Individual};
 use crate::population::Real;
+use crate::rng::EvolutionRng;
+use crate::context::Context;
+use crate::utils::check_probability;
 
 /// # Blend Crossover
 ///
 /// This crossover method is used for real coded individuals. It takes two parents and creates two
 /// children by mixing the genes of the parents. For each gene, x1 is the smaller value and x2 is
 /// the bigger value and diff is `x1 - x2`. The children will have a value between `x1 - alpha * diff`
-/// and `x2 + alpha * diff`.
+/// and `x2 + alpha * diff`, with the genes out of their bounds handled by the boundary handler of
+/// the [`Context`].
 ///
 /// Reference: [An empirical comparison of two crossover operators in real-coded genetic algorithms
 /// for constrained numerical optimization problems](https://ieeexplore.ieee.org/document/7036347)
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BlendCrossover {
     pub crossover_rate: f64,
     pub alpha: f64,
@@ -46,28 +52,43 @@ impl BlendCrossover {
 }
 
 impl Crossover<Real> for BlendCrossover {
-    fn crossover(&self, population: &mut Vec<Real>) {
-        population.par_chunks_mut(2).for_each_init(
-            || thread_rng(),
-            |rng, chunk| {
-                if rng.gen_bool(self.crossover_rate) {
-                    let (parent1, parent2) = chunk.split_at_mut(1);
-                    let parent1 = &mut parent1[0];
-                    let parent2 = &mut parent2[0];
+    fn crossover(&self, population: &mut Vec<Real>, context: &mut Context<Real>) {
+        let streams = context.rng.streams();
+        let boundary_handler = context.boundary_handler;
 
-                    let len = parent1.get_chromosome().len();
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
 
-                    for i in 0..len {
-                        let (min, max) = self.blend_min_max(parent1.get_gene(i), parent2.get_gene(i));
+            if chunk.len() == 2 && rng.gen_bool(self.crossover_rate) {
+                let (parent1, parent2) = chunk.split_at_mut(1);
+                let parent1 = &mut parent1[0];
+                let parent2 = &mut parent2[0];
 
-                        let gene = rng.gen_range(min..=max);
+                let len = parent1.get_chromosome().len();
 
-                        parent1.set_gene(i, gene);
-                        parent2.set_gene(i, gene);
-                    }
+                for i in 0..len {
+                    let (min, max) = self.blend_min_max(parent1.get_gene(i), parent2.get_gene(i));
+
+                    let blend = |rng: &mut EvolutionRng| rng.gen_range(min..=max);
+
+                    let gene = blend(&mut rng);
+                    let gene = boundary_handler.repair(gene, parent1.range.get(i), &mut rng, blend);
+
+                    parent1.set_gene(i, gene);
+                    parent2.set_gene(i, gene);
                 }
             }
-        );
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)?;
+
+        if !(self.alpha >= 0.0 && self.alpha.is_finite()) {
+            return Err(format!("The blend alpha must be non-negative, got {}", self.alpha));
+        }
+
+        Ok(())
     }
 }
 
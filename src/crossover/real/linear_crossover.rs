@@ -1,11 +1,187 @@
-use crate::Crossover;
+use rand::Rng;
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use rayon::prelude::ParallelSliceMut;
+use serde_derive::Deserialize;
+
+use crate::context::Context;
 use crate::population::Real;
+use crate::{BoundaryHandler, Crossover, Individual};
+use crate::utils::check_probability;
+
+/// # Linear Crossover (LX)
+///
+/// Wright's linear crossover. With the `crossover_rate` probability, it creates three candidates
+/// from the parents p1 and p2: `0.5 * p1 + 0.5 * p2`, `1.5 * p1 - 0.5 * p2` and
+/// `-0.5 * p1 + 1.5 * p2`, and keeps the best two as the offsprings. The candidates are evaluated
+/// with [`Context::evaluate`], as the evolution evaluates its individuals, so they count towards
+/// its evaluations and the offsprings keep their fitness. Their genes out of the bounds are handled
+/// by the boundary handler of the context.
+///
+/// When the context has no fitness, like when called outside of an
+/// [`Evolution`](crate::Evolution), the offsprings are the midpoint and one of the other two
+/// candidates, chosen at random.
+///
+/// Reference: [Genetic Algorithms for Real Parameter Optimization](https://doi.org/10.1016/B978-0-08-050684-5.50016-1)
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LinearCrossover {
+    pub crossover_rate: f64,
+}
+
+impl Default for LinearCrossover {
+    fn default() -> Self {
+        Self {
+            crossover_rate: 0.8,
+        }
+    }
+}
+
+impl LinearCrossover {
+    fn candidate<R: Rng + ?Sized>(
+        parent1: &Real,
+        parent2: &Real,
+        (w1, w2): (f64, f64),
+        boundary_handler: BoundaryHandler,
+        rng: &mut R,
+    ) -> Real {
+        let mut candidate = parent1.clone();
+
+        for i in 0..candidate.chromosome.len() {
+            let gene = w1 * parent1.chromosome[i] + w2 * parent2.chromosome[i];
+
+            candidate.chromosome[i] =
+                boundary_handler.repair(gene, parent1.range.get(i), rng, |_| gene);
+        }
+
+        candidate
+    }
+
+    fn offsprings<R: Rng + ?Sized>(
+        parent1: &Real,
+        parent2: &Real,
+        context: &Context<Real>,
+        rng: &mut R,
+    ) -> (Real, Real) {
+        let mut candidates: Vec<Real> = [(0.5, 0.5), (1.5, -0.5), (-0.5, 1.5)]
+            .into_iter()
+            .map(|weights| {
+                Self::candidate(parent1, parent2, weights, context.boundary_handler, rng)
+            })
+            .collect();
+
+        if context.fitness.is_some() {
+            for candidate in candidates.iter_mut() {
+                context.evaluate(candidate);
+            }
 
-#[derive(Clone)]
-pub struct LinearCrossover;
+            // From the best to the worst.
+            let objective = context.objective;
+            candidates.sort_by(|a, b| objective.compare(b.get_fitness(), a.get_fitness()));
+        } else {
+            candidates.swap(1, rng.gen_range(1..=2));
+        }
+
+        let second = candidates.swap_remove(1);
+        (candidates.swap_remove(0), second)
+    }
+}
 
 impl Crossover<Real> for LinearCrossover {
-    fn crossover(&self, _population: &mut Vec<Real>) {
-        todo!()
+    fn crossover(&self, population: &mut Vec<Real>, context: &mut Context<Real>) {
+        let streams = context.rng.streams();
+        let context = &*context;
+
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
+
+            if chunk.len() < 2 || !rng.gen_bool(self.crossover_rate) {
+                return;
+            }
+
+            let (offspring1, offspring2) =
+                Self::offsprings(&chunk[0], &chunk[1], context, &mut rng);
+
+            chunk[0] = offspring1;
+            chunk[1] = offspring2;
+        });
+    }
+
+    fn needs_fitness(&self) -> bool {
+        true
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    #[derive(Clone)]
+    struct SumFitness;
+
+    impl Fitness<Real> for SumFitness {
+        fn calculate_fitness(&self, individual: &Real) -> f64 {
+            individual.chromosome.iter().sum()
+        }
+    }
+
+    fn parents(range: (f64, f64)) -> Vec<Real> {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+
+        [1.0, 2.0]
+            .iter()
+            .map(|&gene| {
+                let mut parent = Real::generate_member(2, &range.into(), &mut rng);
+                parent.chromosome = vec![gene; 2];
+                parent
+            })
+            .collect()
+    }
+
+    fn crossover(range: (f64, f64), objective: Objective) -> Vec<Vec<f64>> {
+        let mut population = parents(range);
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, objective).with_fitness(&SumFitness);
+
+        LinearCrossover { crossover_rate: 1.0 }.crossover(&mut population, &mut context);
+
+        population.into_iter().map(|individual| individual.chromosome).collect()
+    }
+
+    #[test]
+    fn keeps_the_best_two_candidates() {
+        assert_eq!(
+            crossover((0.0, 10.0), Objective::Maximize),
+            vec![vec![2.5, 2.5], vec![1.5, 1.5]]
+        );
+        assert_eq!(
+            crossover((0.0, 10.0), Objective::Minimize),
+            vec![vec![0.5, 0.5], vec![1.5, 1.5]]
+        );
+    }
+
+    #[test]
+    fn clamps_the_candidates_to_the_range() {
+        assert_eq!(
+            crossover((0.8, 2.2), Objective::Maximize),
+            vec![vec![2.2, 2.2], vec![1.5, 1.5]]
+        );
+    }
+
+    #[test]
+    fn keeps_the_midpoint_without_fitness() {
+        let mut population = parents((0.0, 10.0));
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        LinearCrossover { crossover_rate: 1.0 }.crossover(&mut population, &mut context);
+
+        assert_eq!(population[0].chromosome, vec![1.5, 1.5]);
+        assert!([vec![0.5, 0.5], vec![2.5, 2.5]].contains(&population[1].chromosome));
     }
-}
\ No newline at end of file
+}
@@ -1,11 +1,260 @@
-use crate::Crossover;
+use rand::Rng;
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use rayon::prelude::ParallelSliceMut;
+use serde_derive::Deserialize;
+
+use crate::context::Context;
 use crate::population::Real;
+use crate::rng::EvolutionRng;
+use crate::Crossover;
+use crate::utils::check_probability;
+
+/// # Simulated Binary Crossover (SBX)
+///
+/// Simulates, for real coded individuals, the spread of the single point crossover of binary
+/// strings. With the `crossover_rate` probability a pair of parents is crossed and, with the
+/// `gene_probability` probability, each pair of genes `(x1, x2)` becomes
+/// `0.5 * ((x1 + x2) -+ beta * |x2 - x1|)`, where the spread factor `beta` is drawn from a
+/// distribution controlled by the distribution index `eta`. The larger the `eta`, the closer the
+/// offsprings are to their parents, 2 to 20 are the usual values.
+///
+/// When `bounded`, the distribution of `beta` is truncated so the offsprings stay inside the
+/// bounds of the genes, as in Deb's implementation of the NSGA-II. Otherwise the offsprings keep
+/// the mean of their parents, and the genes out of their bounds are handled by the boundary handler
+/// of the [`Context`].
+///
+/// Reference: [Simulated Binary Crossover for Continuous Search Space](https://www.complex-systems.com/abstracts/v09_i02_a02/)
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulatedBinaryCrossover {
+    pub crossover_rate: f64,
+    pub eta: f64,
+    pub gene_probability: f64,
+    pub bounded: bool,
+}
+
+impl Default for SimulatedBinaryCrossover {
+    fn default() -> Self {
+        Self {
+            crossover_rate: 0.9,
+            eta: 15.0,
+            gene_probability: 0.5,
+            bounded: true,
+        }
+    }
+}
+
+impl SimulatedBinaryCrossover {
+    pub fn new(crossover_rate: f64, eta: f64) -> Self {
+        Self {
+            crossover_rate,
+            eta,
+            ..Self::default()
+        }
+    }
+
+    /// The spread factor for the random number `u`, from `[0, 1)`. `alpha` truncates the
+    /// distribution, it's 2 when unbounded.
+    fn spread_factor(&self, u: f64, alpha: f64) -> f64 {
+        let exponent = 1.0 / (self.eta + 1.0);
+
+        if u <= 1.0 / alpha {
+            (u * alpha).powf(exponent)
+        } else {
+            (1.0 / (2.0 - u * alpha)).powf(exponent)
+        }
+    }
+
+    /// The truncation of the spread factor distribution so that the offspring doesn't go further
+    /// than `distance` from the closest parent, for parents `diff` apart.
+    fn truncation(&self, distance: f64, diff: f64) -> f64 {
+        if !self.bounded {
+            return 2.0;
+        }
+
+        let beta = 1.0 + 2.0 * distance.max(0.0) / diff;
+
+        2.0 - beta.powf(-(self.eta + 1.0))
+    }
 
-#[derive(Clone)]
-pub struct SimulatedBinaryCrossover;
+    fn offsprings<R: Rng + ?Sized>(
+        &self,
+        x1: f64,
+        x2: f64,
+        (lower, upper): (f64, f64),
+        rng: &mut R,
+    ) -> (f64, f64) {
+        let (y1, y2) = (x1.min(x2), x1.max(x2));
+        let diff = y2 - y1;
+
+        if diff < 1e-14 {
+            return (x1, x2);
+        }
+
+        let u: f64 = rng.gen();
+        let beta = self.spread_factor(u, self.truncation(y1 - lower, diff));
+        let mut c1 = 0.5 * ((y1 + y2) - beta * diff);
+
+        let beta = self.spread_factor(u, self.truncation(upper - y2, diff));
+        let mut c2 = 0.5 * ((y1 + y2) + beta * diff);
+
+        if self.bounded {
+            c1 = c1.clamp(lower, upper);
+            c2 = c2.clamp(lower, upper);
+        }
+
+        if rng.gen_bool(0.5) {
+            (c2, c1)
+        } else {
+            (c1, c2)
+        }
+    }
+}
 
 impl Crossover<Real> for SimulatedBinaryCrossover {
-    fn crossover(&self, _population: &mut Vec<Real>) {
-        todo!()
+    fn crossover(&self, population: &mut Vec<Real>, context: &mut Context<Real>) {
+        let streams = context.rng.streams();
+        let boundary_handler = context.boundary_handler;
+
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
+
+            if chunk.len() < 2 || !rng.gen_bool(self.crossover_rate) {
+                return;
+            }
+
+            let (parent1, parent2) = chunk.split_at_mut(1);
+            let parent1 = &mut parent1[0];
+            let parent2 = &mut parent2[0];
+
+            for i in 0..parent1.chromosome.len() {
+                if rng.gen_bool(self.gene_probability) {
+                    let (x1, x2) = (parent1.chromosome[i], parent2.chromosome[i]);
+                    let bounds = parent1.range.get(i);
+
+                    let (c1, c2) = self.offsprings(x1, x2, bounds, &mut rng);
+                    let resample = |rng: &mut EvolutionRng| self.offsprings(x1, x2, bounds, rng).0;
+
+                    parent1.chromosome[i] = boundary_handler.repair(c1, bounds, &mut rng, resample);
+                    parent2.chromosome[i] = boundary_handler.repair(c2, bounds, &mut rng, resample);
+                }
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)?;
+        check_probability("gene probability", self.gene_probability)?;
+
+        if !(self.eta >= 0.0 && self.eta.is_finite()) {
+            return Err(format!("The distribution index must be non-negative, got {}", self.eta));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::SimulatedBinaryCrossover;
+    use crate::prelude::*;
+
+    const PARENTS: (f64, f64) = (-1.0, 1.0);
+
+    fn offsprings(sbx: &SimulatedBinaryCrossover, range: (f64, f64)) -> Vec<(f64, f64)> {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+
+        (0..10_000)
+            .map(|_| sbx.offsprings(PARENTS.0, PARENTS.1, range, &mut rng))
+            .collect()
+    }
+
+    fn mean_distance_to_parents(sbx: &SimulatedBinaryCrossover) -> f64 {
+        let offsprings = offsprings(sbx, (-100.0, 100.0));
+
+        offsprings
+            .iter()
+            .map(|&(c1, c2)| (c1.min(c2) - PARENTS.0).abs() + (c1.max(c2) - PARENTS.1).abs())
+            .sum::<f64>()
+            / offsprings.len() as f64
+    }
+
+    #[test]
+    fn keeps_the_mean_and_contracts_half_the_time() {
+        let sbx = SimulatedBinaryCrossover {
+            bounded: false,
+            ..SimulatedBinaryCrossover::new(1.0, 2.0)
+        };
+
+        let offsprings = offsprings(&sbx, (-1.0, 1.0));
+
+        assert!(offsprings.iter().all(|(c1, c2)| (c1 + c2).abs() < 1e-9));
+
+        // The spread factor is below 1, the offsprings between the parents, with probability 0.5.
+        let contracting = offsprings.iter().filter(|(c1, _)| c1.abs() < 1.0).count();
+        let ratio = contracting as f64 / offsprings.len() as f64;
+
+        assert!((ratio - 0.5).abs() < 0.02, "ratio: {}", ratio);
+    }
+
+    #[test]
+    fn larger_eta_keeps_the_offsprings_closer() {
+        let distances: Vec<f64> = [1.0, 5.0, 20.0, 100.0]
+            .iter()
+            .map(|&eta| mean_distance_to_parents(&SimulatedBinaryCrossover::new(1.0, eta)))
+            .collect();
+
+        assert!(distances.windows(2).all(|pair| pair[0] > pair[1]), "{:?}", distances);
+    }
+
+    #[test]
+    fn bounded_stays_in_the_range() {
+        let sbx = SimulatedBinaryCrossover::new(1.0, 0.5);
+        let range = (-1.5, 1.2);
+
+        let offsprings = offsprings(&sbx, range);
+
+        assert!(offsprings
+            .iter()
+            .all(|&(c1, c2)| [c1, c2].iter().all(|c| (range.0..=range.1).contains(c))));
+        assert!(offsprings.iter().any(|&(c1, _)| !(-1.0..=1.0).contains(&c1)));
+    }
+
+    #[test]
+    fn crosses_only_the_chosen_genes() {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let original: Vec<Real> = (0..100)
+            .map(|_| Real::generate_member(10, &(0.0, 1.0).into(), &mut rng))
+            .collect();
+
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        let mut population = original.clone();
+        let sbx = SimulatedBinaryCrossover {
+            gene_probability: 0.0,
+            ..SimulatedBinaryCrossover::new(1.0, 15.0)
+        };
+        sbx.crossover(&mut population, &mut context);
+
+        assert!(population
+            .iter()
+            .zip(&original)
+            .all(|(a, b)| a.chromosome == b.chromosome));
+
+        let sbx = SimulatedBinaryCrossover {
+            gene_probability: 1.0,
+            ..sbx
+        };
+        sbx.crossover(&mut population, &mut context);
+
+        for (pair, original) in population.chunks(2).zip(original.chunks(2)) {
+            for i in 0..10 {
+                assert_ne!(pair[0].chromosome[i], original[0].chromosome[i]);
+                assert!((0.0..=1.0).contains(&pair[0].chromosome[i]));
+                assert!((0.0..=1.0).contains(&pair[1].chromosome[i]));
+            }
+        }
     }
-}
\ No newline at end of file
+}
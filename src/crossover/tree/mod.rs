@@ -0,0 +1,3 @@
+mod subtree_crossover;
+
+pub use subtree_crossover::*;
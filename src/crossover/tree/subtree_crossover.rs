@@ -0,0 +1,130 @@
+use rand::Rng;
+use rayon::{
+    prelude::{IndexedParallelIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
+
+use crate::{
+    context::Context,
+    population::{Tree, TreeLimits, TreeValue},
+    Crossover,
+};
+use crate::utils::check_probability;
+
+/// # Subtree Crossover
+///
+/// The standard crossover of genetic programming. With the `crossover_rate` probability it picks
+/// a random node in each parent and swaps the subtrees rooted at them. The nodes are functions
+/// with the `function_bias` probability, see [`Tree::random_node`]. An offspring that exceeds the
+/// `limits` is discarded and its parent is kept in its place.
+#[derive(Clone)]
+pub struct SubtreeCrossover {
+    pub crossover_rate: f64,
+    pub function_bias: f64,
+    pub limits: TreeLimits,
+}
+
+impl Default for SubtreeCrossover {
+    fn default() -> Self {
+        Self {
+            crossover_rate: 0.9,
+            function_bias: 0.9,
+            limits: TreeLimits::default(),
+        }
+    }
+}
+
+impl<V: TreeValue> Crossover<Tree<V>> for SubtreeCrossover {
+    fn crossover(&self, population: &mut Vec<Tree<V>>, context: &mut Context<Tree<V>>) {
+        let streams = context.rng.streams();
+
+        population.par_chunks_mut(2).enumerate().for_each(|(index, chunk)| {
+            let mut rng = streams.get(index);
+
+            if chunk.len() < 2 || !rng.gen_bool(self.crossover_rate) {
+                return;
+            }
+
+            let range1 = chunk[0].subtree(chunk[0].random_node(self.function_bias, &mut rng));
+            let range2 = chunk[1].subtree(chunk[1].random_node(self.function_bias, &mut rng));
+
+            let mut offspring1 = chunk[0].clone();
+            let mut offspring2 = chunk[1].clone();
+
+            offspring1.replace_subtree(range1.clone(), &chunk[1].nodes()[range2.clone()]);
+            offspring2.replace_subtree(range2, &chunk[0].nodes()[range1]);
+
+            if self.limits.allows(&offspring1) {
+                chunk[0] = offspring1;
+            }
+            if self.limits.allows(&offspring2) {
+                chunk[1] = offspring2;
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("crossover rate", self.crossover_rate)?;
+        check_probability("function bias", self.function_bias)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    fn tree(depth: usize) -> Tree<f64> {
+        let mut nodes = vec![Node::Function { index: 0, arity: 1 }; depth];
+        nodes.push(Node::Constant(depth as f64));
+
+        Tree::new(nodes)
+    }
+
+    fn crossover_pairs(limits: TreeLimits) -> Vec<Vec<Tree<f64>>> {
+        let crossover = SubtreeCrossover {
+            crossover_rate: 1.0,
+            limits,
+            ..Default::default()
+        };
+
+        let mut rng = EvolutionRng::seed_from_u64(3);
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        (0..20)
+            .map(|_| {
+                let mut population = vec![tree(3), tree(5)];
+                crossover.crossover(&mut population, &mut context);
+                population
+            })
+            .collect()
+    }
+
+    #[test]
+    fn swaps_subtrees() {
+        for population in crossover_pairs(TreeLimits::default()) {
+            assert_eq!(population[0].size() + population[1].size(), 10);
+
+            for offspring in &population {
+                assert_eq!(offspring.subtree(0), 0..offspring.size());
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_the_parents_over_the_limits() {
+        let limits = TreeLimits {
+            max_depth: 5,
+            max_size: usize::MAX,
+        };
+
+        let populations = crossover_pairs(limits);
+
+        assert!(populations.iter().any(|population| population[0].size() != 4));
+
+        for population in populations {
+            assert!(population.iter().all(|offspring| limits.allows(offspring)));
+        }
+    }
+}
@@ -1,10 +1,13 @@
+use serde_derive::Deserialize;
+
 // The Uniform Crossover works for Binary, Integer and Reals but not for Permuted Integers,
 // so the implementations are in each module.
 
 /// # Uniform Crossover
 ///
 /// For each gene, it selects whether to swap them between the parents based on the `toss_probability`.
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct UniformCrossover {
     /// The probability of crossover occurring.
     pub crossover_rate: f64,
@@ -0,0 +1,34 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::selection::SelectionError;
+
+/// The reasons a generation of an [`Evolution`](crate::Evolution) can fail. The evolution is left
+/// as it was before the failed generation, so its best individual and metrics are still available.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EvolutionError {
+    /// The selection operator couldn't select the mating pool.
+    Selection(SelectionError),
+}
+
+impl Display for EvolutionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvolutionError::Selection(error) => write!(f, "Selection failed: {}", error),
+        }
+    }
+}
+
+impl Error for EvolutionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EvolutionError::Selection(error) => Some(error),
+        }
+    }
+}
+
+impl From<SelectionError> for EvolutionError {
+    fn from(error: SelectionError) -> Self {
+        EvolutionError::Selection(error)
+    }
+}
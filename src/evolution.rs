@@ -1,22 +1,44 @@
 use ordered_float::OrderedFloat;
 use rayon::iter::{IntoParallelIterator, IntoParallelRefMutIterator};
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::cmp::Reverse;
-use std::collections::BinaryHeap;
-use std::sync::Arc;
+use std::collections::{BinaryHeap, HashSet};
+use std::error::Error;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use crate::{
+    boundary::BoundaryHandler,
+    cancellation::CancellationToken,
+    checkpoint::{Checkpoint, CheckpointConfig},
+    constraints::ConstraintHandler,
+    context::Context,
     crossover::Crossover,
+    error::EvolutionError,
+    evolution_builder::EvolutionBuilder,
     fitness::Fitness,
-    metrics::{Metrics, Steps},
+    fitness_cache::FitnessCache,
+    metrics::{Diversity, Metrics, Steps},
     mutation::Mutation,
+    objective::Objective,
+    observer::EvolutionObserver,
+    plotting::ChartOptions,
     population::{GeneCod, Individual},
+    replacement::{GenerationalReplacement, Replacement},
+    rng::EvolutionRng,
     selection::Selection,
+    stop_condition::StopCondition,
 };
 
 pub type StopConditionFn = Arc<dyn Fn(f64, u32, u32) -> bool + Send + Sync>;
 
+type RankKey = (OrderedFloat<f64>, OrderedFloat<f64>);
+
 #[derive(Clone)]
 pub struct EvolutionConfig<T: Individual> {
     pub dimension: u32,
@@ -50,10 +72,10 @@ pub struct EvolutionConfig<T: Individual> {
 /// evolution.start();
 ///
 /// // and iterate through the evolution with the `next` method:
-/// evolution.next();
+/// evolution.next().unwrap();
 ///
 /// // or you can run it until the stop condition is met with the `run` method:
-/// evolution.run();
+/// evolution.run().unwrap();
 /// ```
 pub struct Evolution<T: Individual> {
     _title: String,
@@ -62,8 +84,19 @@ pub struct Evolution<T: Individual> {
     selection: Box<dyn Selection<T>>,
     crossover: Box<dyn Crossover<T>>,
     mutation: Box<dyn Mutation<T>>,
+    replacement: Box<dyn Replacement<T>>,
     elitism: u32,
-    stop_condition: StopConditionFn,
+    stop_condition: StopCondition,
+    rng: EvolutionRng,
+    objective: Objective,
+    boundary_handler: BoundaryHandler,
+    constraints: Option<ConstraintHandler<T>>,
+    fitness_cache: Option<FitnessCache>,
+    cancellation: Option<CancellationToken>,
+    diversity_metrics: bool,
+    checkpoint: Option<CheckpointConfig<T>>,
+    observers: Vec<Box<dyn EvolutionObserver<T>>>,
+    aborted: bool,
     pub metrics: Metrics,
     current_population: Vec<T>,
 }
@@ -77,7 +110,8 @@ impl<T: Individual> Evolution<T> {
         crossover: Box<dyn Crossover<T>>,
         mutation: Box<dyn Mutation<T>>,
         elitism: u32,
-        stop_condition: StopConditionFn,
+        stop_condition: impl Into<StopCondition>,
+        rng: EvolutionRng,
     ) -> Self {
         Self {
             _title: title,
@@ -87,81 +121,214 @@ impl<T: Individual> Evolution<T> {
             selection,
             crossover,
             mutation,
+            replacement: Box::new(GenerationalReplacement),
             elitism,
-            stop_condition,
+            stop_condition: stop_condition.into(),
+            rng,
+            objective: Objective::Maximize,
+            boundary_handler: BoundaryHandler::default(),
+            constraints: None,
+            fitness_cache: None,
+            cancellation: None,
+            diversity_metrics: false,
+            checkpoint: None,
+            observers: Vec::new(),
+            aborted: false,
             metrics: Metrics::new(),
         }
     }
 
+    pub(crate) fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self.metrics = Metrics::with_objective(objective);
+        self
+    }
+
+    pub(crate) fn with_percentiles(mut self, percentiles: Vec<f64>) -> Self {
+        self.metrics = Metrics::with_objective(self.objective).with_percentiles(percentiles);
+        self
+    }
+
+    pub(crate) fn with_replacement(mut self, replacement: Box<dyn Replacement<T>>) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    pub(crate) fn with_boundary_handler(mut self, boundary_handler: BoundaryHandler) -> Self {
+        self.boundary_handler = boundary_handler;
+        self
+    }
+
+    pub(crate) fn with_constraints(mut self, constraints: Option<ConstraintHandler<T>>) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    pub(crate) fn with_fitness_cache(mut self, capacity: Option<usize>) -> Self {
+        self.fitness_cache = capacity.map(FitnessCache::new);
+        self
+    }
+
+    pub(crate) fn with_diversity_metrics(mut self, diversity_metrics: bool) -> Self {
+        self.diversity_metrics = diversity_metrics;
+        self
+    }
+
+    pub(crate) fn with_cancellation(mut self, cancellation: Option<CancellationToken>) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    pub(crate) fn with_checkpoint(mut self, checkpoint: Option<CheckpointConfig<T>>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    pub(crate) fn with_observers(mut self, observers: Vec<Box<dyn EvolutionObserver<T>>>) -> Self {
+        self.observers = observers;
+        self
+    }
+
     /// Starts the evolution, generating the initial population and calculating the
     /// fitness of each individual.
     pub fn start(&mut self) {
-        self.metrics = Metrics::new();
+        let percentiles = self.metrics.percentiles().to_vec();
+        self.metrics = Metrics::with_objective(self.objective).with_percentiles(percentiles);
 
         self.metrics.start_clock();
 
-        let _ = &self.current_population.clear();
+        let streams = self.rng.streams();
 
         self.current_population = (0..self.config.population_size)
             .into_par_iter()
-            .map(|_| T::generate_member(self.config.dimension, &self.config.range))
+            .map(|i| {
+                let mut rng = streams.get(i as usize);
+                T::generate_member(self.config.dimension, &self.config.range, &mut rng)
+            })
             .collect();
 
-        for _ in 0..self.config.population_size {
-            let _ = &self.current_population.push(T::generate_member(
-                self.config.dimension,
-                &self.config.range,
-            ));
-        }
-
         self.process_fitness();
+        self.record_cache_counts();
 
-        self.metrics
-            .record(self.current_best_fitness(), self.current_fitness_average());
+        self.record_metrics();
+
+        self.aborted = false;
+        self.notify_new_best();
+        self.notify_observers(|observer, evolution| {
+            observer.on_start(evolution);
+            ControlFlow::Continue(())
+        });
     }
 
     /// This method runs one generation of the evolution.
-    /// It selects the mating pool, crossover, mutate and calculates the fitness of the new population.
-    pub fn next(&mut self) {
+    /// It selects the mating pool, crossover, mutate and calculates the fitness of the offsprings,
+    /// which the replacement turns into the new population.
+    /// If the selection fails, the generation is discarded and the error returned.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<(), EvolutionError> {
         self.metrics.step_start(Steps::Elitism);
         let elitists = self.find_elitists();
         self.metrics.step_end(Steps::Elitism);
 
         self.metrics.step_start(Steps::Selection);
-        let mut mating_pool = self.selection.get_mating_pool(&self.current_population);
+        let evaluator = Evaluator::new(
+            self.fitness.as_ref(),
+            self.boundary_handler,
+            self.constraints.as_ref(),
+            self.fitness_cache.as_ref(),
+            self.objective,
+            self.metrics.iterations + 1,
+        );
+        let mut context = Context::new(&mut self.rng, self.objective)
+            .with_fitness(self.fitness.as_ref())
+            .with_boundary_handler(self.boundary_handler)
+            .with_evaluator(&evaluator);
+
+        if let Some(constraints) = &self.constraints {
+            context = context.with_constraint_handling(constraints.handling());
+        }
+
+        let offspring_count = self.replacement.offspring_count(self.current_population.len());
+        let mating_pool =
+            self.selection.select(&self.current_population, offspring_count, &mut context);
         self.metrics.step_end(Steps::Selection);
 
+        let mut mating_pool = mating_pool?;
+        let parents = mating_pool.clone();
+
         self.metrics.step_start(Steps::Crossover);
-        self.crossover.crossover(&mut mating_pool);
+        self.crossover.crossover(&mut mating_pool, &mut context);
         self.metrics.step_end(Steps::Crossover);
 
         self.metrics.step_start(Steps::Mutation);
-        self.mutation.mutate(&mut mating_pool);
+        self.mutation.mutate(&mut mating_pool, &mut context);
         self.metrics.step_end(Steps::Mutation);
 
-        self.current_population = mating_pool;
+        if self.fitness_cache.is_some() {
+            Self::mark_changed_offsprings(&mut mating_pool, &parents);
+        }
 
-        self.process_fitness();
+        self.metrics.step_start(Steps::Fitness);
+        Self::evaluate(&evaluator, self.cancellation.as_ref(), &mut mating_pool);
+        self.metrics.step_end(Steps::Fitness);
+
+        // The offsprings of a cancelled generation may not all have their fitness, so they're
+        // discarded, along with their evaluations.
+        if self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            return Ok(());
+        }
+
+        self.metrics.evaluations += evaluator.evaluations();
 
         self.metrics.step_start(Steps::Elitism);
-        if self.elitism != 0 && !elitists.is_empty() {
-            self.replace_worsts_with_elitists(elitists);
+        let population = std::mem::take(&mut self.current_population);
+        self.current_population =
+            self.replacement.replace(population, parents, mating_pool, &mut context);
+
+        if self.elitism != 0 && !elitists.is_empty() && !self.replacement.keeps_the_best() {
+            self.replacement.keep_elitists(&mut self.current_population, elitists, &mut context);
         }
         self.metrics.step_end(Steps::Elitism);
 
-        self.metrics
-            .record(self.current_best_fitness(), self.current_fitness_average());
+        self.record_cache_counts();
+
+        let best_is_feasible = self.current_best().get_violation() == 0.0;
+        if let Some(constraints) = &mut self.constraints {
+            constraints.update(best_is_feasible);
+        }
+
+        self.record_metrics();
+
+        self.notify_new_best();
+        self.notify_observers(|observer, evolution| observer.on_generation(evolution));
+
+        Ok(())
     }
 
-    /// This method runs the evolution, generation over generation, until the stop condition is met.
-    pub fn run(&mut self) {
+    /// This method runs the evolution, generation over generation, until the stop condition is met
+    /// or a generation fails.
+    pub fn run(&mut self) -> Result<(), EvolutionError> {
         self.start();
 
+        self.continue_run()
+    }
+
+    /// Runs the evolution from the current generation until the stop condition is met, without
+    /// generating a new population. Use it to carry on an evolution restored with
+    /// [`Evolution::resume`].
+    pub fn continue_run(&mut self) -> Result<(), EvolutionError> {
         while !self.reached_stop_condition() {
-            self.next();
+            if let Err(error) = self.next() {
+                self.finish();
+                return Err(error);
+            }
+
+            self.write_periodic_checkpoint();
         }
 
-        self.metrics.end_clock();
+        self.finish();
+
+        Ok(())
     }
 
     pub fn population_digest(&self) {
@@ -189,6 +356,8 @@ impl<T: Individual> Evolution<T> {
     /// Crossover time: X.XXXXs (XX.XX%)
     /// Mutation time: X.XXXXs (XX.XX%)
     /// Fitness time: X.XXXXs (XX.XX%)
+    /// Elitism time: X.XXXXs (XX.XX%)
+    /// Fitness cache: X hits, X misses
     /// ---------------------------------------
     /// ```
     pub fn time_digest(&self) {
@@ -232,6 +401,12 @@ impl<T: Individual> Evolution<T> {
                 / self.metrics.total_time() as f64
                 * 100.0
         );
+        if self.fitness_cache.is_some() {
+            println!(
+                "Fitness cache: {} hits, {} misses",
+                self.metrics.cache_hits, self.metrics.cache_misses
+            );
+        }
         println!("---------------------------------------");
     }
 
@@ -239,26 +414,33 @@ impl<T: Individual> Evolution<T> {
     pub fn current_best(&self) -> &T {
         self.current_population
             .par_iter()
-            .max_by(|a, b| Self::cmp_by_fitness(a, b))
+            .max_by(|a, b| self.cmp_by_fitness(a, b))
             .unwrap()
     }
 
-    /// Returns a copy of the current population sorted by fitness.
+    /// Returns a copy of the current population sorted by fitness, from the worst to the best.
     pub fn current_population(&self) -> Vec<T> {
         let mut current_population = self.current_population.clone();
 
-        current_population.sort_by(|a, b| Self::cmp_by_fitness(a, b));
+        current_population.sort_by(|a, b| self.cmp_by_fitness(a, b));
 
         current_population
     }
 
-    /// Returns if the stop condition was already met for this evolution object.
+    /// Returns if the stop condition was already met for this evolution object, or if an observer
+    /// or the cancellation token stopped it.
     pub fn reached_stop_condition(&self) -> bool {
-        (self.stop_condition)(
-            self.current_best_fitness(),
-            self.metrics.iterations,
-            self.metrics.gens_without_improvement,
-        )
+        self.aborted || self.is_cancelled() || self.stop_condition.is_met(&self.metrics)
+    }
+
+    /// Returns if the evolution was stopped by its [`CancellationToken`].
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Returns whether the fitness is maximized or minimized.
+    pub fn objective(&self) -> Objective {
+        self.objective
     }
 
     pub fn current_best_fitness(&self) -> f64 {
@@ -275,6 +457,19 @@ impl<T: Individual> Evolution<T> {
         sum / self.config.population_size as f64
     }
 
+    /// Standard deviation of the fitness of the current population.
+    pub fn current_fitness_std_dev(&self) -> f64 {
+        let average = self.current_fitness_average();
+        let variance = self
+            .current_population
+            .par_iter()
+            .map(|individual| (individual.get_fitness() - average).powi(2))
+            .sum::<f64>()
+            / self.config.population_size as f64;
+
+        variance.sqrt()
+    }
+
     pub fn plot_chart(
         &self,
         path: impl Into<String>,
@@ -283,58 +478,1179 @@ impl<T: Individual> Evolution<T> {
         self.metrics.plot_chart(&path.into(), &test_name.into())
     }
 
+    /// Plots the metrics as set by the `options`, see [`ChartOptions`].
+    pub fn plot(&self, options: &ChartOptions) -> Result<(), Box<dyn std::error::Error>> {
+        self.metrics.plot(options)
+    }
+
+    /// Plots the diversity of the population, see [`Metrics::plot_diversity`].
+    pub fn plot_diversity(
+        &self,
+        path: impl Into<String>,
+        test_name: impl Into<String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.metrics.plot_diversity(&path.into(), &test_name.into())
+    }
+
+    /// Stops the clock and notifies the observers that the evolution finished.
+    pub(crate) fn finish(&mut self) {
+        self.metrics.end_clock();
+
+        self.notify_observers(|observer, evolution| {
+            observer.on_finish(evolution);
+            ControlFlow::Continue(())
+        });
+    }
+
+    fn write_periodic_checkpoint(&mut self) {
+        let Some(checkpoint) = &self.checkpoint else {
+            return;
+        };
+        if checkpoint.every == 0 || !self.metrics.iterations.is_multiple_of(checkpoint.every) {
+            return;
+        }
+
+        // A failed checkpoint shouldn't kill a long run, the next one may succeed, so it's up to
+        // the observers to stop it.
+        if let Err(error) = (checkpoint.write)(self, &checkpoint.path) {
+            let path = checkpoint.path.clone();
+            self.notify_observers(|observer, evolution| {
+                observer.on_checkpoint_error(evolution, &path, error.as_ref())
+            });
+        }
+    }
+
+    /// Calls `hook` on every observer. If any of them breaks, the evolution is stopped.
+    fn notify_observers<F>(&mut self, mut hook: F)
+    where
+        F: FnMut(&mut dyn EvolutionObserver<T>, &Self) -> ControlFlow<()>,
+    {
+        // The observers are taken out so they can be borrowed mutably along with the evolution.
+        let mut observers = std::mem::take(&mut self.observers);
+
+        for observer in observers.iter_mut() {
+            if hook(observer.as_mut(), self).is_break() {
+                self.aborted = true;
+            }
+        }
+
+        self.observers = observers;
+    }
+
+    fn notify_new_best(&mut self) {
+        if self.observers.is_empty() || !self.found_new_best() {
+            return;
+        }
+
+        let best = self.current_best().clone();
+
+        self.notify_observers(|observer, evolution| {
+            observer.on_new_best(evolution, &best);
+            ControlFlow::Continue(())
+        });
+    }
+
+    /// Returns if the last recorded best fitness is better than all the previous ones.
+    fn found_new_best(&self) -> bool {
+        match self.metrics.best_fitnesses.split_last() {
+            Some((&last, previous)) => previous
+                .iter()
+                .all(|&best| self.objective.is_better(last, best)),
+            None => false,
+        }
+    }
+
+    /// Returns the indexes of the `count` best individuals of the current population, from the
+    /// best to the worst.
+    pub(crate) fn best_indexes(&self, count: usize) -> Vec<usize> {
+        let mut better_heap: BinaryHeap<(RankKey, usize)> = self
+            .current_population
+            .par_iter()
+            .enumerate()
+            .map(|(index, individual)| (self.rank_key(individual), index))
+            .collect();
+
+        (0..count)
+            .map_while(|_| better_heap.pop().map(|(_, idx)| idx))
+            .collect()
+    }
+
+    /// Returns the indexes of the `count` worst individuals of the current population, from the
+    /// worst to the best.
+    pub(crate) fn worst_indexes(&self, count: usize) -> Vec<usize> {
+        // Builds a MinHeap with (fitness, idx) of the population
+        let mut worst_heap: BinaryHeap<(Reverse<RankKey>, usize)> = self
+            .current_population
+            .par_iter()
+            .enumerate()
+            .map(|(index, individual)| (Reverse(self.rank_key(individual)), index))
+            .collect();
+
+        (0..count)
+            .map_while(|_| worst_heap.pop().map(|(_, idx)| idx))
+            .collect()
+    }
+
+    pub(crate) fn population(&self) -> &Vec<T> {
+        &self.current_population
+    }
+
+    pub(crate) fn population_mut(&mut self) -> &mut Vec<T> {
+        &mut self.current_population
+    }
+
     fn find_elitists(&self) -> Vec<T> {
         if self.elitism == 1 {
             vec![self.current_best().clone()]
-        } else if self.elitism > 1 {
-            let mut better_heap: BinaryHeap<(OrderedFloat<f64>, usize)> =
-                self.current_population.par_iter().enumerate()
-                    .map(|(index, individual)| (OrderedFloat(individual.get_fitness()), index))
-                    .collect();
-
-            (0..self.elitism).into_iter().map(|_| {
-                let (_, idx) = better_heap.pop().unwrap();
-
-                self.current_population[idx].clone()
-            }).collect()
         } else {
-            vec![]
+            self.best_indexes(self.elitism as usize)
+                .into_iter()
+                .map(|idx| self.current_population[idx].clone())
+                .collect()
         }
     }
 
-    fn replace_worsts_with_elitists(&mut self, elitists: Vec<T>) {
-        // Builds a MinHeap with (fitness, idx) of the population
-        let mut worst_heap: BinaryHeap<(Reverse<OrderedFloat<f64>>, usize)> =
-            self.current_population.par_iter().enumerate()
-                .map(|(index, individual)| (Reverse(OrderedFloat(individual.get_fitness())), index))
-                .collect();
+    pub(crate) fn cmp_by_fitness(&self, a: &T, b: &T) -> std::cmp::Ordering {
+        match &self.constraints {
+            Some(constraints) => constraints.handling().compare(self.objective, a, b),
+            None => self.objective.compare(a.get_fitness(), b.get_fitness()),
+        }
+    }
+
+    /// The key that orders the individuals from the worst to the best, as `cmp_by_fitness`.
+    fn rank_key(&self, individual: &T) -> RankKey {
+        let feasibility = match &self.constraints {
+            Some(constraints) if constraints.handling().uses_feasibility() => {
+                -individual.get_violation()
+            }
+            _ => 0.0,
+        };
+
+        (
+            OrderedFloat(feasibility),
+            OrderedFloat(self.objective.oriented(individual.get_fitness())),
+        )
+    }
+
+    /// Calculates the fitness of the population. The individuals that the operators already
+    /// evaluated in the generation keep their fitness. With the fitness cache, so do the ones that
+    /// aren't dirty, unless the constraint penalty changes over the generations. Once
+    /// `cancellation` is cancelled, the remaining individuals are left without their fitness.
+    fn evaluate(
+        evaluator: &Evaluator<T>,
+        cancellation: Option<&CancellationToken>,
+        population: &mut [T],
+    ) {
+        let evaluated = evaluator.take_evaluated();
 
-        for elitist in elitists {
-            let (_, idx) = worst_heap.pop().unwrap();
+        let keeps_fitness = evaluator
+            .constraints
+            .is_none_or(|constraints| !constraints.handling().depends_on_generation());
 
-            self.current_population[idx] = elitist.clone();
+        population.par_iter_mut().for_each(|individual| {
+            if cancellation.is_some_and(CancellationToken::is_cancelled) {
+                return;
+            }
+
+            if !evaluated.is_empty()
+                && individual.chromosome_hash().is_some_and(|hash| evaluated.contains(&hash))
+            {
+                individual.set_dirty(false);
+                return;
+            }
+
+            if let Some(cache) = evaluator.cache {
+                if keeps_fitness && !individual.is_dirty() {
+                    cache.skip();
+                    return;
+                }
+            }
+
+            evaluator.evaluate(individual);
+        });
+    }
+
+    /// Marks the offsprings whose chromosome is not the one of their parent as dirty, since the
+    /// operators can change the genes without going through the individual.
+    fn mark_changed_offsprings(offsprings: &mut [T], parents: &[T]) {
+        for (offspring, parent) in offsprings.iter_mut().zip(parents) {
+            if offspring.chromosome_hash() != parent.chromosome_hash() {
+                offspring.set_dirty(true);
+            }
         }
     }
 
-    fn calculate_individual_fitness(fitness: &dyn Fitness<T>, individual: &mut T) -> f64 {
-        let fitness_value = fitness.calculate_fitness(&individual);
-        individual.set_fitness(fitness_value);
-        fitness_value
+    fn record_metrics(&mut self) {
+        self.metrics
+            .record(self.current_best_fitness(), self.current_fitness_average());
+        self.metrics.fitness_std_devs.push(self.current_fitness_std_dev());
+
+        let fitnesses: Vec<f64> =
+            self.current_population.iter().map(Individual::get_fitness).collect();
+        self.metrics.record_spread(&fitnesses);
+
+        if self.diversity_metrics {
+            self.metrics.diversities.push(Diversity::of(&self.current_population));
+        }
     }
 
-    fn cmp_by_fitness(a: &T, b: &T) -> std::cmp::Ordering {
-        a.get_fitness().partial_cmp(&b.get_fitness()).unwrap()
+    fn record_cache_counts(&mut self) {
+        if let Some(cache) = &self.fitness_cache {
+            let (hits, misses) = cache.take_counts();
+            self.metrics.cache_hits += hits;
+            self.metrics.cache_misses += misses;
+        }
     }
 
     fn process_fitness(&mut self) {
         self.metrics.step_start(Steps::Fitness);
 
-        self.current_population
-            .par_iter_mut()
-            .for_each(|individual| {
-                Self::calculate_individual_fitness(self.fitness.as_ref(), individual);
-            });
+        let evaluator = Evaluator::new(
+            self.fitness.as_ref(),
+            self.boundary_handler,
+            self.constraints.as_ref(),
+            self.fitness_cache.as_ref(),
+            self.objective,
+            self.metrics.iterations + 1,
+        );
+        Self::evaluate(&evaluator, None, &mut self.current_population);
+        self.metrics.evaluations += evaluator.evaluations();
 
         self.metrics.step_end(Steps::Fitness);
     }
 }
+
+/// Calculates the fitness of the individuals of a generation as the evolution does: through the
+/// fitness cache, penalized by the boundary handler and the constraints. The operators reach it
+/// through [`Context::evaluate`], so the fitness they calculate counts as evaluations and isn't
+/// calculated again for the same chromosome.
+pub(crate) struct Evaluator<'a, T: Individual> {
+    fitness: &'a dyn Fitness<T>,
+    boundary_handler: BoundaryHandler,
+    constraints: Option<&'a ConstraintHandler<T>>,
+    cache: Option<&'a FitnessCache>,
+    objective: Objective,
+    generation: u32,
+    evaluations: AtomicU64,
+    evaluated: Mutex<HashSet<u64>>,
+}
+
+impl<'a, T: Individual> Evaluator<'a, T> {
+    pub fn new(
+        fitness: &'a dyn Fitness<T>,
+        boundary_handler: BoundaryHandler,
+        constraints: Option<&'a ConstraintHandler<T>>,
+        cache: Option<&'a FitnessCache>,
+        objective: Objective,
+        generation: u32,
+    ) -> Self {
+        Self {
+            fitness,
+            boundary_handler,
+            constraints,
+            cache,
+            objective,
+            generation,
+            evaluations: AtomicU64::new(0),
+            evaluated: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Calculates and sets the fitness of the individual, repairing it and storing its violation
+    /// when there are constraints.
+    pub fn evaluate(&self, individual: &mut T) -> f64 {
+        let calculate = |individual: &T| {
+            self.evaluations.fetch_add(1, AtomicOrdering::Relaxed);
+            self.fitness.calculate_fitness(individual)
+        };
+
+        let evaluate = |individual: &T| {
+            let fitness = match self.cache {
+                Some(cache) => cache.get_or_calculate(individual, calculate),
+                None => calculate(individual),
+            };
+
+            self.boundary_handler.penalize(fitness, individual, self.objective)
+        };
+
+        let fitness = match self.constraints {
+            Some(constraints) => {
+                constraints.apply(individual, evaluate, self.objective, self.generation)
+            }
+            None => evaluate(individual),
+        };
+
+        individual.set_fitness(fitness);
+        individual.set_dirty(false);
+        fitness
+    }
+
+    /// Evaluates an individual created by an operator, remembering its chromosome so the
+    /// evolution keeps its fitness. The individuals without a chromosome hash are evaluated again.
+    pub fn evaluate_offspring(&self, individual: &mut T) -> f64 {
+        let fitness = self.evaluate(individual);
+
+        if let Some(hash) = individual.chromosome_hash() {
+            self.evaluated.lock().unwrap().insert(hash);
+        }
+
+        fitness
+    }
+
+    /// Number of times the fitness function was called.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations.load(AtomicOrdering::Relaxed)
+    }
+
+    fn take_evaluated(&self) -> HashSet<u64> {
+        std::mem::take(&mut self.evaluated.lock().unwrap())
+    }
+}
+
+impl<T: Individual + Serialize> Evolution<T> {
+    /// Saves the current state of the evolution to `path`: the population with its fitness, the
+    /// metrics history and the random number generator state. The evolution can be restored later
+    /// with [`Evolution::resume`].
+    pub fn checkpoint(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        Checkpoint {
+            population: self.current_population.clone(),
+            best_fitnesses: self.metrics.best_fitnesses.clone(),
+            avg_fitnesses: self.metrics.avg_fitnesses.clone(),
+            worst_fitnesses: self.metrics.worst_fitnesses.clone(),
+            median_fitnesses: self.metrics.median_fitnesses.clone(),
+            percentile_fitnesses: self.metrics.percentile_fitnesses.clone(),
+            fitness_std_devs: self.metrics.fitness_std_devs.clone(),
+            iterations: self.metrics.iterations,
+            gens_without_improvement: self.metrics.gens_without_improvement,
+            best_generation: self.metrics.best_generation,
+            best_evaluations: self.metrics.best_evaluations,
+            evaluations: self.metrics.evaluations,
+            generation_evaluations: self.metrics.generation_evaluations.clone(),
+            cache_hits: self.metrics.cache_hits,
+            cache_misses: self.metrics.cache_misses,
+            cache: self.fitness_cache.as_ref().map(FitnessCache::entries).unwrap_or_default(),
+            diversities: self.metrics.diversities.clone(),
+            step_times: Steps::ALL
+                .into_iter()
+                .filter_map(|step| self.metrics.step_time(step).map(|time| (step, time.as_nanos())))
+                .collect(),
+            rng: self.rng.clone(),
+            constraints: self.constraints.as_ref().map(ConstraintHandler::state),
+        }
+        .write(path.as_ref())
+    }
+}
+
+impl<T: Individual + DeserializeOwned> Evolution<T> {
+    /// Restores an evolution saved with [`Evolution::checkpoint`]. The operators, fitness and stop
+    /// condition are not saved, so they're taken from `builder`, which should be configured as the
+    /// one used to create the original evolution. Continue it with [`Evolution::continue_run`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use evolutionary::prelude::*;
+    /// # #[derive(Clone)]
+    /// # struct YourFitness;
+    /// # impl Fitness<Bin> for YourFitness {
+    /// #    fn calculate_fitness(&self, individual: &Bin) -> f64 { 0.0 }
+    /// # }
+    /// let builder = EvolutionBuilder::new(30, 10, GeneCod::Bin, ())
+    ///     .with_fitness(YourFitness)
+    ///     .with_selection(TournamentSelection::default())
+    ///     .with_crossover(NPointsCrossover::default())
+    ///     .with_mutation(BitFlipMutation::default())
+    ///     .with_stop_condition(move |_, iterations, _| iterations >= 1000)
+    ///     .with_checkpoint_every(50, "evolution.json");
+    ///
+    /// let mut evolution = Evolution::resume("evolution.json", &builder).unwrap();
+    ///
+    /// evolution.continue_run().unwrap();
+    /// ```
+    pub fn resume(
+        path: impl AsRef<Path>,
+        builder: &EvolutionBuilder<T>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let checkpoint = Checkpoint::<T>::read(path.as_ref())?;
+
+        let mut evolution = builder.build()?;
+
+        evolution.current_population = checkpoint.population;
+        evolution.rng = checkpoint.rng;
+        evolution.metrics.best_fitnesses = checkpoint.best_fitnesses;
+        evolution.metrics.avg_fitnesses = checkpoint.avg_fitnesses;
+        evolution.metrics.worst_fitnesses = checkpoint.worst_fitnesses;
+        evolution.metrics.median_fitnesses = checkpoint.median_fitnesses;
+        evolution.metrics.percentile_fitnesses = checkpoint.percentile_fitnesses;
+        evolution.metrics.fitness_std_devs = checkpoint.fitness_std_devs;
+        evolution.metrics.iterations = checkpoint.iterations;
+        evolution.metrics.gens_without_improvement = checkpoint.gens_without_improvement;
+        evolution.metrics.best_generation = checkpoint.best_generation;
+        evolution.metrics.best_evaluations = checkpoint.best_evaluations;
+        evolution.metrics.evaluations = checkpoint.evaluations;
+        evolution.metrics.generation_evaluations = checkpoint.generation_evaluations;
+        evolution.metrics.cache_hits = checkpoint.cache_hits;
+        evolution.metrics.cache_misses = checkpoint.cache_misses;
+        if let Some(cache) = &evolution.fitness_cache {
+            cache.restore(checkpoint.cache);
+        }
+        evolution.metrics.diversities = checkpoint.diversities;
+        for (step, nanos) in checkpoint.step_times {
+            if let Some(time) = evolution.metrics.step_times.get_mut(&step) {
+                time.2 = nanos;
+            }
+        }
+        if let (Some(constraints), Some(state)) =
+            (&mut evolution.constraints, checkpoint.constraints)
+        {
+            constraints.restore(state);
+        }
+        evolution.metrics.start_clock();
+
+        Ok(evolution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    use std::sync::{Arc, Mutex};
+
+    use crate::metrics::Steps;
+    use crate::prelude::*;
+    use crate::testing::{ones_builder, OnesFitness};
+
+    fn seeded_builder(seed: u64) -> EvolutionBuilder<Bin> {
+        ones_builder(30, 20)
+            .with_stop_condition(|_, iterations, _| iterations >= 20)
+            .with_seed(seed)
+    }
+
+    fn seeded_evolution(seed: u64) -> Evolution<Bin> {
+        seeded_builder(seed).build().unwrap()
+    }
+
+    fn chromosomes(evolution: &Evolution<Bin>) -> Vec<Vec<bool>> {
+        evolution
+            .current_population()
+            .iter()
+            .map(|individual| individual.get_chromosome().clone())
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_reproduces_the_evolution() {
+        let mut first = seeded_evolution(42);
+        let mut second = seeded_evolution(42);
+
+        first.run().unwrap();
+        second.run().unwrap();
+
+        assert_eq!(first.metrics.best_fitnesses, second.metrics.best_fitnesses);
+        assert_eq!(first.metrics.avg_fitnesses, second.metrics.avg_fitnesses);
+        assert_eq!(chromosomes(&first), chromosomes(&second));
+    }
+
+    #[test]
+    fn start_generates_population_size_members() {
+        let mut evolution = seeded_evolution(1);
+
+        evolution.start();
+
+        assert_eq!(evolution.current_population().len(), 30);
+    }
+
+    #[test]
+    fn resumed_evolution_continues_where_it_stopped() {
+        let path = std::env::temp_dir().join("evolutionary_resume_test.json");
+
+        let mut uninterrupted = seeded_evolution(7);
+        uninterrupted.run().unwrap();
+
+        let mut interrupted = seeded_evolution(7);
+        interrupted.start();
+        for _ in 0..9 {
+            interrupted.next().unwrap();
+        }
+        interrupted.checkpoint(&path).unwrap();
+
+        let mut resumed = Evolution::resume(&path, &seeded_builder(7)).unwrap();
+        resumed.continue_run().unwrap();
+
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(resumed.metrics.iterations, uninterrupted.metrics.iterations);
+        assert_eq!(resumed.metrics.best_fitnesses, uninterrupted.metrics.best_fitnesses);
+        assert_eq!(resumed.metrics.avg_fitnesses, uninterrupted.metrics.avg_fitnesses);
+        assert_eq!(chromosomes(&resumed), chromosomes(&uninterrupted));
+    }
+
+    #[derive(Clone)]
+    struct NonFiniteFitness;
+
+    impl Fitness<Bin> for NonFiniteFitness {
+        fn calculate_fitness(&self, individual: &Bin) -> f64 {
+            match OnesFitness.calculate_fitness(individual) as u32 % 3 {
+                0 => f64::NAN,
+                1 => f64::INFINITY,
+                _ => f64::NEG_INFINITY,
+            }
+        }
+    }
+
+    #[test]
+    fn checkpoints_non_finite_fitness() {
+        let path = std::env::temp_dir().join("evolutionary_non_finite_checkpoint_test.json");
+        let builder = seeded_builder(5).with_fitness(NonFiniteFitness).with_fitness_cache(100);
+        // Every NaN is read back as the same one.
+        let bits = |values: &[f64]| {
+            values
+                .iter()
+                .map(|value| if value.is_nan() { f64::NAN } else { *value }.to_bits())
+                .collect::<Vec<_>>()
+        };
+
+        let mut evolution = builder.build().unwrap();
+        evolution.start();
+        evolution.next().unwrap();
+        evolution.checkpoint(&path).unwrap();
+
+        let resumed = Evolution::resume(&path, &builder).unwrap();
+
+        let _ = std::fs::remove_file(&path);
+
+        let fitnesses = |evolution: &Evolution<Bin>| {
+            evolution.current_population.iter().map(Bin::get_fitness).collect::<Vec<_>>()
+        };
+        assert!(fitnesses(&evolution).iter().any(|fitness| fitness.is_nan()));
+        assert!(fitnesses(&evolution).iter().any(|fitness| fitness.is_infinite()));
+        assert_eq!(bits(&fitnesses(&resumed)), bits(&fitnesses(&evolution)));
+        assert_eq!(
+            bits(&resumed.metrics.best_fitnesses),
+            bits(&evolution.metrics.best_fitnesses)
+        );
+        assert_eq!(bits(&resumed.metrics.avg_fitnesses), bits(&evolution.metrics.avg_fitnesses));
+
+        let cached = |evolution: &Evolution<Bin>| {
+            let entries = evolution.fitness_cache.as_ref().unwrap().entries();
+            let (keys, fitnesses): (Vec<u64>, Vec<f64>) = entries.into_iter().unzip();
+            (keys, bits(&fitnesses))
+        };
+        assert_eq!(cached(&resumed), cached(&evolution));
+    }
+
+    #[derive(Clone)]
+    struct AtMostFifteen;
+
+    impl Constraints<Bin> for AtMostFifteen {
+        fn violations(&self, individual: &Bin) -> Vec<f64> {
+            let ones = individual.get_chromosome().iter().filter(|&&gene| gene).count();
+
+            vec![ones as f64 - 15.0]
+        }
+    }
+
+    #[test]
+    fn resumed_evolution_keeps_every_metric() {
+        let path = std::env::temp_dir().join("evolutionary_resume_metrics_test.json");
+        let builder = seeded_builder(11)
+            .with_diversity_metrics()
+            .with_percentiles([25.0, 75.0])
+            .with_fitness_cache(100)
+            .with_constraints(
+                AtMostFifteen,
+                ConstraintHandling::AdaptivePenalty {
+                    initial: 0.5,
+                    increase: 2.0,
+                    decrease: 1.5,
+                    window: 2,
+                },
+            );
+
+        let mut uninterrupted = builder.build().unwrap();
+        uninterrupted.run().unwrap();
+
+        let mut interrupted = builder.build().unwrap();
+        interrupted.start();
+        for _ in 0..9 {
+            interrupted.next().unwrap();
+        }
+        interrupted.checkpoint(&path).unwrap();
+        let fitness_time = interrupted.metrics.step_time(Steps::Fitness).unwrap();
+
+        let mut resumed = Evolution::resume(&path, &builder).unwrap();
+        assert_eq!(resumed.metrics.step_time(Steps::Fitness), Some(fitness_time));
+        resumed.continue_run().unwrap();
+
+        let _ = std::fs::remove_file(&path);
+
+        let (a, b) = (&resumed.metrics, &uninterrupted.metrics);
+        assert_eq!(a.best_fitnesses, b.best_fitnesses);
+        assert_eq!(a.avg_fitnesses, b.avg_fitnesses);
+        assert_eq!(a.worst_fitnesses, b.worst_fitnesses);
+        assert_eq!(a.median_fitnesses, b.median_fitnesses);
+        assert_eq!(a.percentile_fitnesses, b.percentile_fitnesses);
+        assert_eq!(a.fitness_std_devs, b.fitness_std_devs);
+        assert_eq!(a.diversities, b.diversities);
+        assert_eq!(a.generation_evaluations, b.generation_evaluations);
+        assert_eq!(a.evaluations, b.evaluations);
+        assert_eq!(a.best_generation, b.best_generation);
+        assert_eq!(a.best_evaluations, b.best_evaluations);
+        assert_eq!((a.cache_hits, a.cache_misses), (b.cache_hits, b.cache_misses));
+        assert_eq!(a.diversities.len(), a.best_fitnesses.len());
+        assert_eq!(chromosomes(&resumed), chromosomes(&uninterrupted));
+    }
+
+    #[test]
+    fn writes_periodic_checkpoints() {
+        let path = std::env::temp_dir().join("evolutionary_periodic_checkpoint_test.json");
+        let _ = std::fs::remove_file(&path);
+
+        let builder = seeded_builder(3).with_checkpoint_every(5, &path);
+        builder.build().unwrap().run().unwrap();
+
+        let resumed = Evolution::resume(&path, &builder).unwrap();
+
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(resumed.metrics.iterations, 20);
+    }
+
+    #[derive(Clone, Default)]
+    struct CheckpointErrors {
+        paths: Arc<Mutex<Vec<std::path::PathBuf>>>,
+        stop: bool,
+    }
+
+    impl EvolutionObserver<Bin> for CheckpointErrors {
+        fn on_checkpoint_error(
+            &mut self,
+            _evolution: &Evolution<Bin>,
+            path: &std::path::Path,
+            _error: &dyn std::error::Error,
+        ) -> ControlFlow<()> {
+            self.paths.lock().unwrap().push(path.to_path_buf());
+
+            if self.stop {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    }
+
+    #[test]
+    fn reports_the_failed_checkpoints() {
+        let path = std::env::temp_dir().join("evolutionary_missing_dir").join("checkpoint.json");
+
+        for (stop, failures, iterations) in [(false, 4, 20), (true, 1, 5)] {
+            let errors = CheckpointErrors { stop, ..CheckpointErrors::default() };
+            let mut evolution = seeded_builder(3)
+                .with_checkpoint_every(5, &path)
+                .with_observer(errors.clone())
+                .build()
+                .unwrap();
+            evolution.run().unwrap();
+
+            assert_eq!(*errors.paths.lock().unwrap(), vec![path.clone(); failures]);
+            assert_eq!(evolution.metrics.iterations, iterations);
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder {
+        events: Arc<Mutex<Vec<String>>>,
+        abort_at: u32,
+    }
+
+    impl EvolutionObserver<Bin> for Recorder {
+        fn on_start(&mut self, _evolution: &Evolution<Bin>) {
+            self.events.lock().unwrap().push("start".to_string());
+        }
+
+        fn on_generation(&mut self, evolution: &Evolution<Bin>) -> ControlFlow<()> {
+            self.events.lock().unwrap().push("generation".to_string());
+
+            if evolution.metrics.iterations >= self.abort_at {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+
+        fn on_new_best(&mut self, _evolution: &Evolution<Bin>, best: &Bin) {
+            self.events.lock().unwrap().push(format!("best {}", best.get_fitness()));
+        }
+
+        fn on_finish(&mut self, _evolution: &Evolution<Bin>) {
+            self.events.lock().unwrap().push("finish".to_string());
+        }
+    }
+
+    #[test]
+    fn observers_are_notified_and_can_stop_the_run() {
+        let recorder = Recorder {
+            abort_at: 5,
+            ..Default::default()
+        };
+
+        let mut evolution = seeded_builder(11)
+            .with_observer(recorder.clone())
+            .build()
+            .unwrap();
+        evolution.run().unwrap();
+
+        let events = recorder.events.lock().unwrap();
+        let generations = events.iter().filter(|event| *event == "generation").count();
+
+        assert_eq!(evolution.metrics.iterations, 5);
+        assert_eq!(generations, 4);
+        assert_eq!(events[0], format!("best {}", evolution.metrics.best_fitnesses[0]));
+        assert_eq!(events[1], "start");
+        assert_eq!(events.last().unwrap(), "finish");
+
+        let bests: Vec<&String> = events.iter().filter(|e| e.starts_with("best")).collect();
+        let improvements = evolution
+            .metrics
+            .best_fitnesses
+            .windows(2)
+            .filter(|pair| pair[1] > pair[0])
+            .count();
+
+        assert_eq!(bests.len(), improvements + 1);
+    }
+
+    fn minimized_best<S: Selection<Bin>>(selection: S) -> f64 {
+        let mut evolution = seeded_builder(5)
+            .with_selection(selection)
+            .with_stop_condition(|_, iterations, _| iterations >= 60)
+            .minimize()
+            .build()
+            .unwrap();
+        evolution.run().unwrap();
+
+        let bests = &evolution.metrics.best_fitnesses;
+        assert!(bests.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(evolution.metrics.best_so_far(), bests.last().copied());
+
+        evolution.current_best_fitness()
+    }
+
+    #[test]
+    fn minimizes_the_fitness_with_every_selection() {
+        assert_eq!(minimized_best(TournamentSelection::default()), 0.0);
+        assert_eq!(minimized_best(RankSelection), 0.0);
+        assert_eq!(minimized_best(RouletteSelection::default()), 0.0);
+        assert_eq!(minimized_best(StochasticUniversalSamplingSelection::default()), 0.0);
+    }
+
+    #[derive(Clone)]
+    struct NegativeFitness;
+
+    impl Fitness<Bin> for NegativeFitness {
+        fn calculate_fitness(&self, individual: &Bin) -> f64 {
+            OnesFitness.calculate_fitness(individual) - 100.0
+        }
+    }
+
+    #[test]
+    fn negative_fitness_needs_a_scaling() {
+        let builder = seeded_builder(9).with_fitness(NegativeFitness);
+
+        let mut unscaled = builder
+            .clone()
+            .with_selection(RouletteSelection::default())
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            unscaled.run(),
+            Err(EvolutionError::Selection(SelectionError::NegativeFitness(_)))
+        ));
+        assert_eq!(unscaled.metrics.iterations, 1);
+
+        let mut scaled = builder
+            .with_selection(StochasticUniversalSamplingSelection {
+                scaling: FitnessScaling::Sigma { c: 2.0 },
+            })
+            .build()
+            .unwrap();
+
+        assert!(scaled.run().is_ok());
+        assert_eq!(scaled.metrics.iterations, 20);
+    }
+
+    #[derive(Clone)]
+    struct SumFitness;
+
+    impl Fitness<Real> for SumFitness {
+        fn calculate_fitness(&self, individual: &Real) -> f64 {
+            individual.chromosome.iter().sum()
+        }
+    }
+
+    fn run_with_boundary_handler(boundary_handler: BoundaryHandler) -> Evolution<Real> {
+        let mut evolution = EvolutionBuilder::new(20, 5, GeneCod::Real, vec![(0.0, 1.0); 5])
+            .with_fitness(SumFitness)
+            .with_selection(TournamentSelection::default())
+            .with_crossover(BlendCrossover::default())
+            .with_mutation(GaussianMutation {
+                mutation_rate: 0.5,
+                sigma: 10.0,
+            })
+            .with_boundary_handler(boundary_handler)
+            .with_stop_condition(|_, iterations, _| iterations >= 10)
+            .with_seed(3)
+            .build()
+            .unwrap();
+
+        evolution.run().unwrap();
+        evolution
+    }
+
+    #[test]
+    fn operators_apply_the_boundary_handler() {
+        for handler in [
+            BoundaryHandler::Clamp,
+            BoundaryHandler::Reflect,
+            BoundaryHandler::Wrap,
+            BoundaryHandler::RandomReinit,
+            BoundaryHandler::Resample { max_attempts: 10 },
+        ] {
+            let evolution = run_with_boundary_handler(handler);
+
+            assert!(evolution
+                .current_population()
+                .iter()
+                .all(|individual| individual.bounds_violation() == 0.0));
+        }
+
+        let penalized = run_with_boundary_handler(BoundaryHandler::Penalty { coefficient: 2.0 });
+
+        assert!(penalized.current_population().iter().all(|individual| {
+            let sum: f64 = individual.chromosome.iter().sum();
+            individual.get_fitness() == sum - 2.0 * individual.bounds_violation()
+        }));
+        assert!(penalized
+            .current_population()
+            .iter()
+            .any(|individual| individual.bounds_violation() > 0.0));
+    }
+
+    #[derive(Clone)]
+    struct SumAtMost(f64);
+
+    impl Constraints<Real> for SumAtMost {
+        fn violations(&self, individual: &Real) -> Vec<f64> {
+            vec![individual.chromosome.iter().sum::<f64>() - self.0]
+        }
+    }
+
+    #[test]
+    fn the_best_individual_respects_the_constraints() {
+        for handling in [
+            ConstraintHandling::FeasibilityRules,
+            ConstraintHandling::StochasticRanking { pf: 0.45 },
+            ConstraintHandling::StaticPenalty { coefficient: 100.0 },
+        ] {
+            let mut evolution = EvolutionBuilder::new(30, 5, GeneCod::Real, (0.0, 1.0))
+                .with_fitness(SumFitness)
+                .with_selection(TournamentSelection::default())
+                .with_crossover(BlendCrossover::default())
+                .with_mutation(GaussianMutation::default())
+                .with_constraints(SumAtMost(2.0), handling)
+                .with_elitism(2)
+                .with_stop_condition(|_, iterations, _| iterations >= 50)
+                .with_seed(0)
+                .build()
+                .unwrap();
+
+            evolution.run().unwrap();
+
+            let best = evolution.current_best();
+            let sum: f64 = best.chromosome.iter().sum();
+
+            assert_eq!(best.get_violation(), 0.0);
+            assert!(sum <= 2.0 && sum > 1.5, "{:?}: {}", handling, sum);
+        }
+    }
+
+    fn run_with_replacement<R: Replacement<Real>>(replacement: R) -> Evolution<Real> {
+        let mut evolution = EvolutionBuilder::new(20, 5, GeneCod::Real, (0.0, 1.0))
+            .with_fitness(SumFitness)
+            .with_selection(TournamentSelection::default())
+            .with_crossover(BlendCrossover::default())
+            .with_mutation(GaussianMutation::default())
+            .with_replacement(replacement)
+            .with_elitism(0)
+            .with_stop_condition(|_, iterations, _| iterations >= 30)
+            .with_seed(1)
+            .build()
+            .unwrap();
+
+        evolution.run().unwrap();
+        evolution
+    }
+
+    #[test]
+    fn replacements_keep_the_population_size() {
+        let evolutions = [
+            run_with_replacement(GenerationalReplacement),
+            run_with_replacement(SteadyStateReplacement::new(2, SteadyStateTarget::Worst)),
+            run_with_replacement(SteadyStateReplacement::new(4, SteadyStateTarget::Oldest)),
+            run_with_replacement(SteadyStateReplacement::new(2, SteadyStateTarget::Parent)),
+            run_with_replacement(MuPlusLambdaReplacement { lambda: 7 }),
+            run_with_replacement(MuCommaLambdaReplacement { lambda: 45 }),
+            run_with_replacement(GenerationGapReplacement { gap: 0.3 }),
+        ];
+
+        for evolution in evolutions {
+            assert_eq!(evolution.current_population().len(), 20);
+            assert!(evolution.current_best_fitness() > evolution.metrics.best_fitnesses[0]);
+        }
+
+        let best_fitnesses = run_with_replacement(MuPlusLambdaReplacement { lambda: 7 })
+            .metrics
+            .best_fitnesses;
+
+        assert!(best_fitnesses.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn checks_the_replacement() {
+        let builder = EvolutionBuilder::new(20, 5, GeneCod::Real, (0.0, 1.0))
+            .with_fitness(SumFitness)
+            .with_selection(TournamentSelection::default())
+            .with_crossover(BlendCrossover::default())
+            .with_mutation(GaussianMutation::default())
+            .with_stop_condition(|_, iterations, _| iterations >= 30);
+
+        assert!(builder
+            .clone()
+            .with_replacement(MuCommaLambdaReplacement { lambda: 10 })
+            .build()
+            .is_err());
+        assert!(builder
+            .with_replacement(SteadyStateReplacement::new(20, SteadyStateTarget::Worst))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn checks_the_operators() {
+        let builder = EvolutionBuilder::new(20, 5, GeneCod::Real, (0.0, 1.0))
+            .with_fitness(SumFitness)
+            .with_selection(TournamentSelection::default())
+            .with_crossover(BlendCrossover::default())
+            .with_mutation(GaussianMutation::default())
+            .with_stop_condition(|_, iterations, _| iterations >= 30);
+
+        let sbx = SimulatedBinaryCrossover {
+            gene_probability: 2.0,
+            ..SimulatedBinaryCrossover::default()
+        };
+        let gaussian = GaussianMutation {
+            sigma: -1.0,
+            ..GaussianMutation::default()
+        };
+
+        assert!(builder.build().is_ok());
+        assert_eq!(
+            builder.clone().with_crossover(sbx).build().err().unwrap(),
+            "The gene probability must be in [0, 1], got 2"
+        );
+        assert!(builder.clone().with_mutation(gaussian).build().is_err());
+        assert_eq!(
+            builder.clone().with_selection(TournamentSelection::new(1, 1.0)).build().err().unwrap(),
+            "The tournament size must be at least 2, got 1"
+        );
+        assert!(builder.with_selection(TournamentSelection::new(2, 2.0)).build().is_err());
+    }
+
+    #[derive(Clone)]
+    struct CountingFitness(Arc<AtomicUsize>);
+
+    impl Fitness<Bin> for CountingFitness {
+        fn calculate_fitness(&self, individual: &Bin) -> f64 {
+            self.0.fetch_add(1, AtomicOrdering::Relaxed);
+            individual.get_chromosome().iter().filter(|&&gene| gene).count() as f64
+        }
+    }
+
+    fn run_counting(fitness_cache: Option<usize>) -> (Evolution<Bin>, usize) {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let mut builder = EvolutionBuilder::new(20, 8, GeneCod::Bin, ())
+            .with_fitness(CountingFitness(calls.clone()))
+            .with_selection(TournamentSelection::default())
+            .with_crossover(NPointsCrossover::default())
+            .with_mutation(BitFlipMutation::default())
+            .with_stop_condition(|_, iterations, _| iterations >= 20)
+            .with_seed(5);
+
+        if let Some(capacity) = fitness_cache {
+            builder = builder.with_fitness_cache(capacity);
+        }
+
+        let mut evolution = builder.build().unwrap();
+        evolution.run().unwrap();
+
+        let calls = calls.load(AtomicOrdering::Relaxed);
+        (evolution, calls)
+    }
+
+    #[test]
+    fn the_fitness_cache_avoids_evaluations() {
+        let (uncached, all_calls) = run_counting(None);
+
+        for capacity in [0, 1000] {
+            let (cached, calls) = run_counting(Some(capacity));
+
+            assert_eq!(cached.metrics.best_fitnesses, uncached.metrics.best_fitnesses);
+            assert_eq!(cached.metrics.avg_fitnesses, uncached.metrics.avg_fitnesses);
+            assert_eq!(cached.metrics.cache_misses as usize, calls);
+            assert_eq!(cached.metrics.cache_hits as usize + calls, all_calls);
+            assert!(calls < all_calls);
+        }
+
+        assert_eq!(uncached.metrics.cache_hits, 0);
+    }
+
+    #[test]
+    fn stops_at_the_evaluation_budget() {
+        let mut evolution = seeded_builder(2)
+            .with_stop(StopCondition::MaxEvaluations(1000).or(StopCondition::MaxGenerations(1000)))
+            .build()
+            .unwrap();
+        evolution.run().unwrap();
+
+        assert!((1000..1000 + 30).contains(&evolution.metrics.evaluations));
+        assert_eq!(
+            evolution.metrics.fitness_std_devs.len(),
+            evolution.metrics.best_fitnesses.len()
+        );
+    }
+
+    #[derive(Clone)]
+    struct CountingSum(Arc<AtomicUsize>);
+
+    impl Fitness<Real> for CountingSum {
+        fn calculate_fitness(&self, individual: &Real) -> f64 {
+            self.0.fetch_add(1, AtomicOrdering::Relaxed);
+            SumFitness.calculate_fitness(individual)
+        }
+    }
+
+    #[test]
+    fn counts_the_evaluations_of_the_linear_crossover() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let mut evolution = EvolutionBuilder::new(20, 5, GeneCod::Real, (0.0, 1.0))
+            .with_fitness(CountingSum(Arc::clone(&calls)))
+            .with_selection(TournamentSelection::default())
+            .with_crossover(LinearCrossover { crossover_rate: 1.0 })
+            .with_mutation(GaussianMutation::default())
+            .with_constraints(
+                SumAtMost(2.0),
+                ConstraintHandling::StaticPenalty { coefficient: 3.0 },
+            )
+            .with_stop(StopCondition::MaxEvaluations(500))
+            .with_seed(6)
+            .build()
+            .unwrap();
+        evolution.run().unwrap();
+
+        // Each generation evaluates 3 candidates per pair and the mutated offsprings.
+        let evaluations = evolution.metrics.evaluations;
+        assert_eq!(evaluations, calls.load(AtomicOrdering::Relaxed) as u64);
+        assert!((500..500 + 50).contains(&evaluations));
+
+        // The offsprings kept the fitness penalized by the evolution.
+        assert!(evolution.current_population().iter().all(|individual| {
+            let sum: f64 = individual.chromosome.iter().sum();
+            individual.get_fitness() == sum - 3.0 * (sum - 2.0).max(0.0)
+        }));
+    }
+
+    #[test]
+    fn records_the_spread_of_the_fitness() {
+        assert!(seeded_builder(4).with_percentiles([150.0]).build().is_err());
+
+        let mut evolution = seeded_builder(4).with_percentiles([10.0, 90.0]).build().unwrap();
+        evolution.run().unwrap();
+
+        let metrics = &evolution.metrics;
+        assert_eq!(metrics.percentile_fitnesses.len(), metrics.best_fitnesses.len());
+
+        for (i, percentiles) in metrics.percentile_fitnesses.iter().enumerate() {
+            assert!(metrics.worst_fitnesses[i] <= percentiles[0]);
+            assert!(percentiles[0] <= metrics.median_fitnesses[i]);
+            assert!(metrics.median_fitnesses[i] <= percentiles[1]);
+            assert!(percentiles[1] <= metrics.best_fitnesses[i]);
+        }
+
+        let best_generation = metrics.best_generation as usize;
+        assert_eq!(Some(metrics.best_fitnesses[best_generation]), metrics.best_so_far());
+        assert!(metrics.best_evaluations <= metrics.evaluations);
+    }
+
+    #[test]
+    fn records_the_diversity_when_asked() {
+        let mut evolution = seeded_builder(3).build().unwrap();
+        evolution.run().unwrap();
+        assert!(evolution.metrics.diversities.is_empty());
+
+        let mut evolution = seeded_builder(3).with_diversity_metrics().build().unwrap();
+        evolution.run().unwrap();
+
+        let diversities = &evolution.metrics.diversities;
+        assert_eq!(diversities.len(), evolution.metrics.best_fitnesses.len());
+        assert!(diversities.iter().all(|d| d.unique_chromosomes.is_some_and(|u| u <= 30)));
+        assert!(diversities[0].entropy > diversities[diversities.len() - 1].entropy);
+    }
+
+    #[derive(Clone)]
+    struct CancellingFitness {
+        calls: Arc<AtomicUsize>,
+        cancel_after: usize,
+        token: CancellationToken,
+    }
+
+    impl Fitness<Bin> for CancellingFitness {
+        fn calculate_fitness(&self, individual: &Bin) -> f64 {
+            if self.calls.fetch_add(1, AtomicOrdering::Relaxed) + 1 >= self.cancel_after {
+                self.token.cancel();
+            }
+            OnesFitness.calculate_fitness(individual)
+        }
+    }
+
+    #[test]
+    fn cancellation_keeps_the_last_complete_generation() {
+        // The initial population and the first 3 generations take 120 evaluations, so the 4th
+        // generation is cancelled while being evaluated.
+        for cancel_after in [30 * 4 + 1, 30 * 5] {
+            let token = CancellationToken::new();
+            let fitness = CancellingFitness {
+                calls: Arc::new(AtomicUsize::new(0)),
+                cancel_after,
+                token: token.clone(),
+            };
+
+            let mut evolution = seeded_builder(3)
+                .with_fitness(fitness)
+                .with_cancellation(token)
+                .build()
+                .unwrap();
+            evolution.run().unwrap();
+
+            assert!(evolution.is_cancelled());
+            assert_eq!(evolution.metrics.iterations, 4);
+            assert_eq!(evolution.metrics.evaluations, 30 * 4);
+            assert!(evolution.current_population().iter().all(|individual| {
+                individual.get_fitness() == OnesFitness.calculate_fitness(individual)
+            }));
+        }
+    }
+}
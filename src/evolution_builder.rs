@@ -1,11 +1,23 @@
 use crate::{
+    boundary::BoundaryHandler,
+    cancellation::CancellationToken,
+    checkpoint::CheckpointConfig,
+    constraints::{ConstraintHandler, ConstraintHandling, Constraints},
     crossover::Crossover,
     evolution::{Evolution, EvolutionConfig, StopConditionFn},
     fitness::Fitness,
     mutation::Mutation,
+    objective::Objective,
+    observer::EvolutionObserver,
     population::{GeneCod, Individual},
+    replacement::{GenerationalReplacement, Replacement},
+    rng::EvolutionRng,
     selection::Selection,
+    stop_condition::StopCondition,
 };
+use rand::SeedableRng;
+use serde::Serialize;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 /// This is the helper struct to create a new Evolution object. The `fitness`, `selection`,
@@ -40,8 +52,45 @@ pub struct EvolutionBuilder<T: Individual> {
     selection: Option<Box<dyn Selection<T>>>,
     crossover: Option<Box<dyn Crossover<T>>>,
     mutation: Option<Box<dyn Mutation<T>>>,
+    replacement: Box<dyn Replacement<T>>,
     elitism: Option<u32>,
-    stop_condition: Option<StopConditionFn>,
+    stop_condition: Option<StopCondition>,
+    seed: Option<u64>,
+    objective: Objective,
+    boundary_handler: BoundaryHandler,
+    constraints: Option<ConstraintHandler<T>>,
+    fitness_cache: Option<usize>,
+    cancellation: Option<CancellationToken>,
+    diversity_metrics: bool,
+    percentiles: Vec<f64>,
+    checkpoint: Option<CheckpointConfig<T>>,
+    observers: Vec<Box<dyn EvolutionObserver<T>>>,
+}
+
+impl<T: Individual> Clone for EvolutionBuilder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            title: self.title.clone(),
+            evolution_config: self.evolution_config.clone(),
+            fitness: self.fitness.as_ref().map(|f| dyn_clone::clone_box(f.as_ref())),
+            selection: self.selection.as_ref().map(|s| dyn_clone::clone_box(s.as_ref())),
+            crossover: self.crossover.as_ref().map(|c| dyn_clone::clone_box(c.as_ref())),
+            mutation: self.mutation.as_ref().map(|m| dyn_clone::clone_box(m.as_ref())),
+            replacement: dyn_clone::clone_box(self.replacement.as_ref()),
+            elitism: self.elitism,
+            stop_condition: self.stop_condition.clone(),
+            seed: self.seed,
+            objective: self.objective,
+            boundary_handler: self.boundary_handler,
+            constraints: self.constraints.clone(),
+            fitness_cache: self.fitness_cache,
+            cancellation: self.cancellation.clone(),
+            diversity_metrics: self.diversity_metrics,
+            percentiles: self.percentiles.clone(),
+            checkpoint: self.checkpoint.clone(),
+            observers: self.cloned_observers(),
+        }
+    }
 }
 
 impl<T: Individual> EvolutionBuilder<T> {
@@ -49,10 +98,10 @@ impl<T: Individual> EvolutionBuilder<T> {
         population_size: u32,
         dimension: u32,
         gene_cod: GeneCod,
-        range: T::RangeType,
+        range: impl Into<T::RangeType>,
     ) -> Self {
         let evolution_config = Some(EvolutionConfig {
-            range,
+            range: range.into(),
             dimension,
             gene_cod,
             population_size,
@@ -65,8 +114,19 @@ impl<T: Individual> EvolutionBuilder<T> {
             selection: None,
             crossover: None,
             mutation: None,
+            replacement: Box::new(GenerationalReplacement),
             stop_condition: None,
             elitism: None,
+            seed: None,
+            objective: Objective::Maximize,
+            boundary_handler: BoundaryHandler::default(),
+            constraints: None,
+            fitness_cache: None,
+            cancellation: None,
+            diversity_metrics: false,
+            percentiles: Vec::new(),
+            checkpoint: None,
+            observers: Vec::new(),
         }
     }
 
@@ -78,9 +138,20 @@ impl<T: Individual> EvolutionBuilder<T> {
             selection: None,
             crossover: None,
             mutation: None,
+            replacement: Box::new(GenerationalReplacement),
             stop_condition: None,
             evolution_config: Some(config.clone()),
             elitism: None,
+            seed: None,
+            objective: Objective::Maximize,
+            boundary_handler: BoundaryHandler::default(),
+            constraints: None,
+            fitness_cache: None,
+            cancellation: None,
+            diversity_metrics: false,
+            percentiles: Vec::new(),
+            checkpoint: None,
+            observers: Vec::new(),
         }
     }
 
@@ -90,6 +161,11 @@ impl<T: Individual> EvolutionBuilder<T> {
         self
     }
 
+    pub(crate) fn with_boxed_fitness(mut self, f: Box<dyn Fitness<T>>) -> Self {
+        self.fitness = Some(f);
+        self
+    }
+
     /// Sets the selection operator. Receives a struct that implements the Selection trait.
     pub fn with_selection<S: Selection<T>>(mut self, s: S) -> Self {
         self.selection = Some(Box::new(s));
@@ -109,7 +185,8 @@ impl<T: Individual> EvolutionBuilder<T> {
     }
 
     /// Sets the stop condition. Receives a closure that receives the best fitness, the current
-    /// iteration and the number of generations without improvement and returns a boolean.
+    /// iteration and the number of generations without improvement and returns a boolean. For
+    /// the other stop conditions, use [`with_stop`](Self::with_stop).
     ///
     /// # Example
     ///
@@ -121,7 +198,23 @@ impl<T: Individual> EvolutionBuilder<T> {
         mut self,
         f: F,
     ) -> Self {
-        self.stop_condition = Some(Arc::new(f));
+        self.stop_condition = Some(StopCondition::from(Arc::new(f) as StopConditionFn));
+        self
+    }
+
+    /// Sets the stop condition from the [`StopCondition`]s, which see all the metrics of the
+    /// evolution, like the number of fitness evaluations or the time it's running. Replaces the
+    /// one of [`with_stop_condition`](Self::with_stop_condition).
+    ///
+    /// # Example
+    ///
+    /// ```text
+    /// evolution_builder.with_stop(
+    ///     StopCondition::MaxEvaluations(100_000).or(StopCondition::Stagnation(50)),
+    /// )
+    /// ```
+    pub fn with_stop(mut self, stop_condition: StopCondition) -> Self {
+        self.stop_condition = Some(stop_condition);
         self
     }
 
@@ -137,7 +230,127 @@ impl<T: Individual> EvolutionBuilder<T> {
         self
     }
 
+    /// Sets the seed of the random number generator, making the evolution reproducible: two
+    /// evolutions built with the same seed and parameters produce exactly the same generations.
+    /// When it's not set, the generator is seeded from the system entropy.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets whether the fitness is maximized or minimized. Defaults to [`Objective::Maximize`].
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Makes the evolution minimize the fitness instead of maximizing it, the same as
+    /// `with_objective(Objective::Minimize)`. The best individual is then the one with the lowest
+    /// fitness, for the elitism, the selections, the metrics and the stop condition.
+    pub fn minimize(self) -> Self {
+        self.with_objective(Objective::Minimize)
+    }
+
+    /// Sets what the operators do with the genes they push out of their bounds. Defaults to
+    /// [`BoundaryHandler::Clamp`].
+    pub fn with_boundary_handler(mut self, boundary_handler: BoundaryHandler) -> Self {
+        self.boundary_handler = boundary_handler;
+        self
+    }
+
+    /// Sets the constraints of the problem and how the infeasible individuals are treated, see
+    /// [`Constraints`] and [`ConstraintHandling`]. Without it, every individual is feasible.
+    pub fn with_constraints<C: Constraints<T>>(
+        mut self,
+        constraints: C,
+        handling: ConstraintHandling,
+    ) -> Self {
+        self.constraints = Some(ConstraintHandler::new(Box::new(constraints), handling));
+        self
+    }
+
+    /// Sets how the offsprings of each generation make it into the population, and how many of
+    /// them there are. Defaults to the [`GenerationalReplacement`].
+    pub fn with_replacement<R: Replacement<T>>(mut self, r: R) -> Self {
+        self.replacement = Box::new(r);
+        self
+    }
+
+    /// Memoizes the fitness by the hash of the chromosome, keeping the last `capacity` ones, so
+    /// the fitness isn't calculated again for the individuals that didn't change or that were
+    /// already seen. Use it when the fitness is expensive and deterministic. A `capacity` of 0
+    /// only skips the individuals that didn't change. The hits and misses are counted in the
+    /// [`Metrics`](crate::metrics::Metrics). Only the 64-bit hash is kept, so two chromosomes with
+    /// the same hash, however unlikely, share a fitness.
+    pub fn with_fitness_cache(mut self, capacity: usize) -> Self {
+        self.fitness_cache = Some(capacity);
+        self
+    }
+
+    /// Measures the [`Diversity`](crate::metrics::Diversity) of the population every generation,
+    /// in [`Metrics::diversities`](crate::metrics::Metrics::diversities). It's off by default, as
+    /// the mean distance compares every pair of individuals.
+    pub fn with_diversity_metrics(mut self) -> Self {
+        self.diversity_metrics = true;
+        self
+    }
+
+    /// Records these percentiles, from 0 to 100, of the fitness of the population every
+    /// generation, in
+    /// [`Metrics::percentile_fitnesses`](crate::metrics::Metrics::percentile_fitnesses), besides
+    /// the worst and the median fitness that are always recorded.
+    pub fn with_percentiles(mut self, percentiles: impl Into<Vec<f64>>) -> Self {
+        self.percentiles = percentiles.into();
+        self
+    }
+
+    /// Makes the evolutions built by this builder stop when the `token` is cancelled, keeping the
+    /// best individual and the metrics of the generations run until then. See
+    /// [`CancellationToken`].
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Registers an observer, whose hooks are called as the evolution runs. Can be called more
+    /// than once to register several observers, which are called in the order they were added.
+    pub fn with_observer<O: EvolutionObserver<T>>(mut self, o: O) -> Self {
+        self.observers.push(Box::new(o));
+        self
+    }
+
+    pub(crate) fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub(crate) fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    pub(crate) fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
     pub fn build(&self) -> Result<Evolution<T>, String> {
+        self.build_with_seed(self.seed)
+    }
+
+    /// Checks the parameters of the selection, the crossover and the mutation, when they're set.
+    pub(crate) fn check_operators(&self) -> Result<(), String> {
+        if let Some(selection) = &self.selection {
+            selection.check()?;
+        }
+        if let Some(crossover) = &self.crossover {
+            crossover.check()?;
+        }
+        if let Some(mutation) = &self.mutation {
+            mutation.check()?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn build_with_seed(&self, seed: Option<u64>) -> Result<Evolution<T>, String> {
         let title = self.title.clone().unwrap_or("".to_string());
 
         let evolution_config = self.evolution_config.clone().ok_or("No config provided")?;
@@ -146,11 +359,20 @@ impl<T: Individual> EvolutionBuilder<T> {
             return Err("Number of elitist bigger then the population size".to_string());
         }
 
-        if let (Some(f), Some(s), Some(x), Some(m)) = (
+        T::check_range(evolution_config.dimension, &evolution_config.range)?;
+        self.replacement.check(evolution_config.population_size as usize)?;
+        self.check_operators()?;
+
+        if self.percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) {
+            return Err("The percentiles should be between 0 and 100".to_string());
+        }
+
+        if let (Some(f), Some(s), Some(x), Some(m), Some(stop_condition)) = (
             self.fitness.as_ref().map(|f| f.as_ref()),
             self.selection.as_ref().map(|s| s.as_ref()),
             self.crossover.as_ref().map(|c| c.as_ref()),
             self.mutation.as_ref().map(|m| m.as_ref()),
+            self.stop_condition.as_ref(),
         ) {
             Ok(Evolution::new(
                 title.clone(),
@@ -160,10 +382,43 @@ impl<T: Individual> EvolutionBuilder<T> {
                 dyn_clone::clone_box(&*x),
                 dyn_clone::clone_box(&*m),
                 self.elitism.unwrap_or(1),
-                Arc::clone(self.stop_condition.as_ref().unwrap()),
-            ))
+                stop_condition.clone(),
+                seed.map_or_else(EvolutionRng::from_entropy, EvolutionRng::seed_from_u64),
+            )
+            .with_objective(self.objective)
+            .with_percentiles(self.percentiles.clone())
+            .with_replacement(dyn_clone::clone_box(self.replacement.as_ref()))
+            .with_boundary_handler(self.boundary_handler)
+            .with_constraints(self.constraints.clone())
+            .with_fitness_cache(self.fitness_cache)
+            .with_cancellation(self.cancellation.clone())
+            .with_diversity_metrics(self.diversity_metrics)
+            .with_checkpoint(self.checkpoint.clone())
+            .with_observers(self.cloned_observers()))
         } else {
             Err("Missing required parameters".to_string())
         }
     }
+
+    fn cloned_observers(&self) -> Vec<Box<dyn EvolutionObserver<T>>> {
+        self.observers
+            .iter()
+            .map(|o| dyn_clone::clone_box(o.as_ref()))
+            .collect()
+    }
+}
+
+impl<T: Individual + Serialize> EvolutionBuilder<T> {
+    /// Makes the evolution save a checkpoint to `path` every `generations` generations while it
+    /// runs, overwriting the previous one. See [`Evolution::checkpoint`] and
+    /// [`Evolution::resume`]. The checkpoints that can't be written are reported to the observers,
+    /// see [`EvolutionObserver::on_checkpoint_error`].
+    pub fn with_checkpoint_every(mut self, generations: u32, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(CheckpointConfig {
+            every: generations,
+            path: path.into(),
+            write: |evolution, path| evolution.checkpoint(path),
+        });
+        self
+    }
 }
@@ -1,10 +1,22 @@
-use std::fs::create_dir_all;
+use std::error::Error;
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::mem;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
-use crate::plotting::plot_chart;
-use crate::{evolution_builder::EvolutionBuilder, population::Individual};
+use crate::export::{csv_cell, write_csv};
+use crate::metrics::{percentile, Metrics, MetricsExport, Steps};
+use crate::plotting::{plot_lines, ChartOptions, ChartScale, ChartSeries};
+use crate::{
+    cancellation::CancellationToken, evolution_builder::EvolutionBuilder, population::Individual,
+    rng::EvolutionRng,
+};
 
+use rand::{RngCore, SeedableRng};
 use rayon::prelude::*;
+use serde::Serialize;
+use serde_derive::Serialize;
 
 struct ExperimentMetrics {
     total_time: u128,
@@ -15,15 +27,19 @@ pub struct ExperimentRunner<T: Individual> {
     name: String,
     evolution_builder: EvolutionBuilder<T>,
     experiment_metrics: ExperimentMetrics,
+    chart_options: Option<ChartOptions>,
     pub experiment_results: Vec<ExperimentResult<T>>,
 }
 
 pub struct ExperimentResult<T> {
     total_time: u128,
-    average_fitnesses: Vec<f64>,
-    best_fitnesses: Vec<f64>,
     pub iterations: u32,
     pub best_found: T,
+    /// The seed of the run, when the builder has one.
+    pub seed: Option<u64>,
+    /// The metrics of the run, with every series recorded each generation and the time spent in
+    /// each step.
+    pub metrics: Metrics,
 }
 
 impl<T: Individual> ExperimentRunner<T> {
@@ -33,82 +49,137 @@ impl<T: Individual> ExperimentRunner<T> {
             runs,
             evolution_builder,
             experiment_metrics: ExperimentMetrics { total_time: 0 },
+            chart_options: None,
             experiment_results: Vec::new(),
         }
     }
 
-    pub fn run(&mut self) {
+    /// Sets the chart plotted by [`ExperimentRunner::run`], with each series averaged over the
+    /// runs. Defaults to the normalized best and average fitness, in
+    /// `results/<name>/<name>.png`.
+    pub fn with_chart_options(mut self, options: ChartOptions) -> Self {
+        self.chart_options = Some(options);
+        self
+    }
+
+    /// Runs the experiment, in parallel, and plots the series of each generation averaged over the
+    /// runs, see [`ExperimentRunner::with_chart_options`]. When the builder has a
+    /// [`CancellationToken`] and it's cancelled, the runs stop and the ones that didn't start yet
+    /// are skipped, so only the results gathered so far are kept. Fails when an evolution can't
+    /// be built or fails to run, or the chart can't be plotted.
+    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
         let path = format!("results/{}", self.name);
-        let _ = create_dir_all(path.clone());
+        create_dir_all(&path)?;
+
+        let results = Self::run_evolutions(&self.evolution_builder, self.runs)?;
+
+        if results.is_empty() {
+            return Ok(());
+        }
+
+        self.experiment_results.extend(results);
+
+        for result in &self.experiment_results {
+            self.experiment_metrics.total_time += result.total_time;
+        }
+
+        let options = self.chart_options.clone().unwrap_or_else(|| {
+            ChartOptions::new(format!("{}/{}.png", &path, self.name), &self.name)
+                .with_scale(ChartScale::Normalized)
+        });
 
-        let results: Vec<ExperimentResult<T>> = (0..self.runs)
+        let series: Vec<(String, Vec<f64>)> = options
+            .series
+            .iter()
+            .flat_map(|&series| self.average_series(series))
+            .collect();
+
+        plot_lines(&series, &options)
+    }
+
+    /// Runs `runs` evolutions built by the `builder`, in parallel, skipping the ones that didn't
+    /// start when its cancellation token is cancelled. Fails with the first evolution that can't
+    /// be built or fails to run.
+    pub(crate) fn run_evolutions(
+        builder: &EvolutionBuilder<T>,
+        runs: u32,
+    ) -> Result<Vec<ExperimentResult<T>>, String> {
+        // Each run gets its own seed derived from the builder's one, so the whole experiment is
+        // reproducible without every run being identical.
+        let mut seeds = builder.seed().map(EvolutionRng::seed_from_u64);
+        let run_seeds: Vec<Option<u64>> = (0..runs)
+            .map(|_| seeds.as_mut().map(|rng| rng.next_u64()))
+            .collect();
+
+        run_seeds
             .into_par_iter()
-            .map(|_| {
-                let mut evolution = self.evolution_builder.build().unwrap();
+            .filter_map(|seed| {
+                if builder.cancellation().is_some_and(CancellationToken::is_cancelled) {
+                    return None;
+                }
+
+                let mut evolution = match builder.build_with_seed(seed) {
+                    Ok(evolution) => evolution,
+                    Err(error) => return Some(Err(error)),
+                };
 
                 let start_time = Instant::now();
 
-                evolution.run();
+                if let Err(error) = evolution.run() {
+                    return Some(Err(error.to_string()));
+                }
 
                 let total_time = start_time.elapsed().as_nanos();
 
-                // let test_path = &format!("{}/{} run_{}.png", &path, self.name, run);
-                // evolution.plot_chart(&test_path, &self.name).unwrap();
-
-                let average_fitnesses = evolution.metrics.avg_fitnesses.clone();
-                let best_fitnesses = evolution.metrics.best_fitnesses.clone();
                 let best_found = evolution.current_best().clone();
+                let iterations = evolution.metrics.iterations;
+                let metrics = mem::replace(&mut evolution.metrics, Metrics::new());
 
-                ExperimentResult {
-                    average_fitnesses,
-                    best_fitnesses,
+                Some(Ok(ExperimentResult {
                     total_time,
-                    iterations: evolution.metrics.iterations,
+                    iterations,
                     best_found,
-                }
+                    seed,
+                    metrics,
+                }))
             })
-            .collect();
-
-        let iterations = results.iter().map(|r| r.iterations).max().unwrap();
+            .collect()
+    }
 
-        self.experiment_results.extend(results);
+    /// The `series` of each generation averaged over the runs. The runs that stopped earlier
+    /// count with their last value.
+    fn average_series(&self, series: ChartSeries) -> Vec<(String, Vec<f64>)> {
+        let runs_series: Vec<Vec<(String, Vec<f64>)>> = self
+            .experiment_results
+            .iter()
+            .map(|result| result.metrics.chart_series(series))
+            .collect();
 
-        let mut average_fitness_sum_per_generation = vec![0f64; iterations as usize];
-        let mut best_fitness_sum_per_generation = vec![0f64; iterations as usize];
+        let Some(first) = runs_series.first() else {
+            return Vec::new();
+        };
 
-        for result in &self.experiment_results {
-            for i in 0..(result.iterations) as usize {
-                average_fitness_sum_per_generation[i] += result.average_fitnesses[i];
-                best_fitness_sum_per_generation[i] += result.best_fitnesses[i];
-            }
-
-            for i in result.iterations as usize..iterations as usize {
-                average_fitness_sum_per_generation[i] +=
-                    result.average_fitnesses[(result.iterations - 1) as usize];
-                best_fitness_sum_per_generation[i] +=
-                    result.best_fitnesses[(result.iterations - 1) as usize];
-            }
+        (0..first.len())
+            .map(|line| {
+                let runs: Vec<&Vec<f64>> = runs_series
+                    .iter()
+                    .filter_map(|run| run.get(line).map(|(_, values)| values))
+                    .filter(|values| !values.is_empty())
+                    .collect();
+                let generations = runs.iter().map(|values| values.len()).max().unwrap_or(0);
 
-            self.experiment_metrics.total_time += result.total_time;
-        }
+                let average = (0..generations)
+                    .map(|i| {
+                        runs.iter()
+                            .map(|values| values[i.min(values.len() - 1)])
+                            .sum::<f64>()
+                            / runs.len() as f64
+                    })
+                    .collect();
 
-        let average_fitness_per_generation = average_fitness_sum_per_generation
-            .iter()
-            .map(|a| a / self.runs as f64)
-            .collect::<Vec<f64>>();
-        let best_fitness_per_generation = best_fitness_sum_per_generation
-            .iter()
-            .map(|a| a / self.runs as f64)
-            .collect::<Vec<f64>>();
-
-        let path = format!("{}/{}.png", &path, self.name);
-        plot_chart(
-            &best_fitness_per_generation,
-            &average_fitness_per_generation,
-            &path,
-            &self.name,
-        )
-        .unwrap();
+                (first[line].0.clone(), average)
+            })
+            .collect()
     }
 
     pub fn experiment_digest(&self) {
@@ -129,14 +200,232 @@ impl<T: Individual> ExperimentRunner<T> {
             .iter()
             .map(|result| result.iterations)
             .max()
-            .unwrap();
+            .unwrap_or(0);
 
         println!(
             "Average time for {}: {:?}",
             self.name,
-            Duration::from_nanos(self.experiment_metrics.total_time as u64 / self.runs as u64)
+            Duration::from_nanos(
+                self.experiment_metrics.total_time as u64
+                    / self.experiment_results.len().max(1) as u64
+            )
         );
         println!("Average max iterations: {}", avg_max_iterations);
         println!("Max max iterations: {}", max_max_iterations);
+
+        if self.experiment_results.is_empty() {
+            return;
+        }
+
+        let runs = self.experiment_results.len() as f64;
+        let objective = self.evolution_builder.objective();
+
+        let mut best_found: Vec<f64> = self
+            .experiment_results
+            .iter()
+            .map(|result| result.best_found.get_fitness())
+            .collect();
+        best_found.sort_by(f64::total_cmp);
+
+        let mean = best_found.iter().sum::<f64>() / runs;
+        let std_dev =
+            (best_found.iter().map(|fitness| (fitness - mean).powi(2)).sum::<f64>() / runs).sqrt();
+
+        println!(
+            "Best found: best {}, median {}, worst {}, mean {} ± {}",
+            objective.best(best_found.iter().copied()).unwrap_or(f64::NAN),
+            percentile(&best_found, 50.0),
+            objective.worst(best_found.iter().copied()).unwrap_or(f64::NAN),
+            mean,
+            std_dev
+        );
+        println!(
+            "Average generation of the best found: {}",
+            self.experiment_results
+                .iter()
+                .map(|result| result.metrics.best_generation as f64)
+                .sum::<f64>()
+                / runs
+        );
+        println!(
+            "Average evaluations until the best found: {}",
+            self.experiment_results
+                .iter()
+                .map(|result| result.metrics.best_evaluations as f64)
+                .sum::<f64>()
+                / runs
+        );
+    }
+}
+
+/// A run as it's exported to JSON lines, see [`ExperimentRunner::write_json_lines`].
+#[derive(Serialize)]
+struct RunExport<'a, G> {
+    run: usize,
+    seed: Option<u64>,
+    total_time_ns: u128,
+    best_fitness: f64,
+    best_chromosome: &'a [G],
+    metrics: MetricsExport<'a>,
+}
+
+impl<T: Individual> ExperimentRunner<T>
+where
+    T::Gene: Serialize,
+{
+    /// Writes the results of every run to the `dir` directory as CSV, creating it if needed:
+    ///
+    /// - `runs.csv`: one row per run, with its seed, iterations, total time, best fitness and
+    ///   when it was found, the time spent in each step, in nanoseconds, and the best chromosome
+    ///   as a JSON array.
+    /// - `generations.csv`: the metrics of each generation of each run, as in
+    ///   [`Metrics::write_csv`], with the run in the first column.
+    pub fn write_csv(&self, dir: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let dir = dir.as_ref();
+        create_dir_all(dir)?;
+
+        let mut header: Vec<String> = [
+            "run",
+            "seed",
+            "iterations",
+            "total_time_ns",
+            "best_fitness",
+            "best_generation",
+            "best_evaluations",
+            "evaluations",
+        ]
+        .map(String::from)
+        .to_vec();
+        header.extend(Steps::ALL.map(|step| format!("{:?}_ns", step).to_lowercase()));
+        header.push("best_chromosome".to_string());
+
+        let mut rows = Vec::new();
+        for (run, result) in self.experiment_results.iter().enumerate() {
+            let metrics = &result.metrics;
+
+            let mut row = vec![
+                run.to_string(),
+                csv_cell(result.seed),
+                result.iterations.to_string(),
+                result.total_time.to_string(),
+                result.best_found.get_fitness().to_string(),
+                metrics.best_generation.to_string(),
+                metrics.best_evaluations.to_string(),
+                metrics.evaluations.to_string(),
+            ];
+            row.extend(
+                Steps::ALL.map(|step| csv_cell(metrics.step_time(step).map(|t| t.as_nanos()))),
+            );
+            row.push(serde_json::to_string(result.best_found.get_chromosome())?);
+
+            rows.push(row);
+        }
+
+        write_csv(&dir.join("runs.csv"), &header, rows)?;
+
+        let mut header = vec!["run".to_string()];
+        if let Some(result) = self.experiment_results.first() {
+            header.extend(result.metrics.csv_header());
+        }
+
+        let rows = self.experiment_results.iter().enumerate().flat_map(|(run, result)| {
+            result.metrics.csv_rows().into_iter().map(move |row| {
+                std::iter::once(run.to_string()).chain(row).collect::<Vec<String>>()
+            })
+        });
+
+        write_csv(&dir.join("generations.csv"), &header, rows)
+    }
+
+    /// Writes the results of every run to `path` as JSON lines, one JSON object per run with its
+    /// seed, total time, best fitness and chromosome and all its metrics, as in
+    /// [`Metrics::write_json`].
+    pub fn write_json_lines(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        for (run, result) in self.experiment_results.iter().enumerate() {
+            let export = RunExport {
+                run,
+                seed: result.seed,
+                total_time_ns: result.total_time,
+                best_fitness: result.best_found.get_fitness(),
+                best_chromosome: result.best_found.get_chromosome(),
+                metrics: result.metrics.export(),
+            };
+
+            serde_json::to_writer(&mut writer, &export)?;
+            writeln!(writer)?;
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::ones_builder;
+
+    #[test]
+    fn exports_every_run() {
+        let builder = ones_builder(10, 8)
+            .with_percentiles([25.0])
+            .with_stop_condition(|_, iterations, _| iterations >= 5);
+
+        let mut runner = ExperimentRunner::new("export".to_string(), 2, builder.clone());
+        for seed in [1, 2] {
+            let mut evolution = builder.build_with_seed(Some(seed)).unwrap();
+            evolution.run().unwrap();
+
+            runner.experiment_results.push(ExperimentResult {
+                total_time: 0,
+                iterations: evolution.metrics.iterations,
+                best_found: evolution.current_best().clone(),
+                seed: Some(seed),
+                metrics: mem::replace(&mut evolution.metrics, Metrics::new()),
+            });
+        }
+
+        let dir = std::env::temp_dir().join("evolutionary_export_test");
+        runner.write_csv(&dir).unwrap();
+        runner.write_json_lines(dir.join("runs.jsonl")).unwrap();
+
+        let runs = std::fs::read_to_string(dir.join("runs.csv")).unwrap();
+        let generations = std::fs::read_to_string(dir.join("generations.csv")).unwrap();
+        let json_lines = std::fs::read_to_string(dir.join("runs.jsonl")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(runs.lines().count(), 3);
+        assert!(runs.lines().nth(1).unwrap().starts_with("0,1,5,"));
+        assert!(runs.lines().nth(1).unwrap().ends_with("]\""));
+
+        assert_eq!(
+            generations.lines().next().unwrap(),
+            "run,generation,best,average,worst,median,std_dev,p25"
+        );
+        assert_eq!(generations.lines().count(), 1 + 2 * 5);
+
+        let runs: Vec<serde_json::Value> =
+            json_lines.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1]["seed"], 2);
+        assert_eq!(runs[1]["best_chromosome"].as_array().unwrap().len(), 8);
+        assert_eq!(runs[1]["metrics"]["best_fitnesses"].as_array().unwrap().len(), 5);
+        assert!(runs[1]["metrics"]["step_times_ns"]["Fitness"].is_u64());
+    }
+
+    #[test]
+    fn fails_when_an_evolution_fails() {
+        let builder = ones_builder(10, 8)
+            .with_selection(RouletteSelection { scaling: FitnessScaling::Linear { c: 0.5 } })
+            .with_stop_condition(|_, iterations, _| iterations >= 5);
+
+        let unbuildable = builder.clone().with_mutation(BitFlipMutation { mutation_rate: 2.0 });
+
+        assert!(ExperimentRunner::run_evolutions(&builder, 2).is_err());
+        assert!(ExperimentRunner::run_evolutions(&unbuildable, 2).is_err());
     }
 }
@@ -0,0 +1,276 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::create_dir_all;
+
+use plotters::data::Quartiles;
+
+use crate::experiment_runner::{ExperimentResult, ExperimentRunner};
+use crate::plotting::{plot_boxplot_with_options, ChartOptions};
+use crate::statistics::{mann_whitney_u, mean, median, std_dev, RankSumTest};
+use crate::{evolution_builder::EvolutionBuilder, population::Individual};
+
+/// # Experiment Suite
+///
+/// Runs several named configurations of an evolution on the same problem, each `runs` times, and
+/// compares the best fitness they find, see [`SuiteReport`]. When a target fitness is set, it
+/// also reports how often and how fast each configuration reaches it.
+///
+/// # Example
+///
+/// ```no_run
+/// # use evolutionary::prelude::*;
+/// # #[derive(Clone)]
+/// # struct YourFitness;
+/// # impl Fitness<Bin> for YourFitness {
+/// #    fn calculate_fitness(&self, individual: &Bin) -> f64 { 0.0 }
+/// # }
+/// let builder = EvolutionBuilder::new(30, 10, GeneCod::Bin, ())
+///     .with_fitness(YourFitness)
+///     .with_crossover(NPointsCrossover::default())
+///     .with_mutation(BitFlipMutation::default())
+///     .with_stop_condition(move |_, iterations, _| iterations >= 1000);
+///
+/// let mut suite = ExperimentSuite::new("selections", 30)
+///     .with_configuration(
+///         "tournament",
+///         builder.clone().with_selection(TournamentSelection::default()),
+///     )
+///     .with_configuration("roulette", builder.with_selection(RouletteSelection::default()))
+///     .with_target(10.0);
+///
+/// suite.run().unwrap();
+/// println!("{}", suite.report());
+/// ```
+pub struct ExperimentSuite<T: Individual> {
+    name: String,
+    runs: u32,
+    target: Option<f64>,
+    configurations: Vec<(String, EvolutionBuilder<T>)>,
+    /// The results of the runs of each configuration, in the order they were added.
+    pub results: Vec<(String, Vec<ExperimentResult<T>>)>,
+}
+
+/// Summary of the best fitness found by the runs of one configuration of an [`ExperimentSuite`].
+/// The success rate and the evaluations to the target are `None` without a target, and the mean
+/// evaluations also when no run reached it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigurationSummary {
+    pub name: String,
+    pub runs: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    /// The fraction of the runs that reached the target.
+    pub success_rate: Option<f64>,
+    /// The mean number of fitness evaluations until the target, of the runs that reached it.
+    pub mean_evaluations_to_target: Option<f64>,
+}
+
+/// Mann-Whitney U test between the best fitness found by two configurations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub first: String,
+    pub second: String,
+    pub test: RankSumTest,
+}
+
+/// The summary of each configuration of an [`ExperimentSuite`] and the comparison of every pair
+/// of them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SuiteReport {
+    pub configurations: Vec<ConfigurationSummary>,
+    pub comparisons: Vec<Comparison>,
+}
+
+impl<T: Individual> ExperimentSuite<T> {
+    pub fn new(name: impl Into<String>, runs: u32) -> Self {
+        Self {
+            name: name.into(),
+            runs,
+            target: None,
+            configurations: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    pub fn with_configuration(
+        mut self,
+        name: impl Into<String>,
+        builder: EvolutionBuilder<T>,
+    ) -> Self {
+        self.configurations.push((name.into(), builder));
+        self
+    }
+
+    /// Sets the fitness a run should reach to succeed, according to the objective of its
+    /// configuration. A run succeeds when the best fitness it found so far reaches it.
+    pub fn with_target(mut self, target: f64) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Runs every configuration, one after the other and its runs in parallel, and plots a boxplot
+    /// of the best fitness found by each to `results/<name>/boxplot.png`. Fails when an evolution
+    /// can't be built or fails to run, or the boxplot can't be plotted.
+    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
+        self.run_configurations()?;
+
+        let path = format!("results/{}", self.name);
+        create_dir_all(&path)?;
+
+        let options = ChartOptions::new(format!("{}/boxplot.png", path), &self.name);
+        self.plot_boxplot(&options)
+    }
+
+    pub(crate) fn run_configurations(&mut self) -> Result<(), String> {
+        self.results = self
+            .configurations
+            .iter()
+            .map(|(name, builder)| {
+                Ok((name.clone(), ExperimentRunner::run_evolutions(builder, self.runs)?))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(())
+    }
+
+    /// Plots a boxplot of the best fitness found by the runs of each configuration.
+    pub fn plot_boxplot(&self, options: &ChartOptions) -> Result<(), Box<dyn Error>> {
+        let (labels, quartiles): (Vec<String>, Vec<Quartiles>) = self
+            .results
+            .iter()
+            .filter(|(_, results)| !results.is_empty())
+            .map(|(name, results)| (name.clone(), Quartiles::new(&best_fitnesses(results))))
+            .unzip();
+
+        plot_boxplot_with_options(&quartiles, &labels, options)
+    }
+
+    pub fn report(&self) -> SuiteReport {
+        let configurations = self.summaries();
+
+        let mut comparisons = Vec::new();
+        for (i, (first, first_results)) in self.results.iter().enumerate() {
+            for (second, second_results) in &self.results[i + 1..] {
+                comparisons.push(Comparison {
+                    first: first.clone(),
+                    second: second.clone(),
+                    test: mann_whitney_u(
+                        &best_fitnesses(first_results),
+                        &best_fitnesses(second_results),
+                    ),
+                });
+            }
+        }
+
+        SuiteReport { configurations, comparisons }
+    }
+
+    pub(crate) fn summaries(&self) -> Vec<ConfigurationSummary> {
+        self.results
+            .iter()
+            .map(|(name, results)| self.summarize(name, results))
+            .collect()
+    }
+
+    fn summarize(&self, name: &str, results: &[ExperimentResult<T>]) -> ConfigurationSummary {
+        let best_fitnesses = best_fitnesses(results);
+
+        let reached: Option<Vec<u64>> = self.target.map(|target| {
+            results
+                .iter()
+                .filter_map(|result| result.metrics.evaluations_to(target))
+                .collect()
+        });
+
+        ConfigurationSummary {
+            name: name.to_string(),
+            runs: results.len(),
+            mean: mean(&best_fitnesses),
+            median: median(&best_fitnesses),
+            std_dev: std_dev(&best_fitnesses),
+            success_rate: reached
+                .as_ref()
+                .map(|reached| reached.len() as f64 / results.len().max(1) as f64),
+            mean_evaluations_to_target: reached
+                .filter(|reached| !reached.is_empty())
+                .map(|reached| reached.iter().sum::<u64>() as f64 / reached.len() as f64),
+        }
+    }
+}
+
+fn best_fitnesses<T: Individual>(results: &[ExperimentResult<T>]) -> Vec<f64> {
+    results.iter().map(|result| result.best_found.get_fitness()).collect()
+}
+
+impl Display for SuiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for summary in &self.configurations {
+            write!(
+                f,
+                "{}: {} runs, mean {:.4} ± {:.4}, median {:.4}",
+                summary.name, summary.runs, summary.mean, summary.std_dev, summary.median
+            )?;
+
+            if let Some(success_rate) = summary.success_rate {
+                write!(f, ", success rate {:.1}%", success_rate * 100.0)?;
+            }
+            if let Some(evaluations) = summary.mean_evaluations_to_target {
+                write!(f, ", mean evaluations to target {:.0}", evaluations)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        for comparison in &self.comparisons {
+            writeln!(
+                f,
+                "{} vs {}: U = {}, p = {:.4}",
+                comparison.first, comparison.second, comparison.test.u, comparison.test.p_value
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::ones_builder;
+
+    #[test]
+    fn compares_the_configurations() {
+        let builder = ones_builder(20, 20)
+            .with_stop_condition(|best, iterations, _| best == 20.0 || iterations >= 100)
+            .with_seed(7);
+
+        let mut suite = ExperimentSuite::new("suite_test", 12)
+            .with_configuration(
+                "mutation",
+                builder.clone().with_mutation(BitFlipMutation::default()),
+            )
+            .with_configuration("no mutation", builder.with_mutation(DoNothingMutation))
+            .with_target(20.0);
+
+        suite.run_configurations().unwrap();
+
+        let report = suite.report();
+        let (mutation, no_mutation) = (&report.configurations[0], &report.configurations[1]);
+
+        assert_eq!(mutation.runs, 12);
+        assert!(mutation.mean > no_mutation.mean);
+        assert!(mutation.success_rate.unwrap() > no_mutation.success_rate.unwrap());
+        assert!(mutation.mean_evaluations_to_target.is_some());
+
+        assert_eq!(report.comparisons.len(), 1);
+        assert!(report.comparisons[0].test.p_value < 0.05);
+        assert!(report.to_string().contains("mutation vs no mutation: U = "));
+
+        let path = std::env::temp_dir().join("evolutionary_suite_test.svg");
+        suite.plot_boxplot(&ChartOptions::new(&path, "Suite")).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("no mutation"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Quotes the CSV `field` when it has a comma, a quote or a line break.
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Formats an optional value as a CSV cell, empty when there's no value.
+pub(crate) fn csv_cell<V: ToString>(value: Option<V>) -> String {
+    value.map_or_else(String::new, |value| value.to_string())
+}
+
+/// Writes the `header` and the `rows` as a CSV file to `path`.
+pub(crate) fn write_csv(
+    path: &Path,
+    header: &[String],
+    rows: impl IntoIterator<Item = Vec<String>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    for row in std::iter::once(header.to_vec()).chain(rows) {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        writeln!(writer, "{}", fields.join(","))?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_the_fields_that_need_it() {
+        assert_eq!(csv_field("1.5"), "1.5");
+        assert_eq!(csv_field("[1,2]"), "\"[1,2]\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_cell::<f64>(None), "");
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::population::Individual;
+
+/// The most entries kept in a shard before the cache is split into another one.
+const SHARD_CAPACITY: usize = 256;
+
+/// The most shards a cache is split into.
+const MAX_SHARDS: usize = 16;
+
+/// The fitness of the most recently used chromosomes, by their hash. When it's full, the least
+/// recently used one is dropped.
+struct Entries {
+    capacity: usize,
+    fitness: HashMap<u64, (f64, u64)>,
+    recency: BTreeMap<u64, u64>,
+}
+
+impl Entries {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, fitness: HashMap::new(), recency: BTreeMap::new() }
+    }
+
+    fn get(&mut self, key: u64, tick: u64) -> Option<f64> {
+        let (fitness, last_used) = self.fitness.get_mut(&key)?;
+
+        self.recency.remove(last_used);
+        self.recency.insert(tick, key);
+        *last_used = tick;
+
+        Some(*fitness)
+    }
+
+    fn insert(&mut self, key: u64, fitness: f64, tick: u64) {
+        if let Some((_, last_used)) = self.fitness.remove(&key) {
+            self.recency.remove(&last_used);
+        }
+
+        while self.fitness.len() >= self.capacity {
+            match self.recency.pop_first() {
+                Some((_, oldest)) => self.fitness.remove(&oldest),
+                None => return,
+            };
+        }
+
+        self.fitness.insert(key, (fitness, tick));
+        self.recency.insert(tick, key);
+    }
+}
+
+/// Memoizes the fitness of the individuals by the hash of their chromosome, see
+/// [`EvolutionBuilder::with_fitness_cache`](crate::EvolutionBuilder::with_fitness_cache). It's
+/// shared by the threads that calculate the fitness, and counts its hits and misses.
+///
+/// The cache is probabilistic: it only keeps the 64-bit hash of the chromosomes, so two
+/// chromosomes with the same hash share a fitness. With `n` different chromosomes the odds of a
+/// collision are about `n² / 2⁶⁵`, negligible for the sizes it's meant for.
+///
+/// Large caches are split into shards by hash, each with its own lock and its share of the
+/// capacity, so the threads rarely wait for each other. The least recently used fitness is then
+/// dropped from the shard that's full, not from the whole cache.
+pub(crate) struct FitnessCache {
+    enabled: bool,
+    shards: Vec<Mutex<Entries>>,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl FitnessCache {
+    pub fn new(capacity: usize) -> Self {
+        let shards = (capacity / SHARD_CAPACITY).clamp(1, MAX_SHARDS);
+
+        Self {
+            enabled: capacity > 0,
+            shards: (0..shards)
+                .map(|_| Mutex::new(Entries::new(capacity.div_ceil(shards))))
+                .collect(),
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, key: u64) -> &Mutex<Entries> {
+        &self.shards[(key % self.shards.len() as u64) as usize]
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Returns the cached fitness of the individual or, on a miss, calculates and caches it.
+    pub fn get_or_calculate<T: Individual>(
+        &self,
+        individual: &T,
+        calculate: impl FnOnce(&T) -> f64,
+    ) -> f64 {
+        let key = individual.chromosome_hash().filter(|_| self.enabled);
+        let cached = key.and_then(|key| self.shard(key).lock().unwrap().get(key, self.tick()));
+
+        if let Some(fitness) = cached {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return fitness;
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let fitness = calculate(individual);
+
+        if let Some(key) = key {
+            self.shard(key).lock().unwrap().insert(key, fitness, self.tick());
+        }
+
+        fitness
+    }
+
+    /// Counts an evaluation avoided because the individual didn't change, as a hit.
+    pub fn skip(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the cached fitness by chromosome hash, from the least to the most recently used.
+    pub fn entries(&self) -> Vec<(u64, f64)> {
+        let mut entries: Vec<(u64, u64, f64)> = self
+            .shards
+            .iter()
+            .flat_map(|shard| {
+                let shard = shard.lock().unwrap();
+                shard
+                    .recency
+                    .iter()
+                    .map(|(tick, key)| (*tick, *key, shard.fitness[key].0))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        entries.sort_unstable_by_key(|(tick, _, _)| *tick);
+        entries.into_iter().map(|(_, key, fitness)| (key, fitness)).collect()
+    }
+
+    /// Caches the fitness returned by [`FitnessCache::entries`], in the same order of use.
+    pub fn restore(&self, cached: Vec<(u64, f64)>) {
+        if !self.enabled {
+            return;
+        }
+
+        for (key, fitness) in cached {
+            self.shard(key).lock().unwrap().insert(key, fitness, self.tick());
+        }
+    }
+
+    /// Returns the hits and misses counted since the last call.
+    pub fn take_counts(&self) -> (u64, u64) {
+        (self.hits.swap(0, Ordering::Relaxed), self.misses.swap(0, Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn drops_the_least_recently_used_fitness() {
+        let cache = FitnessCache::new(2);
+        let individuals: Vec<Bin> = [[true, true], [true, false], [false, false]]
+            .into_iter()
+            .map(|genes| Bin::new(genes.to_vec()))
+            .collect();
+
+        let count_ones = |individual: &Bin| {
+            individual.get_chromosome().iter().filter(|&&gene| gene).count() as f64
+        };
+
+        assert_eq!(cache.get_or_calculate(&individuals[0], count_ones), 2.0);
+        assert_eq!(cache.get_or_calculate(&individuals[1], count_ones), 1.0);
+        assert_eq!(cache.get_or_calculate(&individuals[0], |_| unreachable!()), 2.0);
+        assert_eq!(cache.get_or_calculate(&individuals[2], count_ones), 0.0);
+
+        // The second individual was the least recently used, so it was dropped.
+        assert_eq!(cache.get_or_calculate(&individuals[0], |_| unreachable!()), 2.0);
+        assert_eq!(cache.get_or_calculate(&individuals[1], |_| -1.0), -1.0);
+        assert_eq!(cache.take_counts(), (2, 4));
+        assert_eq!(cache.take_counts(), (0, 0));
+    }
+
+    #[test]
+    fn shards_large_caches_and_keeps_the_order_of_use() {
+        let cache = FitnessCache::new(4 * SHARD_CAPACITY);
+        assert_eq!(cache.shards.len(), 4);
+
+        let individuals: Vec<Bin> =
+            (0..100).map(|n| Bin::new((0..7).map(|bit| n >> bit & 1 == 1).collect())).collect();
+        let decode = |individual: &Bin| {
+            individual.get_chromosome().iter().rev().fold(0.0, |n, &bit| 2.0 * n + bit as u8 as f64)
+        };
+
+        for individual in individuals.iter().rev() {
+            cache.get_or_calculate(individual, decode);
+        }
+        cache.get_or_calculate(&individuals[50], |_| unreachable!());
+
+        let entries = cache.entries();
+        assert_eq!(entries.len(), 100);
+        assert_eq!(entries[0].1, 99.0);
+        assert_eq!(entries[98].1, 0.0);
+        assert_eq!(entries[99].1, 50.0);
+
+        let restored = FitnessCache::new(4 * SHARD_CAPACITY);
+        restored.restore(entries.clone());
+        assert_eq!(restored.entries(), entries);
+        assert_eq!(cache.take_counts(), (1, 100));
+    }
+}
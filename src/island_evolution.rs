@@ -0,0 +1,518 @@
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::{Rng, RngCore, SeedableRng};
+use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{
+    error::EvolutionError, evolution::Evolution, evolution_builder::EvolutionBuilder,
+    population::Individual, rng::EvolutionRng,
+};
+
+/// Defines to which islands each island sends its migrants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationTopology {
+    /// Each island sends its migrants to the next one, the last sending to the first.
+    Ring,
+    /// Each island sends its migrants to every other island. When more migrants arrive than an
+    /// island has individuals, only the best ones are received.
+    FullyConnected,
+    /// Each island sends its migrants to another island chosen at random on every migration.
+    Random,
+}
+
+/// Defines which individuals of an island are chosen to migrate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrantSelection {
+    /// The best individuals of the island migrate.
+    Best,
+    /// Random individuals of the island migrate.
+    Random,
+}
+
+/// Defines which individuals of an island are replaced by the arriving migrants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrantReplacement {
+    /// The migrants replace the worst individuals of the island.
+    Worst,
+    /// The migrants replace random individuals of the island.
+    Random,
+}
+
+/// # Island Evolution
+///
+/// Runs several [`Evolution`]s, the islands, in parallel. Every `migration_interval` generations
+/// some individuals migrate between the islands following the `topology`. Keeping the
+/// sub-populations mostly isolated preserves diversity, which helps fighting premature
+/// convergence, while the migrations spread the good solutions found by each island.
+///
+/// The islands can have different operators, as each one is built from its own
+/// [`EvolutionBuilder`]. The evolution stops when any of the islands reaches its stop condition.
+///
+/// # Example
+///
+/// ```
+/// # use evolutionary::prelude::*;
+/// # #[derive(Clone)]
+/// # struct YourFitness;
+/// # impl Fitness<Bin> for YourFitness {
+/// #    fn calculate_fitness(&self, individual: &Bin) -> f64 { 0.0 }
+/// # }
+/// let builder = EvolutionBuilder::new(30, 10, GeneCod::Bin, ())
+///     .with_fitness(YourFitness)
+///     .with_selection(TournamentSelection::default())
+///     .with_crossover(NPointsCrossover::default())
+///     .with_mutation(BitFlipMutation::default())
+///     .with_stop_condition(move |_, iterations, _| iterations >= 100);
+///
+/// let mut islands = IslandEvolutionBuilder::new()
+///     .with_islands(&builder, 4)
+///     .with_topology(MigrationTopology::Ring)
+///     .with_migration_interval(10)
+///     .with_migrants(2)
+///     .build()
+///     .unwrap();
+///
+/// islands.run().unwrap();
+///
+/// println!("Best fitness: {}", islands.current_best_fitness());
+/// ```
+pub struct IslandEvolution<T: Individual> {
+    islands: Vec<Evolution<T>>,
+    topology: MigrationTopology,
+    migration_interval: u32,
+    migrants: usize,
+    migrant_selection: MigrantSelection,
+    migrant_replacement: MigrantReplacement,
+    rng: EvolutionRng,
+    epochs: u32,
+}
+
+impl<T: Individual> IslandEvolution<T> {
+    pub fn new(
+        islands: Vec<Evolution<T>>,
+        topology: MigrationTopology,
+        migration_interval: u32,
+        migrants: usize,
+        migrant_selection: MigrantSelection,
+        migrant_replacement: MigrantReplacement,
+        rng: EvolutionRng,
+    ) -> Self {
+        Self {
+            islands,
+            topology,
+            migration_interval,
+            migrants,
+            migrant_selection,
+            migrant_replacement,
+            rng,
+            epochs: 0,
+        }
+    }
+
+    /// Starts every island, generating their initial populations.
+    pub fn start(&mut self) {
+        self.epochs = 0;
+
+        self.islands
+            .par_iter_mut()
+            .for_each(|island| island.start());
+    }
+
+    /// Runs one epoch: every island evolves for `migration_interval` generations, or until it
+    /// reaches its stop condition, and then the migration happens. If a generation of any island
+    /// fails, the migration doesn't happen and the error is returned.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<(), EvolutionError> {
+        let migration_interval = self.migration_interval;
+
+        self.islands.par_iter_mut().try_for_each(|island| {
+            for _ in 0..migration_interval {
+                if island.reached_stop_condition() {
+                    break;
+                }
+
+                island.next()?;
+            }
+
+            Ok::<_, EvolutionError>(())
+        })?;
+
+        self.migrate();
+
+        self.epochs += 1;
+
+        Ok(())
+    }
+
+    /// Runs the islands, epoch over epoch, until one of them meets its stop condition or a
+    /// generation fails.
+    pub fn run(&mut self) -> Result<(), EvolutionError> {
+        self.start();
+
+        let mut result = Ok(());
+
+        while result.is_ok() && !self.reached_stop_condition() {
+            result = self.next();
+        }
+
+        for island in &mut self.islands {
+            island.finish();
+        }
+
+        result
+    }
+
+    /// Returns if any of the islands already met its stop condition.
+    pub fn reached_stop_condition(&self) -> bool {
+        self.islands
+            .iter()
+            .any(|island| island.reached_stop_condition())
+    }
+
+    /// Returns the islands, to inspect their populations and metrics.
+    pub fn islands(&self) -> &Vec<Evolution<T>> {
+        &self.islands
+    }
+
+    /// Number of migrations that happened.
+    pub fn epochs(&self) -> u32 {
+        self.epochs
+    }
+
+    /// Returns the best individual among all the islands.
+    pub fn current_best(&self) -> &T {
+        let objective = self.islands[0].objective();
+
+        self.islands
+            .iter()
+            .map(|island| island.current_best())
+            .max_by(|a, b| objective.compare(a.get_fitness(), b.get_fitness()))
+            .unwrap()
+    }
+
+    pub fn current_best_fitness(&self) -> f64 {
+        self.current_best().get_fitness()
+    }
+
+    fn migrate(&mut self) {
+        let len = self.islands.len();
+
+        if len < 2 || self.migrants == 0 {
+            return;
+        }
+
+        let emigrants: Vec<Vec<T>> = (0..len).map(|i| self.select_emigrants(i)).collect();
+
+        let mut arrivals: Vec<Vec<T>> = vec![Vec::new(); len];
+
+        for (from, emigrants) in emigrants.into_iter().enumerate() {
+            for to in self.destinations(from) {
+                arrivals[to].extend(emigrants.iter().cloned());
+            }
+        }
+
+        for (to, arrivals) in arrivals.into_iter().enumerate() {
+            self.receive_immigrants(to, arrivals);
+        }
+    }
+
+    fn destinations(&mut self, from: usize) -> Vec<usize> {
+        let len = self.islands.len();
+
+        match self.topology {
+            MigrationTopology::Ring => vec![(from + 1) % len],
+            MigrationTopology::FullyConnected => (0..len).filter(|&to| to != from).collect(),
+            MigrationTopology::Random => {
+                let to = self.rng.gen_range(0..len - 1);
+
+                vec![if to >= from { to + 1 } else { to }]
+            }
+        }
+    }
+
+    fn select_emigrants(&mut self, island: usize) -> Vec<T> {
+        let island = &self.islands[island];
+
+        let indexes = match self.migrant_selection {
+            MigrantSelection::Best => island.best_indexes(self.migrants),
+            MigrantSelection::Random => {
+                (0..island.population().len()).choose_multiple(&mut self.rng, self.migrants)
+            }
+        };
+
+        indexes
+            .into_iter()
+            .map(|idx| island.population()[idx].clone())
+            .collect()
+    }
+
+    fn receive_immigrants(&mut self, island: usize, mut immigrants: Vec<T>) {
+        let island = &mut self.islands[island];
+
+        let population_size = island.population().len();
+        if immigrants.len() > population_size {
+            // From the best to the worst.
+            immigrants.sort_by(|a, b| island.cmp_by_fitness(b, a));
+            immigrants.truncate(population_size);
+        }
+
+        let indexes = match self.migrant_replacement {
+            MigrantReplacement::Worst => island.worst_indexes(immigrants.len()),
+            MigrantReplacement::Random => {
+                let mut indexes: Vec<usize> = (0..island.population().len()).collect();
+                indexes.shuffle(&mut self.rng);
+                indexes.truncate(immigrants.len());
+                indexes
+            }
+        };
+
+        let population = island.population_mut();
+
+        for (idx, immigrant) in indexes.into_iter().zip(immigrants) {
+            population[idx] = immigrant;
+        }
+    }
+}
+
+/// Helper struct to create a new [`IslandEvolution`]. At least one island is required.
+pub struct IslandEvolutionBuilder<T: Individual> {
+    islands: Vec<EvolutionBuilder<T>>,
+    topology: MigrationTopology,
+    migration_interval: u32,
+    migrants: usize,
+    migrant_selection: MigrantSelection,
+    migrant_replacement: MigrantReplacement,
+    seed: Option<u64>,
+}
+
+impl<T: Individual> Default for IslandEvolutionBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Individual> IslandEvolutionBuilder<T> {
+    pub fn new() -> Self {
+        Self {
+            islands: Vec::new(),
+            topology: MigrationTopology::Ring,
+            migration_interval: 10,
+            migrants: 1,
+            migrant_selection: MigrantSelection::Best,
+            migrant_replacement: MigrantReplacement::Worst,
+            seed: None,
+        }
+    }
+
+    /// Adds an island that will be built from the `builder`.
+    pub fn with_island(mut self, builder: EvolutionBuilder<T>) -> Self {
+        self.islands.push(builder);
+        self
+    }
+
+    /// Adds `count` islands that will be built from the same `builder`.
+    pub fn with_islands(mut self, builder: &EvolutionBuilder<T>, count: u32) -> Self {
+        for _ in 0..count {
+            self.islands.push(builder.clone());
+        }
+        self
+    }
+
+    /// Sets the migration topology. Default is `MigrationTopology::Ring`.
+    pub fn with_topology(mut self, topology: MigrationTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Sets the number of generations between migrations. Default is 10.
+    pub fn with_migration_interval(mut self, generations: u32) -> Self {
+        self.migration_interval = generations;
+        self
+    }
+
+    /// Sets the number of individuals each island sends to each destination. Default is 1.
+    pub fn with_migrants(mut self, migrants: usize) -> Self {
+        self.migrants = migrants;
+        self
+    }
+
+    /// Sets which individuals migrate. Default is `MigrantSelection::Best`.
+    pub fn with_migrant_selection(mut self, selection: MigrantSelection) -> Self {
+        self.migrant_selection = selection;
+        self
+    }
+
+    /// Sets which individuals are replaced by the migrants. Default is `MigrantReplacement::Worst`.
+    pub fn with_migrant_replacement(mut self, replacement: MigrantReplacement) -> Self {
+        self.migrant_replacement = replacement;
+        self
+    }
+
+    /// Sets the seed of the random number generator. The seed of each island is derived from it,
+    /// the ones set in the islands' builders are ignored. When it's not set, every island is
+    /// seeded from the system entropy.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(&self) -> Result<IslandEvolution<T>, String> {
+        if self.islands.is_empty() {
+            return Err("No islands provided".to_string());
+        }
+
+        if self.migration_interval == 0 {
+            return Err("The migration interval must be at least 1".to_string());
+        }
+
+        let objective = self.islands[0].objective();
+        if self.islands.iter().any(|island| island.objective() != objective) {
+            return Err("All the islands must have the same objective".to_string());
+        }
+
+        let mut rng = self
+            .seed
+            .map_or_else(EvolutionRng::from_entropy, EvolutionRng::seed_from_u64);
+
+        let islands = self
+            .islands
+            .iter()
+            .map(|builder| builder.build_with_seed(self.seed.map(|_| rng.next_u64())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(IslandEvolution::new(
+            islands,
+            self.topology,
+            self.migration_interval,
+            self.migrants,
+            self.migrant_selection,
+            self.migrant_replacement,
+            rng,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::prelude::*;
+    use crate::testing::ones_builder;
+
+    fn builder() -> EvolutionBuilder<Bin> {
+        ones_builder(10, 20).with_stop_condition(|_, iterations, _| iterations >= 30)
+    }
+
+    #[test]
+    fn runs_all_the_islands() {
+        let mut islands = IslandEvolutionBuilder::new()
+            .with_islands(&builder(), 3)
+            .with_island(builder().with_mutation(SwapMutation::default()))
+            .with_topology(MigrationTopology::FullyConnected)
+            .with_migration_interval(5)
+            .with_migrants(2)
+            .with_seed(10)
+            .build()
+            .unwrap();
+
+        islands.run().unwrap();
+
+        assert_eq!(islands.islands().len(), 4);
+        assert_eq!(islands.epochs(), 6);
+        for island in islands.islands() {
+            assert_eq!(island.metrics.iterations, 30);
+            assert_eq!(island.current_population().len(), 10);
+        }
+    }
+
+    #[test]
+    fn ring_migration_moves_the_best_to_the_next_island() {
+        let mut islands = IslandEvolutionBuilder::new()
+            .with_islands(&builder(), 2)
+            .with_migrants(1)
+            .with_seed(5)
+            .build()
+            .unwrap();
+
+        islands.start();
+
+        let bests: Vec<Vec<bool>> = islands
+            .islands()
+            .iter()
+            .map(|island| island.current_best().get_chromosome().clone())
+            .collect();
+
+        islands.migrate();
+
+        for (i, best) in bests.iter().enumerate() {
+            let next = &islands.islands()[(i + 1) % 2];
+
+            assert!(next
+                .current_population()
+                .iter()
+                .any(|individual| individual.get_chromosome() == best));
+        }
+    }
+
+    #[test]
+    fn fully_connected_migration_keeps_the_best_immigrants() {
+        let mut islands = IslandEvolutionBuilder::new()
+            .with_islands(&builder(), 4)
+            .with_topology(MigrationTopology::FullyConnected)
+            .with_migrants(5)
+            .with_seed(5)
+            .build()
+            .unwrap();
+
+        islands.start();
+
+        let bests: Vec<Vec<bool>> = islands
+            .islands()
+            .iter()
+            .map(|island| island.current_best().get_chromosome().clone())
+            .collect();
+
+        islands.migrate();
+
+        // Each island receives 15 immigrants for its 10 individuals.
+        for (i, island) in islands.islands().iter().enumerate() {
+            assert_eq!(island.current_population().len(), 10);
+
+            for best in bests.iter().take(i).chain(bests.iter().skip(i + 1)) {
+                assert!(island
+                    .current_population()
+                    .iter()
+                    .any(|individual| individual.get_chromosome() == best));
+            }
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct CountFinishes(Arc<AtomicUsize>);
+
+    impl EvolutionObserver<Bin> for CountFinishes {
+        fn on_finish(&mut self, _evolution: &Evolution<Bin>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn notifies_the_islands_observers_when_finished() {
+        let finishes = CountFinishes::default();
+
+        let mut islands = IslandEvolutionBuilder::new()
+            .with_islands(&builder().with_observer(finishes.clone()), 3)
+            .with_seed(5)
+            .build()
+            .unwrap();
+
+        islands.run().unwrap();
+
+        assert_eq!(finishes.0.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn requires_an_island() {
+        assert!(IslandEvolutionBuilder::<Bin>::new().build().is_err());
+    }
+}
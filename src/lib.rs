@@ -10,10 +10,14 @@
 //! - [`Selection`]
 //! - [`Crossover`]
 //! - [`Mutation`]
+//! - [`Replacement`]
 //!
 //! You can code your own selection, crossover or mutation implementing the traits and passing them
 //! to the `EvolutionBuilder`.
 //!
+//! Problems with more than one objective can be solved with [`Nsga2`](multi_objective::Nsga2),
+//! which returns the Pareto front of the trade-offs between the objectives.
+//!
 //! ## Example:
 //!
 //! ```rust
@@ -60,37 +64,101 @@
 //! Find this and other examples in the [examples folder](./examples).
 
 pub mod config_read;
+pub mod config_registry;
 pub mod experiment_runner;
+pub mod experiment_suite;
 pub mod metrics;
+pub mod multi_objective;
+pub mod parameter_sweep;
+pub mod racing;
 
+mod boundary;
+mod cancellation;
+mod checkpoint;
+mod constraints;
+mod context;
 mod crossover;
+mod error;
 mod evolution;
 mod evolution_builder;
+mod export;
 mod fitness;
+mod fitness_cache;
+mod island_evolution;
 mod mutation;
+mod objective;
+mod observer;
 mod population;
+mod replacement;
+mod rng;
 mod selection;
+mod stop_condition;
+#[cfg(test)]
+mod testing;
 pub mod utils;
 pub mod plotting;
+pub mod statistics;
 
+pub use boundary::BoundaryHandler;
+pub use cancellation::CancellationToken;
+pub use constraints::{ConstraintHandling, Constraints};
+pub use context::Context;
 pub use crossover::Crossover;
+pub use error::EvolutionError;
 pub use evolution::Evolution;
 pub use evolution_builder::EvolutionBuilder;
 pub use fitness::Fitness;
+pub use island_evolution::{
+    IslandEvolution, IslandEvolutionBuilder, MigrantReplacement, MigrantSelection,
+    MigrationTopology,
+};
 pub use mutation::Mutation;
+pub use objective::Objective;
+pub use observer::EvolutionObserver;
 pub use population::Individual;
+pub use replacement::Replacement;
+pub use rng::{EvolutionRng, RngStreams};
 pub use selection::Selection;
+pub use stop_condition::StopCondition;
 
 pub mod prelude {
-    pub use crate::config_read::{read_config, RawConfig};
+    pub use crate::config_read::{
+        read_config, read_experiment_config, ExperimentConfig, RawConfig,
+    };
+    pub use crate::config_registry::OperatorRegistry;
     pub use crate::crossover::*;
     pub use crate::experiment_runner::ExperimentRunner;
+    pub use crate::experiment_suite::ExperimentSuite;
     pub use crate::fitness::Fitness;
+    pub use crate::island_evolution::{
+        IslandEvolution, IslandEvolutionBuilder, MigrantReplacement, MigrantSelection,
+        MigrationTopology,
+    };
+    pub use crate::multi_objective::{MultiFitness, Nsga2, Nsga2Builder};
     pub use crate::mutation::*;
-    pub use crate::population::{Bin, GeneCod, Int, IntPerm, Real};
+    pub use crate::parameter_sweep::{
+        ParameterSpace, ParameterSweep, ParameterValue, Parameters, Statistic,
+    };
+    pub use crate::plotting::{ChartFormat, ChartOptions, ChartScale, ChartSeries};
+    pub use crate::population::{
+        Bin, BoundValue, Bounds, GeneCod, Int, IntPerm, Node, PrimitiveFunction, PrimitiveSet, Real,
+        Tree, TreeConfig, TreeLimits, TreeValue,
+    };
+    pub use crate::racing::RacingTuner;
+    pub use crate::replacement::*;
     pub use crate::selection::*;
     pub use crate::utils::{convert_bin, within_range};
+    pub use crate::BoundaryHandler;
+    pub use crate::CancellationToken;
+    pub use crate::ConstraintHandling;
+    pub use crate::Constraints;
+    pub use crate::Context;
     pub use crate::Evolution;
+    pub use crate::EvolutionError;
+    pub use crate::EvolutionObserver;
+    pub use crate::EvolutionRng;
     pub use crate::EvolutionBuilder;
     pub use crate::Individual;
+    pub use crate::Objective;
+    pub use crate::StopCondition;
 }
@@ -1,9 +1,20 @@
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::time::Duration;
 use std::{collections::HashMap, time::Instant};
 
-use crate::plotting::plot_chart;
+use rayon::prelude::*;
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+use crate::export::{csv_cell, write_csv};
+use crate::objective::Objective;
+use crate::plotting::{plot_chart, plot_lines, ChartOptions, ChartScale, ChartSeries};
+use crate::population::Individual;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
 pub enum Steps {
     Selection,
     Crossover,
@@ -12,18 +23,121 @@ pub enum Steps {
     Elitism,
 }
 
+impl Steps {
+    pub const ALL: [Steps; 5] = [
+        Steps::Selection,
+        Steps::Crossover,
+        Steps::Mutation,
+        Steps::Fitness,
+        Steps::Elitism,
+    ];
+}
+
+/// Diversity of the chromosomes of a population, see
+/// [`EvolutionBuilder::with_diversity_metrics`](crate::EvolutionBuilder::with_diversity_metrics).
+/// Each measure is `None` when the individual doesn't support it.
+///
+/// - `entropy`: Shannon entropy, in bits, of the genes at each locus, averaged over the loci, see
+///   [`Individual::locus_entropy`].
+/// - `mean_distance`: mean distance between every pair of individuals, see
+///   [`Individual::distance`]. Hamming for `Bin` and `Int`, Euclidean for `Real` and the number
+///   of adjacencies not shared for `IntPerm`.
+/// - `unique_chromosomes`: how many different chromosomes there are, by their
+///   [`Individual::chromosome_hash`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Diversity {
+    #[serde(with = "crate::checkpoint::float")]
+    pub entropy: Option<f64>,
+    #[serde(with = "crate::checkpoint::float")]
+    pub mean_distance: Option<f64>,
+    pub unique_chromosomes: Option<usize>,
+}
+
+impl Diversity {
+    /// Measures the diversity of the `population`. The mean distance compares every pair of
+    /// individuals, so it's quadratic in the population size.
+    pub fn of<T: Individual>(population: &[T]) -> Self {
+        let pairs = population.len() * population.len().saturating_sub(1) / 2;
+
+        let mean_distance = population
+            .first()
+            .and_then(|first| first.distance(first))
+            .map(|_| {
+                let total: f64 = (0..population.len())
+                    .into_par_iter()
+                    .map(|i| {
+                        population[i + 1..]
+                            .iter()
+                            .filter_map(|other| population[i].distance(other))
+                            .sum::<f64>()
+                    })
+                    .sum();
+
+                if pairs == 0 {
+                    0.0
+                } else {
+                    total / pairs as f64
+                }
+            });
+
+        let unique_chromosomes = population
+            .iter()
+            .map(Individual::chromosome_hash)
+            .collect::<Option<HashSet<u64>>>()
+            .map(|hashes| hashes.len());
+
+        Self {
+            entropy: T::locus_entropy(population),
+            mean_distance,
+            unique_chromosomes,
+        }
+    }
+}
+
 pub struct Metrics {
     pub best_fitnesses: Vec<f64>,
     pub avg_fitnesses: Vec<f64>,
+    /// Worst fitness of the population, each generation.
+    pub worst_fitnesses: Vec<f64>,
+    /// Median fitness of the population, each generation.
+    pub median_fitnesses: Vec<f64>,
+    /// The fitness of the population at each of the configured percentiles, each generation. See
+    /// [`EvolutionBuilder::with_percentiles`](crate::EvolutionBuilder::with_percentiles).
+    pub percentile_fitnesses: Vec<Vec<f64>>,
+    /// Standard deviation of the fitness of the population, each generation.
+    pub fitness_std_devs: Vec<f64>,
     pub iterations: u32,
     pub gens_without_improvement: u32,
+    /// The generation in which the best fitness so far was found, 0 being the initial population.
+    pub best_generation: u32,
+    /// How many times the fitness was calculated until the best fitness so far was found.
+    pub best_evaluations: u64,
+    /// How many times the fitness was calculated.
+    pub evaluations: u64,
+    /// How many times the fitness was calculated until each generation, included.
+    pub generation_evaluations: Vec<u64>,
+    /// Fitness evaluations avoided by the fitness cache, because the individual didn't change or
+    /// its chromosome was cached.
+    pub cache_hits: u64,
+    /// Fitness evaluations made while the fitness cache is enabled.
+    pub cache_misses: u64,
+    /// Diversity of the population, each generation, when it's measured.
+    pub diversities: Vec<Diversity>,
     start_time: Instant,
     end_time: Instant,
     pub step_times: HashMap<Steps, (bool, Instant, u128)>,
+    objective: Objective,
+    percentiles: Vec<f64>,
 }
 
 impl Metrics {
     pub fn new() -> Self {
+        Self::with_objective(Objective::Maximize)
+    }
+
+    /// Creates the metrics of an evolution with the given objective, used to tell when the best
+    /// fitness improved.
+    pub fn with_objective(objective: Objective) -> Self {
         let mut step_times = HashMap::new();
         step_times.insert(Steps::Selection, (false, Instant::now(), 0));
         step_times.insert(Steps::Crossover, (false, Instant::now(), 0));
@@ -34,29 +148,88 @@ impl Metrics {
         Self {
             best_fitnesses: Vec::new(),
             avg_fitnesses: Vec::new(),
+            worst_fitnesses: Vec::new(),
+            median_fitnesses: Vec::new(),
+            percentile_fitnesses: Vec::new(),
+            fitness_std_devs: Vec::new(),
             iterations: 0,
             gens_without_improvement: 0,
+            best_generation: 0,
+            best_evaluations: 0,
+            evaluations: 0,
+            generation_evaluations: Vec::new(),
+            cache_hits: 0,
+            cache_misses: 0,
+            diversities: Vec::new(),
             start_time: Instant::now(),
             end_time: Instant::now(),
             step_times,
+            objective,
+            percentiles: Vec::new(),
         }
     }
 
+    /// Sets the percentiles, from 0 to 100, of the fitness of the population recorded each
+    /// generation by [`Metrics::record_spread`]. The ones out of that range are clamped to it.
+    pub fn with_percentiles(mut self, percentiles: Vec<f64>) -> Self {
+        self.percentiles = percentiles;
+        self
+    }
+
+    pub fn percentiles(&self) -> &[f64] {
+        &self.percentiles
+    }
+
     pub fn record(&mut self, best_fitness: f64, avg_fitness: f64) {
-        if self.best_fitnesses.len() > 0
-            && self.best_fitnesses[self.best_fitnesses.len() - 1] == best_fitness
-        {
-            self.gens_without_improvement += 1;
-        } else {
-            self.gens_without_improvement = 0;
+        match self.best_so_far() {
+            Some(best) if !self.objective.is_better(best_fitness, best) => {
+                self.gens_without_improvement += 1;
+            }
+            _ => {
+                self.gens_without_improvement = 0;
+                self.best_generation = self.iterations;
+                self.best_evaluations = self.evaluations;
+            }
         }
 
         self.best_fitnesses.push(best_fitness);
         self.avg_fitnesses.push(avg_fitness);
+        self.generation_evaluations.push(self.evaluations);
 
         self.iterations += 1;
     }
 
+    /// Records the spread of the `fitnesses` of the population: the worst, the median and the
+    /// configured percentiles.
+    pub fn record_spread(&mut self, fitnesses: &[f64]) {
+        let mut sorted = fitnesses.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let worst = self.objective.worst(sorted.iter().copied()).unwrap_or(f64::NAN);
+        self.worst_fitnesses.push(worst);
+        self.median_fitnesses.push(percentile(&sorted, 50.0));
+        self.percentile_fitnesses
+            .push(self.percentiles.iter().map(|&p| percentile(&sorted, p)).collect());
+    }
+
+    /// How many times the fitness was calculated until the best fitness was at least as good as
+    /// the `target`, if it ever was.
+    pub fn evaluations_to(&self, target: f64) -> Option<u64> {
+        self.best_fitnesses
+            .iter()
+            .position(|&best| !self.objective.is_better(target, best))
+            .and_then(|generation| self.generation_evaluations.get(generation).copied())
+    }
+
+    /// The best fitness recorded so far, according to the objective.
+    pub fn best_so_far(&self) -> Option<f64> {
+        self.objective.best(self.best_fitnesses.iter().copied())
+    }
+
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
     pub fn start_clock(&mut self) {
         self.start_time = Instant::now();
     }
@@ -65,6 +238,11 @@ impl Metrics {
         self.end_time = Instant::now();
     }
 
+    /// Time since the clock started.
+    pub fn elapsed(&self) -> Duration {
+        self.start_time.elapsed()
+    }
+
     pub fn step_start(&mut self, step: Steps) {
         self.step_times.get_mut(&step).map(|a| {
             if a.0 {
@@ -97,9 +275,313 @@ impl Metrics {
 
     pub fn plot_chart(
         &self,
-        path: &String,
-        test_name: &String,
+        path: &str,
+        test_name: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        plot_chart(&self.best_fitnesses, &self.avg_fitnesses, path, test_name)
+        plot_chart(
+            &self.best_fitnesses,
+            &self.avg_fitnesses,
+            path,
+            test_name,
+            self.objective,
+        )
+    }
+
+    /// Writes the metrics of each generation to `path` as CSV, one row per generation with the
+    /// best, average, worst and median fitness, its standard deviation, the configured
+    /// percentiles and, when it was measured, the diversity.
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        write_csv(path.as_ref(), &self.csv_header(), self.csv_rows())
+    }
+
+    /// Writes all the metrics to `path` as JSON: the series of each generation, the counters and
+    /// the time spent in each step, in nanoseconds.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, &self.export())?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    pub(crate) fn csv_header(&self) -> Vec<String> {
+        let mut header: Vec<String> =
+            ["generation", "best", "average", "worst", "median", "std_dev"]
+                .map(String::from)
+                .to_vec();
+
+        header.extend(self.percentiles.iter().map(|p| format!("p{}", p)));
+
+        if !self.diversities.is_empty() {
+            header.extend(["entropy", "mean_distance", "unique_chromosomes"].map(String::from));
+        }
+
+        header
+    }
+
+    pub(crate) fn csv_rows(&self) -> Vec<Vec<String>> {
+        (0..self.best_fitnesses.len())
+            .map(|i| {
+                let mut row = vec![
+                    i.to_string(),
+                    self.best_fitnesses[i].to_string(),
+                    csv_cell(self.avg_fitnesses.get(i)),
+                    csv_cell(self.worst_fitnesses.get(i)),
+                    csv_cell(self.median_fitnesses.get(i)),
+                    csv_cell(self.fitness_std_devs.get(i)),
+                ];
+
+                let percentiles = self.percentile_fitnesses.get(i);
+                row.extend((0..self.percentiles.len()).map(|p| {
+                    csv_cell(percentiles.and_then(|percentiles| percentiles.get(p)))
+                }));
+
+                if !self.diversities.is_empty() {
+                    let diversity = self.diversities.get(i);
+                    row.extend([
+                        csv_cell(diversity.and_then(|d| d.entropy)),
+                        csv_cell(diversity.and_then(|d| d.mean_distance)),
+                        csv_cell(diversity.and_then(|d| d.unique_chromosomes)),
+                    ]);
+                }
+
+                row
+            })
+            .collect()
+    }
+
+    /// The metrics as they're exported to JSON.
+    pub(crate) fn export(&self) -> MetricsExport<'_> {
+        MetricsExport {
+            best_fitnesses: &self.best_fitnesses,
+            avg_fitnesses: &self.avg_fitnesses,
+            worst_fitnesses: &self.worst_fitnesses,
+            median_fitnesses: &self.median_fitnesses,
+            fitness_std_devs: &self.fitness_std_devs,
+            percentiles: &self.percentiles,
+            percentile_fitnesses: &self.percentile_fitnesses,
+            diversities: &self.diversities,
+            iterations: self.iterations,
+            gens_without_improvement: self.gens_without_improvement,
+            best_generation: self.best_generation,
+            best_evaluations: self.best_evaluations,
+            evaluations: self.evaluations,
+            generation_evaluations: &self.generation_evaluations,
+            cache_hits: self.cache_hits,
+            cache_misses: self.cache_misses,
+            total_time_ns: self.total_time(),
+            step_times_ns: Steps::ALL
+                .into_iter()
+                .filter_map(|step| self.step_time(step).map(|time| (step, time.as_nanos())))
+                .collect(),
+        }
+    }
+
+    /// Plots the diversity of the population over the generations: the standard deviation of the
+    /// fitness and the measures of [`Diversity`] the individual supports, each scaled on its own.
+    pub fn plot_diversity(
+        &self,
+        path: &str,
+        test_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.plot(
+            &ChartOptions::new(path, test_name)
+                .with_scale(ChartScale::NormalizedPerSeries)
+                .with_series(vec![
+                    ChartSeries::FitnessStdDev,
+                    ChartSeries::Entropy,
+                    ChartSeries::MeanDistance,
+                    ChartSeries::UniqueChromosomes,
+                ]),
+        )
+    }
+
+    /// Plots the series selected in the `options` over the generations, see [`ChartOptions`].
+    pub fn plot(&self, options: &ChartOptions) -> Result<(), Box<dyn std::error::Error>> {
+        let series: Vec<(String, Vec<f64>)> = options
+            .series
+            .iter()
+            .flat_map(|&series| self.chart_series(series))
+            .collect();
+
+        plot_lines(&series, options)
+    }
+
+    /// The labeled values of the `series` over the generations. It's empty for a diversity
+    /// measure that wasn't measured, and has one entry per percentile for the percentiles.
+    pub fn chart_series(&self, series: ChartSeries) -> Vec<(String, Vec<f64>)> {
+        let measure = |label: &str, f: fn(&Diversity) -> Option<f64>| {
+            self.diversities
+                .iter()
+                .map(f)
+                .collect::<Option<Vec<f64>>>()
+                .filter(|values| !values.is_empty())
+                .map(|values| (label.to_string(), values))
+        };
+
+        match series {
+            ChartSeries::Best => {
+                let label = match self.objective {
+                    Objective::Maximize => "Best Fitness",
+                    Objective::Minimize => "Best Fitness (Lowest)",
+                };
+                vec![(label.to_string(), self.best_fitnesses.clone())]
+            }
+            ChartSeries::Average => {
+                vec![("Average Fitness".to_string(), self.avg_fitnesses.clone())]
+            }
+            ChartSeries::Worst => vec![("Worst Fitness".to_string(), self.worst_fitnesses.clone())],
+            ChartSeries::Median => {
+                vec![("Median Fitness".to_string(), self.median_fitnesses.clone())]
+            }
+            ChartSeries::Percentiles => self
+                .percentiles
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let values = self
+                        .percentile_fitnesses
+                        .iter()
+                        .map(|percentiles| percentiles.get(i).copied().unwrap_or(f64::NAN))
+                        .collect();
+                    (format!("P{} Fitness", p), values)
+                })
+                .collect(),
+            ChartSeries::FitnessStdDev => {
+                vec![("Fitness Std Dev".to_string(), self.fitness_std_devs.clone())]
+            }
+            ChartSeries::Entropy => measure("Locus Entropy", |d| d.entropy).into_iter().collect(),
+            ChartSeries::MeanDistance => {
+                measure("Mean Distance", |d| d.mean_distance).into_iter().collect()
+            }
+            ChartSeries::UniqueChromosomes => {
+                measure("Unique Chromosomes", |d| d.unique_chromosomes.map(|u| u as f64))
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+}
+
+/// The metrics as they're exported to JSON, see [`Metrics::write_json`].
+#[derive(Serialize)]
+pub(crate) struct MetricsExport<'a> {
+    best_fitnesses: &'a [f64],
+    avg_fitnesses: &'a [f64],
+    worst_fitnesses: &'a [f64],
+    median_fitnesses: &'a [f64],
+    fitness_std_devs: &'a [f64],
+    percentiles: &'a [f64],
+    percentile_fitnesses: &'a [Vec<f64>],
+    diversities: &'a [Diversity],
+    iterations: u32,
+    gens_without_improvement: u32,
+    best_generation: u32,
+    best_evaluations: u64,
+    evaluations: u64,
+    generation_evaluations: &'a [u64],
+    cache_hits: u64,
+    cache_misses: u64,
+    total_time_ns: u128,
+    step_times_ns: BTreeMap<Steps, u128>,
+}
+
+/// The `p`th percentile, from 0 to 100, of the `sorted` values, interpolating between the closest
+/// ranks. It's NaN when there are no values or `p` is NaN, and `p` is clamped to 0 and 100.
+pub(crate) fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() || p.is_nan() {
+        return f64::NAN;
+    }
+
+    let rank = p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use rand::SeedableRng;
+
+    fn individual<T: Individual>(range: T::RangeType, chromosome: Vec<T::Gene>) -> T {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut individual = T::generate_member(chromosome.len() as u32, &range, &mut rng);
+
+        *individual.get_mut_chromosome() = chromosome;
+        individual
+    }
+
+    #[test]
+    fn measures_the_diversity() {
+        let bins: Vec<Bin> = [[true, true], [true, false], [true, true]]
+            .into_iter()
+            .map(|genes| Bin::new(genes.to_vec()))
+            .collect();
+
+        let diversity = Diversity::of(&bins);
+        let entropy = -(1.0 / 3.0f64).log2() / 3.0 - (2.0 / 3.0f64).log2() * 2.0 / 3.0;
+
+        assert!((diversity.entropy.unwrap() - entropy / 2.0).abs() < 1e-9);
+        assert_eq!(diversity.mean_distance, Some(2.0 / 3.0));
+        assert_eq!(diversity.unique_chromosomes, Some(2));
+
+        let reals: Vec<Real> = vec![
+            individual((0.0, 5.0).into(), vec![0.0, 0.0]),
+            individual((0.0, 5.0).into(), vec![3.0, 4.0]),
+        ];
+        let diversity = Diversity::of(&reals);
+
+        assert_eq!(diversity.entropy, None);
+        assert_eq!(diversity.mean_distance, Some(5.0));
+        assert_eq!(diversity.unique_chromosomes, Some(2));
+    }
+
+    #[test]
+    fn records_the_spread_of_the_fitness() {
+        let mut metrics = Metrics::with_objective(Objective::Minimize).with_percentiles(vec![25.0]);
+
+        metrics.record_spread(&[4.0, 1.0, 3.0, 2.0, 5.0]);
+        for (evaluations, best_fitness) in [(10, 1.0), (20, 1.0), (30, 0.5), (40, 0.7)] {
+            metrics.evaluations = evaluations;
+            metrics.record(best_fitness, best_fitness);
+        }
+
+        assert_eq!(metrics.worst_fitnesses, vec![5.0]);
+        assert_eq!(metrics.median_fitnesses, vec![3.0]);
+        assert_eq!(metrics.percentile_fitnesses, vec![vec![2.0]]);
+        assert_eq!((metrics.best_generation, metrics.best_evaluations), (2, 30));
+        assert_eq!(percentile(&[1.0, 2.0], 50.0), 1.5);
+    }
+
+    #[test]
+    fn handles_the_percentiles_out_of_range() {
+        let mut metrics = Metrics::new().with_percentiles(vec![-10.0, 150.0]);
+        metrics.record_spread(&[1.0, 2.0, 3.0]);
+        assert_eq!(metrics.percentile_fitnesses, vec![vec![1.0, 3.0]]);
+        assert!(percentile(&[1.0, 2.0], f64::NAN).is_nan());
+
+        metrics.percentile_fitnesses.push(vec![2.0]);
+        let series = metrics.chart_series(ChartSeries::Percentiles);
+        assert_eq!(series[0].1, vec![1.0, 2.0]);
+        assert!(series[1].1[1].is_nan());
+
+        let builder = EvolutionBuilder::<Bin>::new(10, 8, GeneCod::Bin, ())
+            .with_percentiles([50.0, 101.0]);
+        assert_eq!(
+            builder.build().err().unwrap(),
+            "The percentiles should be between 0 and 100"
+        );
+    }
+
+    #[test]
+    fn measures_the_adjacencies_of_permutations() {
+        let perm = |chromosome: Vec<i64>| -> IntPerm { individual((), chromosome) };
+        let a = perm(vec![0, 1, 2, 3]);
+
+        assert_eq!(a.distance(&perm(vec![3, 2, 1, 0])), Some(0.0));
+        assert_eq!(a.distance(&perm(vec![1, 0, 3, 2])), Some(1.0));
+        assert_eq!(a.distance(&perm(vec![2, 0, 3, 1])), Some(3.0));
     }
 }
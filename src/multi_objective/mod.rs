@@ -0,0 +1,41 @@
+//! Multi-objective optimization. When a problem has conflicting objectives, like cost and latency,
+//! there is no single best solution but a set of trade-offs where no objective can be improved
+//! without worsening another, the Pareto front. Instead of folding the objectives into a weighted
+//! sum, implement [`MultiFitness`] and evolve the front with [`Nsga2`].
+
+use dyn_clone::DynClone;
+
+use crate::population::Individual;
+
+mod nsga2;
+mod sorting;
+
+pub use nsga2::*;
+pub use sorting::*;
+
+/// Trait that defines the objectives of a multi-objective problem. It's the counterpart of the
+/// [`Fitness`](crate::Fitness) trait, but returns one value per objective. Every objective is
+/// maximized, so negate the ones you want to minimize.
+///
+/// Example:
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// #[derive(Clone)]
+/// struct OnesAndZeros;
+///
+/// impl MultiFitness<Bin> for OnesAndZeros {
+///     fn calculate_objectives(&self, individual: &Bin) -> Vec<f64> {
+///         let ones = individual.get_chromosome().iter().filter(|&&gene| gene).count();
+///         let zeros = individual.get_chromosome().len() - ones;
+///
+///         vec![ones as f64, zeros as f64]
+///     }
+/// }
+/// ```
+pub trait MultiFitness<T: Individual>: 'static + DynClone + Send + Sync {
+    /// Method that calculates the objectives of an individual. It must always return the same
+    /// number of objectives.
+    fn calculate_objectives(&self, individual: &T) -> Vec<f64>;
+}
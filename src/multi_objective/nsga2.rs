@@ -0,0 +1,516 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    boundary::BoundaryHandler,
+    cancellation::CancellationToken,
+    context::Context,
+    crossover::Crossover,
+    evolution::EvolutionConfig,
+    mutation::Mutation,
+    objective::Objective,
+    population::{GeneCod, Individual},
+    rng::EvolutionRng,
+};
+
+use super::{crowding_distance, non_dominated_sort, MultiFitness};
+
+pub type MultiStopConditionFn = Arc<dyn Fn(u32) -> bool + Send + Sync>;
+
+/// # NSGA-II
+///
+/// The Non-dominated Sorting Genetic Algorithm II. Instead of a single fitness, each individual
+/// has a vector of objectives, calculated by a [`MultiFitness`]. Every generation it selects the
+/// parents with a binary tournament using the crowded comparison, applies the crossover and the
+/// mutation, and keeps the best half of the parents and offsprings, ranked by their Pareto front
+/// and then by the crowding distance.
+///
+/// Build it with the [`Nsga2Builder`] and, after running it, get the solutions found with
+/// [`Nsga2::pareto_front`]. The operators get the same [`Context`] as in an
+/// [`Evolution`](crate::Evolution), but without a fitness, so the crossovers that evaluate their
+/// offsprings, like the [`LinearCrossover`](crate::crossover::LinearCrossover), are rejected.
+///
+/// Reference: [A fast and elitist multiobjective genetic algorithm: NSGA-II](https://ieeexplore.ieee.org/document/996017)
+pub struct Nsga2<T: Individual> {
+    config: EvolutionConfig<T>,
+    fitness: Box<dyn MultiFitness<T>>,
+    crossover: Box<dyn Crossover<T>>,
+    mutation: Box<dyn Mutation<T>>,
+    stop_condition: MultiStopConditionFn,
+    rng: EvolutionRng,
+    boundary_handler: BoundaryHandler,
+    cancellation: Option<CancellationToken>,
+    iterations: u32,
+    population: Vec<T>,
+    objectives: Vec<Vec<f64>>,
+    ranks: Vec<usize>,
+    crowding_distances: Vec<f64>,
+}
+
+impl<T: Individual> Nsga2<T> {
+    pub fn new(
+        config: EvolutionConfig<T>,
+        fitness: Box<dyn MultiFitness<T>>,
+        crossover: Box<dyn Crossover<T>>,
+        mutation: Box<dyn Mutation<T>>,
+        stop_condition: MultiStopConditionFn,
+        rng: EvolutionRng,
+    ) -> Self {
+        Self {
+            config,
+            fitness,
+            crossover,
+            mutation,
+            stop_condition,
+            rng,
+            boundary_handler: BoundaryHandler::default(),
+            cancellation: None,
+            iterations: 0,
+            population: Vec::new(),
+            objectives: Vec::new(),
+            ranks: Vec::new(),
+            crowding_distances: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_boundary_handler(mut self, boundary_handler: BoundaryHandler) -> Self {
+        self.boundary_handler = boundary_handler;
+        self
+    }
+
+    pub(crate) fn with_cancellation(mut self, cancellation: Option<CancellationToken>) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Starts the evolution, generating the initial population and calculating the objectives of
+    /// each individual.
+    pub fn start(&mut self) {
+        let streams = self.rng.streams();
+
+        let population: Vec<T> = (0..self.config.population_size)
+            .into_par_iter()
+            .map(|i| {
+                let mut rng = streams.get(i as usize);
+                T::generate_member(self.config.dimension, &self.config.range, &mut rng)
+            })
+            .collect();
+        let objectives = self.calculate_objectives(&population);
+
+        self.iterations = 0;
+        self.survive(population, objectives);
+    }
+
+    /// Runs one generation: selects the parents, crossover and mutate them into the offsprings and
+    /// keeps the best individuals among parents and offsprings.
+    pub fn next(&mut self) {
+        let streams = self.rng.streams();
+        let len = self.population.len();
+
+        let mut offsprings: Vec<T> = (0..len)
+            .into_par_iter()
+            .map(|i| {
+                let mut rng = streams.get(i);
+
+                let a = rng.gen_range(0..len);
+                let b = rng.gen_range(0..len);
+
+                let winner = if self.crowded_cmp(a, b) == Ordering::Less {
+                    b
+                } else {
+                    a
+                };
+
+                self.population[winner].clone()
+            })
+            .collect();
+
+        // The operators don't compare fitness, and NSGA-II maximizes every objective anyway.
+        let mut context = Context::new(&mut self.rng, Objective::Maximize)
+            .with_boundary_handler(self.boundary_handler);
+
+        self.crossover.crossover(&mut offsprings, &mut context);
+        self.mutation.mutate(&mut offsprings, &mut context);
+
+        let offsprings_objectives = self.calculate_objectives(&offsprings);
+
+        let mut population = std::mem::take(&mut self.population);
+        let mut objectives = std::mem::take(&mut self.objectives);
+
+        population.extend(offsprings);
+        objectives.extend(offsprings_objectives);
+
+        self.survive(population, objectives);
+    }
+
+    /// Runs the evolution, generation over generation, until the stop condition is met or it's
+    /// cancelled.
+    pub fn run(&mut self) {
+        self.start();
+
+        while !self.reached_stop_condition() && !self.is_cancelled() {
+            self.next();
+        }
+    }
+
+    /// Returns if the stop condition was already met.
+    pub fn reached_stop_condition(&self) -> bool {
+        (self.stop_condition)(self.iterations)
+    }
+
+    /// Returns if the evolution was cancelled through its [`CancellationToken`].
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Number of generations, counting the initial population.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Returns the non-dominated individuals of the current population with their objectives.
+    pub fn pareto_front(&self) -> Vec<(&T, &Vec<f64>)> {
+        (0..self.population.len())
+            .filter(|&i| self.ranks[i] == 0)
+            .map(|i| (&self.population[i], &self.objectives[i]))
+            .collect()
+    }
+
+    /// Returns the current population with the objectives of each individual.
+    pub fn current_population(&self) -> Vec<(&T, &Vec<f64>)> {
+        self.population.iter().zip(self.objectives.iter()).collect()
+    }
+
+    /// Calculates the objectives of each individual, penalized by the boundary handler.
+    fn calculate_objectives(&self, population: &[T]) -> Vec<Vec<f64>> {
+        population
+            .par_iter()
+            .map(|individual| {
+                self.fitness
+                    .calculate_objectives(individual)
+                    .into_iter()
+                    .map(|objective| {
+                        self.boundary_handler.penalize(objective, individual, Objective::Maximize)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Crowded comparison: the lower rank wins and, within the same front, the larger crowding
+    /// distance wins.
+    fn crowded_cmp(&self, a: usize, b: usize) -> Ordering {
+        self.ranks[b].cmp(&self.ranks[a]).then(
+            self.crowding_distances[a]
+                .partial_cmp(&self.crowding_distances[b])
+                .unwrap_or(Ordering::Equal),
+        )
+    }
+
+    /// Keeps the `population_size` best individuals of `population`, front by front, breaking the
+    /// last front that doesn't fit by the crowding distance.
+    fn survive(&mut self, population: Vec<T>, objectives: Vec<Vec<f64>>) {
+        let population_size = self.config.population_size as usize;
+
+        let mut selected = Vec::with_capacity(population_size);
+
+        for (rank, front) in non_dominated_sort(&objectives).into_iter().enumerate() {
+            if selected.len() >= population_size {
+                break;
+            }
+
+            let distances = crowding_distance(&objectives, &front);
+            let mut front: Vec<(usize, f64)> = front.into_iter().zip(distances).collect();
+
+            if selected.len() + front.len() > population_size {
+                front.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+                front.truncate(population_size - selected.len());
+            }
+
+            selected.extend(front.into_iter().map(|(index, distance)| (index, rank, distance)));
+        }
+
+        let mut population: Vec<Option<T>> = population.into_iter().map(Some).collect();
+        let mut objectives: Vec<Option<Vec<f64>>> = objectives.into_iter().map(Some).collect();
+
+        self.population = Vec::with_capacity(population_size);
+        self.objectives = Vec::with_capacity(population_size);
+        self.ranks = Vec::with_capacity(population_size);
+        self.crowding_distances = Vec::with_capacity(population_size);
+
+        for (index, rank, distance) in selected {
+            self.population.push(population[index].take().unwrap());
+            self.objectives.push(objectives[index].take().unwrap());
+            self.ranks.push(rank);
+            self.crowding_distances.push(distance);
+        }
+
+        self.iterations += 1;
+    }
+}
+
+/// Helper struct to create a new [`Nsga2`] object. The `fitness`, `crossover`, `mutation` and
+/// `stop_condition` are required.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// // Maximizes both -x² and -(x - 2)², so every x in [0, 2] is a Pareto optimal solution.
+/// #[derive(Clone)]
+/// struct TwoParabolas;
+///
+/// impl MultiFitness<Real> for TwoParabolas {
+///     fn calculate_objectives(&self, individual: &Real) -> Vec<f64> {
+///         let x = individual.get_gene(0);
+///
+///         vec![-x * x, -(x - 2.0) * (x - 2.0)]
+///     }
+/// }
+///
+/// let mut nsga2 = Nsga2Builder::new(20, 1, GeneCod::Real, (-5.0, 5.0))
+///     .with_fitness(TwoParabolas)
+///     .with_crossover(BlendCrossover::default())
+///     .with_mutation(DeltaMutation::default())
+///     .with_stop_condition(|iterations| iterations >= 50)
+///     .with_seed(42)
+///     .build()
+///     .unwrap();
+///
+/// nsga2.run();
+///
+/// for (individual, objectives) in nsga2.pareto_front() {
+///     println!("x = {:.3}: {:?}", individual.get_gene(0), objectives);
+/// }
+/// ```
+pub struct Nsga2Builder<T: Individual> {
+    config: EvolutionConfig<T>,
+    fitness: Option<Box<dyn MultiFitness<T>>>,
+    crossover: Option<Box<dyn Crossover<T>>>,
+    mutation: Option<Box<dyn Mutation<T>>>,
+    stop_condition: Option<MultiStopConditionFn>,
+    seed: Option<u64>,
+    boundary_handler: BoundaryHandler,
+    cancellation: Option<CancellationToken>,
+}
+
+impl<T: Individual> Nsga2Builder<T> {
+    pub fn new(
+        population_size: u32,
+        dimension: u32,
+        gene_cod: GeneCod,
+        range: impl Into<T::RangeType>,
+    ) -> Self {
+        Self::from_config(EvolutionConfig {
+            dimension,
+            population_size,
+            range: range.into(),
+            gene_cod,
+        })
+    }
+
+    /// Helper method to create a new Nsga2Builder from a EvolutionConfig.
+    pub fn from_config(config: EvolutionConfig<T>) -> Self {
+        Self {
+            config,
+            fitness: None,
+            crossover: None,
+            mutation: None,
+            stop_condition: None,
+            seed: None,
+            boundary_handler: BoundaryHandler::default(),
+            cancellation: None,
+        }
+    }
+
+    /// Sets the objectives function. Receives a struct that implements the MultiFitness trait.
+    pub fn with_fitness<F: MultiFitness<T>>(mut self, f: F) -> Self {
+        self.fitness = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the crossover operator. Receives a struct that implements the Crossover trait.
+    pub fn with_crossover<X: Crossover<T>>(mut self, c: X) -> Self {
+        self.crossover = Some(Box::new(c));
+        self
+    }
+
+    /// Sets the mutation operator. Receives a struct that implements the Mutation trait.
+    pub fn with_mutation<M: Mutation<T>>(mut self, m: M) -> Self {
+        self.mutation = Some(Box::new(m));
+        self
+    }
+
+    /// Sets the stop condition. Receives a closure that receives the current iteration and returns
+    /// a boolean.
+    pub fn with_stop_condition<F: Fn(u32) -> bool + 'static + Send + Sync>(mut self, f: F) -> Self {
+        self.stop_condition = Some(Arc::new(f));
+        self
+    }
+
+    /// Sets the seed of the random number generator, making the evolution reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets what the operators do with the genes they push out of their bounds, see
+    /// [`BoundaryHandler`]. With `Penalty`, every objective is penalized.
+    pub fn with_boundary_handler(mut self, boundary_handler: BoundaryHandler) -> Self {
+        self.boundary_handler = boundary_handler;
+        self
+    }
+
+    /// Stops the evolution between generations when the token is cancelled, see
+    /// [`CancellationToken`].
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    pub fn build(&self) -> Result<Nsga2<T>, String> {
+        T::check_range(self.config.dimension, &self.config.range)?;
+
+        if self.crossover.as_ref().is_some_and(|c| c.needs_fitness()) {
+            return Err(
+                "The crossover needs a single fitness, which NSGA-II doesn't have".to_string()
+            );
+        }
+
+        if let (Some(f), Some(x), Some(m), Some(stop_condition)) = (
+            self.fitness.as_ref().map(|f| f.as_ref()),
+            self.crossover.as_ref().map(|c| c.as_ref()),
+            self.mutation.as_ref().map(|m| m.as_ref()),
+            self.stop_condition.as_ref(),
+        ) {
+            x.check()?;
+            m.check()?;
+
+            Ok(Nsga2::new(
+                self.config.clone(),
+                dyn_clone::clone_box(f),
+                dyn_clone::clone_box(x),
+                dyn_clone::clone_box(m),
+                Arc::clone(stop_condition),
+                self.seed
+                    .map_or_else(EvolutionRng::from_entropy, EvolutionRng::seed_from_u64),
+            )
+            .with_boundary_handler(self.boundary_handler)
+            .with_cancellation(self.cancellation.clone()))
+        } else {
+            Err("Missing required parameters".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::multi_objective::dominates;
+    use crate::prelude::*;
+
+    #[derive(Clone)]
+    struct TwoParabolas;
+
+    impl MultiFitness<Real> for TwoParabolas {
+        fn calculate_objectives(&self, individual: &Real) -> Vec<f64> {
+            let x = individual.get_gene(0);
+
+            vec![-x * x, -(x - 2.0) * (x - 2.0)]
+        }
+    }
+
+    #[test]
+    fn converges_to_the_pareto_set() {
+        let mut nsga2 = Nsga2Builder::new(20, 1, GeneCod::Real, (-10.0, 10.0))
+            .with_fitness(TwoParabolas)
+            .with_crossover(BlendCrossover::default())
+            .with_mutation(DeltaMutation::default())
+            .with_stop_condition(|iterations| iterations >= 100)
+            .with_seed(3)
+            .build()
+            .unwrap();
+
+        nsga2.run();
+
+        let front = nsga2.pareto_front();
+
+        assert_eq!(nsga2.current_population().len(), 20);
+        assert!(front.len() > 1);
+
+        for (individual, objectives) in &front {
+            let x = individual.get_gene(0);
+            assert!((-0.1..=2.1).contains(&x), "{} is not Pareto optimal", x);
+
+            for (_, other) in &front {
+                assert!(!dominates(other, objectives));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_crossovers_that_need_a_fitness() {
+        let builder = Nsga2Builder::new(20, 1, GeneCod::Real, (-10.0, 10.0))
+            .with_fitness(TwoParabolas)
+            .with_crossover(LinearCrossover::default())
+            .with_mutation(DeltaMutation::default())
+            .with_stop_condition(|iterations| iterations >= 100);
+
+        assert!(builder.build().is_err());
+    }
+
+    fn run_on(range: (f64, f64), boundary_handler: BoundaryHandler) -> Nsga2<Real> {
+        let mut nsga2 = Nsga2Builder::new(20, 1, GeneCod::Real, range)
+            .with_fitness(TwoParabolas)
+            .with_crossover(BlendCrossover::default())
+            .with_mutation(DeltaMutation::default())
+            .with_stop_condition(|iterations| iterations >= 20)
+            .with_boundary_handler(boundary_handler)
+            .with_seed(3)
+            .build()
+            .unwrap();
+
+        nsga2.run();
+        nsga2
+    }
+
+    #[test]
+    fn applies_the_boundary_handler() {
+        let clamped = run_on((0.5, 1.0), BoundaryHandler::Clamp);
+        let penalized = run_on((0.5, 1.0), BoundaryHandler::Penalty { coefficient: 10.0 });
+
+        for (individual, _) in clamped.current_population() {
+            assert!((0.5..=1.0).contains(&individual.get_gene(0)));
+        }
+
+        for (individual, objectives) in penalized.current_population() {
+            let x = individual.get_gene(0);
+            let penalty = 10.0 * individual.bounds_violation();
+
+            assert_eq!(objectives, &vec![-x * x - penalty, -(x - 2.0) * (x - 2.0) - penalty]);
+        }
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let mut nsga2 = Nsga2Builder::new(20, 1, GeneCod::Real, (-10.0, 10.0))
+            .with_fitness(TwoParabolas)
+            .with_crossover(BlendCrossover::default())
+            .with_mutation(DeltaMutation::default())
+            .with_stop_condition(|iterations| iterations >= 100)
+            .with_cancellation(token)
+            .build()
+            .unwrap();
+
+        nsga2.run();
+
+        assert!(nsga2.is_cancelled());
+        assert_eq!(nsga2.iterations(), 1);
+    }
+}
@@ -0,0 +1,164 @@
+use std::cmp::Ordering;
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+/// Returns if the objectives `a` dominate the objectives `b`, that is, `a` is not worse than `b`
+/// in any objective and is strictly better in at least one of them. All the objectives are
+/// maximized.
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    let mut strictly_better = false;
+
+    for (x, y) in a.iter().zip(b) {
+        if x < y {
+            return false;
+        }
+        if x > y {
+            strictly_better = true;
+        }
+    }
+
+    strictly_better
+}
+
+/// # Fast Non-Dominated Sort
+///
+/// Splits the solutions into Pareto fronts. The first front holds the indexes of the solutions no
+/// other solution dominates, the second front the ones only dominated by solutions of the first
+/// front, and so on.
+///
+/// Reference: [A fast and elitist multiobjective genetic algorithm: NSGA-II](https://ieeexplore.ieee.org/document/996017)
+pub fn non_dominated_sort(objectives: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let len = objectives.len();
+
+    let (mut dominated, mut domination_count): (Vec<Vec<usize>>, Vec<usize>) = (0..len)
+        .into_par_iter()
+        .map(|p| {
+            let mut dominated = Vec::new();
+            let mut domination_count = 0;
+
+            for q in 0..len {
+                if dominates(&objectives[p], &objectives[q]) {
+                    dominated.push(q);
+                } else if dominates(&objectives[q], &objectives[p]) {
+                    domination_count += 1;
+                }
+            }
+
+            (dominated, domination_count)
+        })
+        .unzip();
+
+    let mut fronts = vec![];
+    let mut current: Vec<usize> = (0..len).filter(|&p| domination_count[p] == 0).collect();
+
+    while !current.is_empty() {
+        let mut next = vec![];
+
+        for &p in &current {
+            for q in std::mem::take(&mut dominated[p]) {
+                domination_count[q] -= 1;
+
+                if domination_count[q] == 0 {
+                    next.push(q);
+                }
+            }
+        }
+
+        fronts.push(current);
+        current = next;
+    }
+
+    fronts
+}
+
+/// # Crowding Distance
+///
+/// Calculates how isolated each solution of a front is from its neighbours in the objective space.
+/// Returns the distances in the same order as `front`. The solutions at the boundaries of each
+/// objective get an infinite distance, so they're always preferred.
+pub fn crowding_distance(objectives: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let len = front.len();
+
+    if len <= 2 {
+        return vec![f64::INFINITY; len];
+    }
+
+    let mut distances = vec![0.0; len];
+
+    for m in 0..objectives[front[0]].len() {
+        add_objective_distance(objectives, front, m, &mut distances);
+    }
+
+    distances
+}
+
+fn add_objective_distance(
+    objectives: &[Vec<f64>],
+    front: &[usize],
+    objective: usize,
+    distances: &mut [f64],
+) {
+    let len = front.len();
+    let value = |k: usize| objectives[front[k]][objective];
+
+    let mut order: Vec<usize> = (0..len).collect();
+    order.sort_by(|&a, &b| value(a).partial_cmp(&value(b)).unwrap_or(Ordering::Equal));
+
+    let min = value(order[0]);
+    let max = value(order[len - 1]);
+
+    distances[order[0]] = f64::INFINITY;
+    distances[order[len - 1]] = f64::INFINITY;
+
+    if max == min {
+        return;
+    }
+
+    for k in 1..len - 1 {
+        distances[order[k]] += (value(order[k + 1]) - value(order[k - 1])) / (max - min);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crowding_distance, dominates, non_dominated_sort};
+
+    #[test]
+    fn test_dominates() {
+        assert!(dominates(&[2.0, 2.0], &[1.0, 2.0]));
+        assert!(!dominates(&[2.0, 2.0], &[2.0, 2.0]));
+        assert!(!dominates(&[3.0, 1.0], &[1.0, 3.0]));
+    }
+
+    #[test]
+    fn test_non_dominated_sort() {
+        let objectives = vec![
+            vec![1.0, 1.0],
+            vec![3.0, 1.0],
+            vec![2.0, 2.0],
+            vec![1.0, 3.0],
+            vec![0.0, 0.0],
+        ];
+
+        let fronts = non_dominated_sort(&objectives);
+
+        assert_eq!(fronts, vec![vec![1, 2, 3], vec![0], vec![4]]);
+    }
+
+    #[test]
+    fn test_crowding_distance() {
+        let objectives = vec![
+            vec![0.0, 4.0],
+            vec![1.0, 3.0],
+            vec![3.0, 1.0],
+            vec![4.0, 0.0],
+        ];
+
+        let distances = crowding_distance(&objectives, &[0, 1, 2, 3]);
+
+        assert_eq!(distances[0], f64::INFINITY);
+        assert_eq!(distances[3], f64::INFINITY);
+        assert_eq!(distances[1], 1.5);
+        assert_eq!(distances[2], 1.5);
+    }
+}
@@ -1,8 +1,11 @@
-use rand::{thread_rng, Rng};
-use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+use rand::Rng;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use serde_derive::Deserialize;
 use crate::{Individual, Mutation};
 
 use crate::population::Bin;
+use crate::context::Context;
+use crate::utils::check_probability;
 
 /// # Bit Swap Mutation
 ///
@@ -11,17 +14,20 @@ use crate::population::Bin;
 /// Example:
 /// ```rust
 /// use evolutionary::prelude::*;
+/// use rand::SeedableRng;
+///
 /// let mut population = vec![Bin::new(vec![true, false, true, false, true, false, true, false])];
 ///
 /// let mut mutation = BitFlipMutation {
 ///    mutation_rate: 1.0,
 /// };
 ///
-/// mutation.mutate(&mut population);
+/// mutation.mutate(&mut population, &mut Context::new(&mut EvolutionRng::seed_from_u64(0), Objective::Maximize));
 ///
 /// assert_eq!(*population[0].get_chromosome(), vec![false, true, false, true, false, true, false, true]);
 /// ```
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BitFlipMutation {
     pub mutation_rate: f64,
 }
@@ -35,16 +41,21 @@ impl Default for BitFlipMutation {
 }
 
 impl Mutation<Bin> for BitFlipMutation {
-    fn mutate(&self, population: &mut Vec<Bin>) {
-        population.par_iter_mut().for_each_init(
-            || thread_rng(),
-            |rng, member| {
-                for i in 0..member.get_chromosome().len() {
-                    if rng.gen_bool(self.mutation_rate) {
-                        member.set_gene(i, !member.get_gene(i));
-                    }
+    fn mutate(&self, population: &mut Vec<Bin>, context: &mut Context<Bin>) {
+        let streams = context.rng.streams();
+
+        population.par_iter_mut().enumerate().for_each(|(index, member)| {
+            let mut rng = streams.get(index);
+
+            for i in 0..member.get_chromosome().len() {
+                if rng.gen_bool(self.mutation_rate) {
+                    member.set_gene(i, !member.get_gene(i));
                 }
-            },
-        );
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)
     }
 }
@@ -1,8 +1,9 @@
 use crate::{Individual, Mutation};
+use crate::context::Context;
 
 #[derive(Clone)]
 pub struct DoNothingMutation;
 
 impl<T: Individual> Mutation<T> for DoNothingMutation {
-    fn mutate(&self, _population: &mut Vec<T>) {}
+    fn mutate(&self, _population: &mut Vec<T>, _context: &mut Context<T>) {}
 }
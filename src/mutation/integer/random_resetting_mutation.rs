@@ -1,21 +1,28 @@
-use rand::{thread_rng, Rng};
-use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+use rand::Rng;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 
 use crate::mutation::random_resetting_mutation::RandomResettingMutation;
 use crate::{population::Int, Individual, Mutation};
+use crate::context::Context;
+use crate::utils::check_probability;
 
 impl Mutation<Int> for RandomResettingMutation {
-    fn mutate(&self, population: &mut Vec<Int>) {
-        population.par_iter_mut().for_each_init(
-            || thread_rng(),
-            |rng, individual| {
-                for j in 0..individual.get_chromosome().len() {
-                    if rng.gen_bool(self.mutation_rate) {
-                        individual
-                            .set_gene(j, rng.gen_range(individual.range.0..individual.range.1));
-                    }
+    fn mutate(&self, population: &mut Vec<Int>, context: &mut Context<Int>) {
+        let streams = context.rng.streams();
+
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            for j in 0..individual.get_chromosome().len() {
+                if rng.gen_bool(self.mutation_rate) {
+                    let (min, max) = individual.range.get(j);
+                    individual.set_gene(j, rng.gen_range(min..=max));
                 }
-            },
-        );
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)
     }
 }
@@ -1,10 +1,12 @@
 use dyn_clone::DynClone;
 
+use crate::context::Context;
 use crate::population::Individual;
 
 mod real;
 mod binary;
 mod integer;
+mod tree;
 
 mod do_nothing_mutation;
 mod swap_mutation;
@@ -16,6 +18,7 @@ pub use real::*;
 pub use binary::*;
 pub use integer::*;
 pub use permuted::*;
+pub use tree::*;
 
 pub use do_nothing_mutation::*;
 pub use swap_mutation::*;
@@ -35,7 +38,7 @@ pub use random_resetting_mutation::*;
 /// struct AlwaysMutateMutation;
 ///
 /// impl Mutation<Bin> for AlwaysMutateMutation {
-///     fn mutate(&self, population: &mut Vec<Bin>) {
+///     fn mutate(&self, population: &mut Vec<Bin>, _context: &mut Context<Bin>) {
 ///         for individual in population.iter_mut() {
 ///             for i in 0..individual.get_chromosome().len() {
 ///                 individual.set_gene(i, !individual.get_gene(i));
@@ -46,6 +49,13 @@ pub use random_resetting_mutation::*;
 /// ```
 pub trait Mutation<T: Individual>: 'static + DynClone + Send + Sync {
     /// Method that performs the mutation. It receives a mutable reference to the population and
-    /// should modify it in place.
-    fn mutate(&self, population: &mut Vec<T>);
+    /// should modify it in place. All the randomness must come from `context.rng` so the evolution
+    /// can be reproduced from its seed.
+    fn mutate(&self, population: &mut Vec<T>, context: &mut Context<T>);
+
+    /// Checks the parameters of the mutation, like its rate. It's called when the evolution is
+    /// built, so an invalid parameter is reported instead of panicking during the run.
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
 }
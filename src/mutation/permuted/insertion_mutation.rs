@@ -1,14 +1,17 @@
-use rand::{Rng, thread_rng};
-use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use rand::Rng;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use serde_derive::Deserialize;
 use crate::{Individual, Mutation};
-use crate::population::IntPerm;
+use crate::{context::Context, population::IntPerm};
+use crate::utils::check_probability;
 
 /// # Insertion Mutation
 ///
 /// For each individual in the population it has `mutation_rate` probability of removing a random
 /// gene from one place and inserting it back in other random position. If the gene is inserted in
 /// the same position, nothing happens.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct InsertionMutation {
     pub mutation_rate: f64,
 }
@@ -29,20 +32,26 @@ impl InsertionMutation {
 }
 
 impl Mutation<IntPerm> for InsertionMutation {
-    fn mutate(&self, population: &mut Vec<IntPerm>) {
-        population.par_iter_mut().for_each_init(
-            || thread_rng(),
-            |rng, individual| {
-                if rng.gen_bool(self.mutation_rate) {
-                    let from = rng.gen_range(0..individual.chromosome.len());
-                    let to = rng.gen_range(0..individual.chromosome.len());
-
-                    if from != to {
-                        Self::insert(&mut individual.get_mut_chromosome(), from, to);
-                    }
+    fn mutate(&self, population: &mut Vec<IntPerm>, context: &mut Context<IntPerm>) {
+        let streams = context.rng.streams();
+
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            if rng.gen_bool(self.mutation_rate) {
+                let from = rng.gen_range(0..individual.chromosome.len());
+                let to = rng.gen_range(0..individual.chromosome.len());
+
+                if from != to {
+                    Self::insert(&mut individual.get_mut_chromosome(), from, to);
                 }
+            }
         });
     }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)
+    }
 }
 
 #[cfg(test)]
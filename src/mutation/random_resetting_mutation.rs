@@ -1,10 +1,13 @@
+use serde_derive::Deserialize;
+
 // Random Resetting mutation is implemented for Real and Int chromosome types.
 
 /// # Substitute Mutation
 ///
 /// For each gene in the real representation it has `mutation_rate` probability of replacing
 /// the gene with a random value within the range.
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RandomResettingMutation {
     pub mutation_rate: f64,
 }
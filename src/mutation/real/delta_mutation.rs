@@ -1,9 +1,13 @@
-use rand::{thread_rng, Rng};
-use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+use rand::Rng;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use serde_derive::Deserialize;
 
-use crate::{population::Real, Mutation};
+use crate::{population::Real, rng::EvolutionRng, Mutation};
+use crate::context::Context;
+use crate::utils::check_probability;
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct DeltaMutation {
     pub mutation_rate: f64,
     pub frac: f64,
@@ -19,31 +23,43 @@ impl Default for DeltaMutation {
 }
 
 impl Mutation<Real> for DeltaMutation {
-    fn mutate(&self, population: &mut Vec<Real>) {
-        population.par_iter_mut().for_each_init(
-            || thread_rng(),
-            |rng, individual| {
-                for j in 0..individual.chromosome.len() {
-                    if rng.gen_bool(self.mutation_rate) {
-                        let delta =
-                            rng.gen_range(individual.range.0..individual.range.1) / self.frac;
-
-                        let result = if rng.gen_bool(0.5) {
-                            individual.chromosome[j] - delta
-                        } else {
-                            individual.chromosome[j] + delta
-                        };
+    fn mutate(&self, population: &mut Vec<Real>, context: &mut Context<Real>) {
+        let streams = context.rng.streams();
+        let boundary_handler = context.boundary_handler;
+
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            for j in 0..individual.chromosome.len() {
+                if rng.gen_bool(self.mutation_rate) {
+                    let bounds = individual.range.get(j);
+                    let gene = individual.chromosome[j];
 
-                        if result < individual.range.0 {
-                            individual.chromosome[j] = individual.range.0;
-                        } else if result > individual.range.1 {
-                            individual.chromosome[j] = individual.range.1;
+                    let delta_mutate = |rng: &mut EvolutionRng| {
+                        let delta = rng.gen_range(bounds.0..=bounds.1) / self.frac;
+
+                        if rng.gen_bool(0.5) {
+                            gene - delta
                         } else {
-                            individual.chromosome[j] = result;
+                            gene + delta
                         }
-                    }
+                    };
+
+                    let result = delta_mutate(&mut rng);
+                    individual.chromosome[j] =
+                        boundary_handler.repair(result, bounds, &mut rng, delta_mutate);
                 }
-            },
-        );
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)?;
+
+        if !(self.frac > 0.0 && self.frac.is_finite()) {
+            return Err(format!("The delta fraction must be positive, got {}", self.frac));
+        }
+
+        Ok(())
     }
 }
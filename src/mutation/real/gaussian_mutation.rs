@@ -1,15 +1,21 @@
+use serde_derive::Deserialize;
 use crate::prelude::Real;
+use crate::rng::EvolutionRng;
 use crate::{Individual, Mutation};
-use rand::{thread_rng, Rng};
+use crate::context::Context;
+use rand::Rng;
 use rand_distr::Normal;
-use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use crate::utils::check_probability;
 
 /// # Gaussian Mutation
 ///
 /// For each gene in each individual in the population it has `mutation_rate` probability of
 /// mutating the gene. The mutation is done by adding a random number from a Normal (Gaussian)
-/// distribution with mean 0 and standard deviation `sigma`.
-#[derive(Clone)]
+/// distribution with mean 0 and standard deviation `sigma`, with the genes out of their bounds
+/// handled by the boundary handler of the [`Context`].
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct GaussianMutation {
     pub mutation_rate: f64,
     pub sigma: f64,
@@ -25,21 +31,37 @@ impl Default for GaussianMutation {
 }
 
 impl Mutation<Real> for GaussianMutation {
-    fn mutate(&self, population: &mut Vec<Real>) {
+    fn mutate(&self, population: &mut Vec<Real>, context: &mut Context<Real>) {
         let gaussian_distribution = Normal::new(0.0, self.sigma).unwrap();
 
-        population.par_iter_mut().for_each_init(
-            || thread_rng(),
-            |rng, individual| {
-                for j in 0..individual.get_chromosome().len() {
-                    if rng.gen_bool(self.mutation_rate) {
-                        individual.set_gene(
-                            j,
-                            individual.get_gene(j) + rng.sample(gaussian_distribution),
-                        );
-                    }
+        let streams = context.rng.streams();
+        let boundary_handler = context.boundary_handler;
+
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            for j in 0..individual.get_chromosome().len() {
+                if rng.gen_bool(self.mutation_rate) {
+                    let gene = individual.get_gene(j);
+                    let mutate = |rng: &mut EvolutionRng| gene + rng.sample(gaussian_distribution);
+
+                    let mutated = mutate(&mut rng);
+                    let bounds = individual.range.get(j);
+
+                    let gene = boundary_handler.repair(mutated, bounds, &mut rng, mutate);
+                    individual.set_gene(j, gene);
                 }
-            },
-        );
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)?;
+
+        if !(self.sigma >= 0.0 && self.sigma.is_finite()) {
+            return Err(format!("The standard deviation must be non-negative, got {}", self.sigma));
+        }
+
+        Ok(())
     }
 }
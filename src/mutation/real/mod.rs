@@ -4,3 +4,4 @@ mod gaussian_mutation;
 
 pub use delta_mutation::*;
 pub use random_resetting_mutation::*;
+pub use gaussian_mutation::*;
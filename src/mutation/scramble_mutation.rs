@@ -1,7 +1,9 @@
-use rand::{Rng, thread_rng};
+use rand::Rng;
 use rand::seq::SliceRandom;
-use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 use crate::{Individual, Mutation};
+use crate::context::Context;
+use crate::utils::check_probability;
 
 /// # Scramble Mutation
 ///
@@ -21,19 +23,24 @@ impl Default for ScrambleMutation {
 }
 
 impl<T: Individual> Mutation<T> for ScrambleMutation {
-    fn mutate(&self, population: &mut Vec<T>) {
-        population.par_iter_mut().for_each_init(
-            || thread_rng(),
-            |rng, individual| {
-                if rng.gen_bool(self.mutation_rate) {
-                    let from = rng.gen_range(0..individual.get_chromosome().len());
-                    let to = rng.gen_range(from..individual.get_chromosome().len());
+    fn mutate(&self, population: &mut Vec<T>, context: &mut Context<T>) {
+        let streams = context.rng.streams();
 
-                    if from != to {
-                        individual.get_mut_chromosome()[from..=to].shuffle(rng);
-                    }
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            if rng.gen_bool(self.mutation_rate) {
+                let from = rng.gen_range(0..individual.get_chromosome().len());
+                let to = rng.gen_range(from..individual.get_chromosome().len());
+
+                if from != to {
+                    individual.get_mut_chromosome()[from..=to].shuffle(&mut rng);
                 }
             }
-        );
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)
     }
 }
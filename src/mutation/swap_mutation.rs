@@ -1,9 +1,13 @@
-use rand::{thread_rng, Rng};
-use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+use rand::Rng;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use serde_derive::Deserialize;
 
 use crate::{Individual, Mutation};
+use crate::context::Context;
+use crate::utils::check_probability;
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SwapMutation {
     pub mutation_rate: f64,
 }
@@ -23,20 +27,25 @@ impl Default for SwapMutation {
 }
 
 impl<T: Individual> Mutation<T> for SwapMutation {
-    fn mutate(&self, population: &mut Vec<T>) {
-        population.par_iter_mut().for_each_init(
-            || thread_rng(),
-            |rng, individual| {
-                for j in 0..individual.get_chromosome().len() {
-                    if rng.gen_bool(self.mutation_rate) {
-                        let swap_with = rng.gen_range(0..individual.get_chromosome().len());
-
-                        let temp = individual.get_gene(j);
-                        individual.set_gene(j, individual.get_gene(swap_with));
-                        individual.set_gene(swap_with, temp);
-                    }
+    fn mutate(&self, population: &mut Vec<T>, context: &mut Context<T>) {
+        let streams = context.rng.streams();
+
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            for j in 0..individual.get_chromosome().len() {
+                if rng.gen_bool(self.mutation_rate) {
+                    let swap_with = rng.gen_range(0..individual.get_chromosome().len());
+
+                    let temp = individual.get_gene(j);
+                    individual.set_gene(j, individual.get_gene(swap_with));
+                    individual.set_gene(swap_with, temp);
                 }
-            },
-        );
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)
     }
 }
@@ -0,0 +1,121 @@
+use rand::Rng;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::context::Context;
+use crate::population::{Tree, TreeValue};
+use crate::Mutation;
+use crate::utils::check_probability;
+
+/// # Hoist Mutation
+///
+/// With the `mutation_rate` probability, picks a random subtree of the individual and replaces it
+/// with one of its own subtrees, chosen at random. The tree can only shrink, so it's used to fight
+/// the bloat.
+#[derive(Clone)]
+pub struct HoistMutation {
+    pub mutation_rate: f64,
+}
+
+impl Default for HoistMutation {
+    fn default() -> Self {
+        Self {
+            mutation_rate: 0.05,
+        }
+    }
+}
+
+impl<V: TreeValue> Mutation<Tree<V>> for HoistMutation {
+    fn mutate(&self, population: &mut Vec<Tree<V>>, context: &mut Context<Tree<V>>) {
+        let streams = context.rng.streams();
+
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            if !rng.gen_bool(self.mutation_rate) {
+                return;
+            }
+
+            let outer = individual.subtree(rng.gen_range(0..individual.size()));
+            let inner = individual.subtree(rng.gen_range(outer.clone()));
+
+            let hoisted = individual.nodes()[inner].to_vec();
+            individual.replace_subtree(outer, &hoisted);
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    fn primitives() -> Arc<PrimitiveSet<f64>> {
+        Arc::new(
+            PrimitiveSet::<f64>::new()
+                .with_function("add", 2, |args| args[0] + args[1])
+                .with_function("mul", 2, |args| args[0] * args[1])
+                .with_function("neg", 1, |args| -args[0])
+                .with_variable("x")
+                .with_constant(1.0),
+        )
+    }
+
+    fn population(rng: &mut EvolutionRng) -> Vec<Tree<f64>> {
+        let config = TreeConfig::new(primitives());
+
+        (0..50)
+            .map(|_| Tree::generate_member(5, &config, rng))
+            .collect()
+    }
+
+    fn assert_well_formed(population: &[Tree<f64>]) {
+        for tree in population {
+            assert_eq!(tree.subtree(0), 0..tree.size());
+        }
+    }
+
+    #[test]
+    fn tree_mutations_keep_the_trees_well_formed() {
+        let mut rng = EvolutionRng::seed_from_u64(1);
+        let original = population(&mut rng);
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        let mut hoisted = original.clone();
+        HoistMutation { mutation_rate: 1.0 }.mutate(&mut hoisted, &mut context);
+        assert_well_formed(&hoisted);
+        assert!(hoisted.iter().zip(&original).all(|(h, o)| h.size() <= o.size()));
+
+        let mut pointed = original.clone();
+        PointMutation {
+            mutation_rate: 1.0,
+            primitives: primitives(),
+        }
+        .mutate(&mut pointed, &mut context);
+        assert_well_formed(&pointed);
+        assert!(pointed.iter().zip(&original).all(|(p, o)| p.size() == o.size()));
+
+        let limits = TreeLimits {
+            max_depth: 6,
+            max_size: 40,
+        };
+        let mut grown = original.clone();
+        SubtreeMutation {
+            mutation_rate: 1.0,
+            limits,
+            ..SubtreeMutation::new(primitives())
+        }
+        .mutate(&mut grown, &mut context);
+        assert_well_formed(&grown);
+        assert!(grown
+            .iter()
+            .zip(&original)
+            .all(|(g, o)| limits.allows(g) || g.get_chromosome() == o.get_chromosome()));
+    }
+}
@@ -0,0 +1,7 @@
+mod hoist_mutation;
+mod point_mutation;
+mod subtree_mutation;
+
+pub use hoist_mutation::*;
+pub use point_mutation::*;
+pub use subtree_mutation::*;
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use rand::Rng;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::context::Context;
+use crate::population::{Node, PrimitiveSet, Tree, TreeValue};
+use crate::{Individual, Mutation};
+use crate::utils::check_probability;
+
+/// # Point Mutation
+///
+/// Each node has the `mutation_rate` probability of being replaced by another primitive of the
+/// same arity: a function by a function with as many arguments, if there is one, and a terminal
+/// by a random terminal. The shape of the tree never changes.
+#[derive(Clone)]
+pub struct PointMutation<V> {
+    pub mutation_rate: f64,
+    pub primitives: Arc<PrimitiveSet<V>>,
+}
+
+impl<V> PointMutation<V> {
+    /// Creates the mutation with a `mutation_rate` of 0.05.
+    pub fn new(primitives: Arc<PrimitiveSet<V>>) -> Self {
+        Self {
+            mutation_rate: 0.05,
+            primitives,
+        }
+    }
+}
+
+impl<V: TreeValue> Mutation<Tree<V>> for PointMutation<V> {
+    fn mutate(&self, population: &mut Vec<Tree<V>>, context: &mut Context<Tree<V>>) {
+        let streams = context.rng.streams();
+
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            for j in 0..individual.size() {
+                if !rng.gen_bool(self.mutation_rate) {
+                    continue;
+                }
+
+                let replacement = match individual.get_gene(j) {
+                    Node::Function { arity, .. } => {
+                        self.primitives.random_function_with_arity(arity, &mut rng)
+                    }
+                    _ => Some(self.primitives.random_terminal(&mut rng)),
+                };
+
+                if let Some(node) = replacement {
+                    individual.set_gene(j, node);
+                }
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)
+    }
+}
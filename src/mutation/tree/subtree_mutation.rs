@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use rand::Rng;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::context::Context;
+use crate::population::{PrimitiveSet, Tree, TreeLimits, TreeValue};
+use crate::Mutation;
+use crate::utils::check_probability;
+
+/// # Subtree Mutation
+///
+/// With the `mutation_rate` probability, replaces a random subtree of the individual with a new
+/// one, generated with the grow method from the `primitives` and up to `max_depth` deep. A mutated
+/// tree that exceeds the `limits` is discarded and the original is kept.
+#[derive(Clone)]
+pub struct SubtreeMutation<V> {
+    pub mutation_rate: f64,
+    pub primitives: Arc<PrimitiveSet<V>>,
+    pub max_depth: usize,
+    pub limits: TreeLimits,
+}
+
+impl<V> SubtreeMutation<V> {
+    /// Creates the mutation with a `mutation_rate` of 0.1, a `max_depth` of 4 and the default
+    /// limits.
+    pub fn new(primitives: Arc<PrimitiveSet<V>>) -> Self {
+        Self {
+            mutation_rate: 0.1,
+            primitives,
+            max_depth: 4,
+            limits: TreeLimits::default(),
+        }
+    }
+}
+
+impl<V: TreeValue> Mutation<Tree<V>> for SubtreeMutation<V> {
+    fn mutate(&self, population: &mut Vec<Tree<V>>, context: &mut Context<Tree<V>>) {
+        let streams = context.rng.streams();
+
+        population.par_iter_mut().enumerate().for_each(|(index, individual)| {
+            let mut rng = streams.get(index);
+
+            if !rng.gen_bool(self.mutation_rate) {
+                return;
+            }
+
+            let range = individual.subtree(rng.gen_range(0..individual.size()));
+            let subtree = self.primitives.grow(self.max_depth, &mut rng);
+
+            let mut mutated = individual.clone();
+            mutated.replace_subtree(range, &subtree);
+
+            if self.limits.allows(&mutated) {
+                *individual = mutated;
+            }
+        });
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_probability("mutation rate", self.mutation_rate)
+    }
+}
@@ -0,0 +1,84 @@
+use std::cmp::Ordering;
+
+use serde_derive::{Deserialize, Serialize};
+
+/// # Objective
+///
+/// The direction of the optimization. With [`Objective::Maximize`], the default, the larger the
+/// fitness the better the individual. With [`Objective::Minimize`] the smaller the better, so a
+/// cost can be used directly as the fitness instead of subtracting it from a known worst value.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// assert!(Objective::Minimize.is_better(1.0, 2.0));
+/// assert!(Objective::Maximize.is_better(2.0, 1.0));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Objective {
+    #[default]
+    Maximize,
+    Minimize,
+}
+
+impl Objective {
+    /// Compares two fitness values by how good they are: returns `Ordering::Greater` when `a` is
+    /// better than `b`. Sorting with it orders the individuals from the worst to the best.
+    pub fn compare(self, a: f64, b: f64) -> Ordering {
+        self.oriented(a)
+            .partial_cmp(&self.oriented(b))
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Returns if the fitness `a` is strictly better than `b`.
+    pub fn is_better(self, a: f64, b: f64) -> bool {
+        self.compare(a, b) == Ordering::Greater
+    }
+
+    /// Returns the fitness as a value to maximize, negating it when minimizing.
+    pub fn oriented(self, fitness: f64) -> f64 {
+        match self {
+            Objective::Maximize => fitness,
+            Objective::Minimize => -fitness,
+        }
+    }
+
+    /// Returns the best of the fitness values, if any.
+    pub fn best(self, fitnesses: impl IntoIterator<Item = f64>) -> Option<f64> {
+        fitnesses
+            .into_iter()
+            .reduce(|best, fitness| if self.is_better(fitness, best) { fitness } else { best })
+    }
+
+    /// Returns the worst of the fitness values, if any.
+    pub fn worst(self, fitnesses: impl IntoIterator<Item = f64>) -> Option<f64> {
+        fitnesses
+            .into_iter()
+            .reduce(|worst, fitness| if self.is_better(worst, fitness) { fitness } else { worst })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::Objective;
+
+    #[test]
+    fn compares_by_direction() {
+        assert_eq!(Objective::Maximize.compare(2.0, 1.0), Ordering::Greater);
+        assert_eq!(Objective::Minimize.compare(2.0, 1.0), Ordering::Less);
+        assert_eq!(Objective::Minimize.compare(1.0, 1.0), Ordering::Equal);
+    }
+
+    #[test]
+    fn finds_the_best() {
+        let fitnesses = [3.0, -1.0, 7.0];
+
+        assert_eq!(Objective::Maximize.best(fitnesses), Some(7.0));
+        assert_eq!(Objective::Minimize.best(fitnesses), Some(-1.0));
+        assert_eq!(Objective::Minimize.best([]), None);
+    }
+}
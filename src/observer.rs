@@ -0,0 +1,67 @@
+use std::error::Error;
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use dyn_clone::DynClone;
+
+use crate::{evolution::Evolution, population::Individual};
+
+/// Trait to watch an [`Evolution`] while it runs. Every hook has an empty default implementation,
+/// so implement only the ones you need and register the observer with
+/// [`EvolutionBuilder::with_observer`](crate::EvolutionBuilder::with_observer).
+///
+/// The observers are cloned when the evolution is built, so to read the collected data after the
+/// run keep it behind an `Arc`.
+///
+/// Example:
+///
+/// ```
+/// use std::ops::ControlFlow;
+/// use evolutionary::prelude::*;
+///
+/// #[derive(Clone)]
+/// struct Progress;
+///
+/// impl EvolutionObserver<Bin> for Progress {
+///     fn on_generation(&mut self, evolution: &Evolution<Bin>) -> ControlFlow<()> {
+///         println!("{}: {}", evolution.metrics.iterations, evolution.current_best_fitness());
+///
+///         // Aborts the run when the best fitness is good enough.
+///         if evolution.current_best_fitness() >= 10.0 {
+///             ControlFlow::Break(())
+///         } else {
+///             ControlFlow::Continue(())
+///         }
+///     }
+/// }
+/// ```
+pub trait EvolutionObserver<T: Individual>: 'static + DynClone + Send + Sync {
+    /// Called by [`Evolution::start`] after the initial population is generated and evaluated.
+    fn on_start(&mut self, _evolution: &Evolution<T>) {}
+
+    /// Called at the end of every [`Evolution::next`]. Returning `ControlFlow::Break` stops the
+    /// evolution, as if the stop condition was met.
+    fn on_generation(&mut self, _evolution: &Evolution<T>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called when a generation finds an individual better than any of the previous generations,
+    /// including the initial population, before `on_start` or `on_generation`.
+    fn on_new_best(&mut self, _evolution: &Evolution<T>, _best: &T) {}
+
+    /// Called when [`Evolution::run`] or [`Evolution::continue_run`] finishes.
+    fn on_finish(&mut self, _evolution: &Evolution<T>) {}
+
+    /// Called when a periodic checkpoint couldn't be written to `path`, see
+    /// [`EvolutionBuilder::with_checkpoint_every`](crate::EvolutionBuilder::with_checkpoint_every).
+    /// The evolution carries on, and tries again in the next period, unless it returns
+    /// `ControlFlow::Break`.
+    fn on_checkpoint_error(
+        &mut self,
+        _evolution: &Evolution<T>,
+        _path: &Path,
+        _error: &dyn Error,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,585 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::path::Path;
+
+use rand::seq::{index::sample, SliceRandom};
+use rand::{Rng, SeedableRng};
+
+use crate::experiment_suite::{ConfigurationSummary, ExperimentSuite};
+use crate::export::{csv_cell, write_csv};
+use crate::{
+    evolution_builder::EvolutionBuilder, objective::Objective, population::Individual,
+    rng::EvolutionRng,
+};
+
+/// A value a parameter of a [`ParameterSpace`] takes: a number, as a population size or a
+/// mutation rate, or the name of a choice, as an operator.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParameterValue {
+    Number(f64),
+    Choice(String),
+}
+
+impl From<f64> for ParameterValue {
+    fn from(value: f64) -> Self {
+        ParameterValue::Number(value)
+    }
+}
+
+impl From<u32> for ParameterValue {
+    fn from(value: u32) -> Self {
+        ParameterValue::Number(value as f64)
+    }
+}
+
+impl From<usize> for ParameterValue {
+    fn from(value: usize) -> Self {
+        ParameterValue::Number(value as f64)
+    }
+}
+
+impl From<&str> for ParameterValue {
+    fn from(value: &str) -> Self {
+        ParameterValue::Choice(value.to_string())
+    }
+}
+
+impl Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterValue::Number(value) => write!(f, "{}", value),
+            ParameterValue::Choice(choice) => write!(f, "{}", choice),
+        }
+    }
+}
+
+/// One point of a [`ParameterSpace`]: a value for each of its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameters {
+    values: Vec<(String, ParameterValue)>,
+}
+
+impl Parameters {
+    pub fn get(&self, name: &str) -> Option<&ParameterValue> {
+        self.values
+            .iter()
+            .find(|(parameter, _)| parameter == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The value of the numeric parameter `name`, if there's one.
+    pub fn number(&self, name: &str) -> Option<f64> {
+        match self.get(name) {
+            Some(ParameterValue::Number(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of the choice parameter `name`, if there's one.
+    pub fn choice(&self, name: &str) -> Option<&str> {
+        match self.get(name) {
+            Some(ParameterValue::Choice(choice)) => Some(choice),
+            _ => None,
+        }
+    }
+
+    pub fn values(&self) -> &[(String, ParameterValue)] {
+        &self.values
+    }
+}
+
+impl Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> =
+            self.values.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+
+        write!(f, "{}", values.join(", "))
+    }
+}
+
+/// # Parameter Space
+///
+/// The values to try for each parameter of an evolution. Its points are the Cartesian product of
+/// the values, see [`ParameterSpace::grid`], and a [`ParameterSweep`] runs all or a random
+/// sample of them.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// let space = ParameterSpace::new()
+///     .with_values("population_size", [50u32, 100, 200])
+///     .with_values("mutation_rate", [0.01, 0.05])
+///     .with_values("crossover", ["pmx", "cycle"]);
+///
+/// assert_eq!(space.len(), 12);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParameterSpace {
+    parameters: Vec<(String, Vec<ParameterValue>)>,
+}
+
+impl ParameterSpace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_values<V: Into<ParameterValue>>(
+        mut self,
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.parameters
+            .push((name.into(), values.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// The number of points of the space, 0 when it has no parameters.
+    pub fn len(&self) -> usize {
+        if self.parameters.is_empty() {
+            return 0;
+        }
+
+        self.parameters.iter().map(|(_, values)| values.len()).product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks that the space has parameters, and that every parameter has values.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        if self.parameters.is_empty() {
+            return Err("The parameter space has no parameters".to_string());
+        }
+
+        match self.parameters.iter().find(|(_, values)| values.is_empty()) {
+            Some((name, _)) => Err(format!("The parameter <{}> has no values", name)),
+            None => Ok(()),
+        }
+    }
+
+    /// Every point of the space, varying the last parameter first.
+    pub fn grid(&self) -> Vec<Parameters> {
+        (0..self.len()).map(|index| self.point(index)).collect()
+    }
+
+    /// `samples` different points of the space drawn at random, or all of them when there are
+    /// not that many.
+    pub fn sample(&self, samples: usize, rng: &mut EvolutionRng) -> Vec<Parameters> {
+        let len = self.len();
+
+        sample(rng, len, samples.min(len))
+            .into_iter()
+            .map(|index| self.point(index))
+            .collect()
+    }
+
+    /// A point near the `elite`: each parameter keeps its value with probability `keep`, and
+    /// otherwise takes one of its values at random.
+    pub(crate) fn neighbour(
+        &self,
+        elite: &Parameters,
+        keep: f64,
+        rng: &mut EvolutionRng,
+    ) -> Parameters {
+        let values = self
+            .parameters
+            .iter()
+            .map(|(name, values)| match elite.get(name) {
+                Some(value) if rng.gen_bool(keep) => (name.clone(), value.clone()),
+                _ => (name.clone(), values.choose(rng).unwrap().clone()),
+            })
+            .collect();
+
+        Parameters { values }
+    }
+
+    /// The point at `index` of the grid, decoding it digit by digit with the number of values of
+    /// each parameter as the base.
+    fn point(&self, mut index: usize) -> Parameters {
+        let mut values: Vec<(String, ParameterValue)> = self
+            .parameters
+            .iter()
+            .rev()
+            .map(|(name, values)| {
+                let value = values[index % values.len()].clone();
+                index /= values.len();
+                (name.clone(), value)
+            })
+            .collect();
+        values.reverse();
+
+        Parameters { values }
+    }
+}
+
+/// A statistic of the best fitness found by the runs of each point, to rank the points of a
+/// [`ParameterSweep`]. The mean and the median rank by the objective, the standard deviation and
+/// the evaluations to the target from the lowest and the success rate from the highest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Statistic {
+    #[default]
+    Mean,
+    Median,
+    StdDev,
+    SuccessRate,
+    EvaluationsToTarget,
+}
+
+/// # Parameter Sweep
+///
+/// Runs the evolution at every point of a [`ParameterSpace`], or at a random sample of them, each
+/// `runs` times, and ranks the points by a [`Statistic`] of the best fitness they found. The
+/// evolution of each point is built by a closure that receives its [`Parameters`].
+///
+/// # Example
+///
+/// ```no_run
+/// use evolutionary::prelude::*;
+/// # #[derive(Clone)]
+/// # struct YourFitness;
+/// # impl Fitness<IntPerm> for YourFitness {
+/// #    fn calculate_fitness(&self, individual: &IntPerm) -> f64 { 0.0 }
+/// # }
+///
+/// let space = ParameterSpace::new()
+///     .with_values("population_size", [50u32, 100, 200])
+///     .with_values("mutation_rate", [0.01, 0.05])
+///     .with_values("tournament_size", [2u32, 4])
+///     .with_values("crossover", ["pmx", "cycle"]);
+///
+/// let table = ParameterSweep::new(space, 10)
+///     .with_target(0.0)
+///     .with_ranking(Statistic::SuccessRate)
+///     .run(|parameters| {
+///         let builder = EvolutionBuilder::new(
+///             parameters.number("population_size").unwrap() as u32,
+///             8,
+///             GeneCod::IntPerm,
+///             (),
+///         )
+///         .with_fitness(YourFitness)
+///         .with_selection(TournamentSelection::new(
+///             parameters.number("tournament_size").unwrap() as usize,
+///             1.0,
+///         ))
+///         .with_mutation(SwapMutation::new(parameters.number("mutation_rate").unwrap()))
+///         .with_stop_condition(|_, iterations, _| iterations >= 500);
+///
+///         match parameters.choice("crossover") {
+///             Some("pmx") => builder.with_crossover(PartiallyMappedCrossover::default()),
+///             _ => builder.with_crossover(CycleCrossover::default()),
+///         }
+///     })
+///     .unwrap();
+///
+/// println!("{}", table);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterSweep {
+    space: ParameterSpace,
+    runs: u32,
+    samples: Option<usize>,
+    seed: Option<u64>,
+    target: Option<f64>,
+    ranking: Statistic,
+}
+
+/// The summary of the runs at one point of a [`ParameterSweep`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepResult {
+    pub parameters: Parameters,
+    pub summary: ConfigurationSummary,
+}
+
+/// The results of a [`ParameterSweep`], from the best ranked point to the worst.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepTable {
+    pub ranking: Statistic,
+    pub rows: Vec<SweepResult>,
+}
+
+impl ParameterSweep {
+    pub fn new(space: ParameterSpace, runs: u32) -> Self {
+        Self {
+            space,
+            runs,
+            samples: None,
+            seed: None,
+            target: None,
+            ranking: Statistic::default(),
+        }
+    }
+
+    /// Runs only `samples` points of the space drawn at random, instead of all of them.
+    pub fn with_random_sample(mut self, samples: usize) -> Self {
+        self.samples = Some(samples);
+        self
+    }
+
+    /// Seeds the random sample of the points.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the fitness a run should reach to succeed, see
+    /// [`ExperimentSuite::with_target`].
+    pub fn with_target(mut self, target: f64) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Sets the statistic the points are ranked by. Defaults to the mean.
+    pub fn with_ranking(mut self, ranking: Statistic) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// The points of the space the sweep runs.
+    pub fn points(&self) -> Vec<Parameters> {
+        match self.samples {
+            Some(samples) => {
+                let mut rng =
+                    self.seed.map_or_else(EvolutionRng::from_entropy, EvolutionRng::seed_from_u64);
+                self.space.sample(samples, &mut rng)
+            }
+            None => self.space.grid(),
+        }
+    }
+
+    /// Runs the evolutions built by `build` at each point, one point after the other and its
+    /// runs in parallel, and ranks the points. Fails when the space has no points, when the
+    /// builders don't share the objective, as the points are ranked by it, or when an evolution
+    /// can't be built or fails to run.
+    pub fn run<T, F>(&self, build: F) -> Result<SweepTable, String>
+    where
+        T: Individual,
+        F: Fn(&Parameters) -> EvolutionBuilder<T>,
+    {
+        self.space.check()?;
+
+        let points = self.points();
+        let builders: Vec<EvolutionBuilder<T>> = points.iter().map(build).collect();
+
+        let objective = builders.first().map(EvolutionBuilder::objective).unwrap_or_default();
+        if builders.iter().any(|builder| builder.objective() != objective) {
+            return Err("The evolutions of a sweep should share the objective".to_string());
+        }
+
+        let mut suite = ExperimentSuite::new("sweep", self.runs);
+        for (parameters, builder) in points.iter().zip(builders) {
+            suite = suite.with_configuration(parameters.to_string(), builder);
+        }
+        if let Some(target) = self.target {
+            suite = suite.with_target(target);
+        }
+
+        suite.run_configurations()?;
+
+        let mut rows: Vec<SweepResult> = points
+            .into_iter()
+            .zip(suite.summaries())
+            .map(|(parameters, summary)| SweepResult { parameters, summary })
+            .collect();
+
+        rows.sort_by(|a, b| self.compare(&a.summary, &b.summary, objective));
+
+        Ok(SweepTable { ranking: self.ranking, rows })
+    }
+
+    /// Orders the summaries from the best to the worst by the ranking statistic. The missing
+    /// values, as the evaluations of a point that never reached the target, go last.
+    fn compare(
+        &self,
+        a: &ConfigurationSummary,
+        b: &ConfigurationSummary,
+        objective: Objective,
+    ) -> Ordering {
+        let missing_last = |a: Option<f64>, b: Option<f64>, order: fn(&f64, &f64) -> Ordering| {
+            match (a, b) {
+                (Some(a), Some(b)) => order(&a, &b),
+                (a, b) => b.is_none().cmp(&a.is_none()),
+            }
+        };
+
+        match self.ranking {
+            Statistic::Mean => objective.compare(b.mean, a.mean),
+            Statistic::Median => objective.compare(b.median, a.median),
+            Statistic::StdDev => a.std_dev.total_cmp(&b.std_dev),
+            Statistic::SuccessRate => {
+                missing_last(a.success_rate, b.success_rate, |a, b| b.total_cmp(a))
+            }
+            Statistic::EvaluationsToTarget => missing_last(
+                a.mean_evaluations_to_target,
+                b.mean_evaluations_to_target,
+                f64::total_cmp,
+            ),
+        }
+    }
+}
+
+impl SweepTable {
+    /// Writes the table to `path` as CSV, one row per point with its rank, the value of each
+    /// parameter and the statistics of its runs.
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let mut header = vec!["rank".to_string()];
+        if let Some(row) = self.rows.first() {
+            header.extend(row.parameters.values().iter().map(|(name, _)| name.clone()));
+        }
+        header.extend(
+            ["runs", "mean", "median", "std_dev", "success_rate", "mean_evaluations_to_target"]
+                .map(String::from),
+        );
+
+        let rows = self.rows.iter().enumerate().map(|(rank, row)| {
+            let summary = &row.summary;
+
+            let mut fields = vec![(rank + 1).to_string()];
+            fields.extend(row.parameters.values().iter().map(|(_, value)| value.to_string()));
+            fields.extend([
+                summary.runs.to_string(),
+                summary.mean.to_string(),
+                summary.median.to_string(),
+                summary.std_dev.to_string(),
+                csv_cell(summary.success_rate),
+                csv_cell(summary.mean_evaluations_to_target),
+            ]);
+
+            fields
+        });
+
+        write_csv(path.as_ref(), &header, rows)
+    }
+}
+
+impl Display for SweepTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Ranked by {:?}", self.ranking)?;
+
+        for (rank, row) in self.rows.iter().enumerate() {
+            let summary = &row.summary;
+            write!(
+                f,
+                "{}. {}: mean {:.4} ± {:.4}, median {:.4}",
+                rank + 1,
+                row.parameters,
+                summary.mean,
+                summary.std_dev,
+                summary.median
+            )?;
+
+            if let Some(success_rate) = summary.success_rate {
+                write!(f, ", success rate {:.1}%", success_rate * 100.0)?;
+            }
+            if let Some(evaluations) = summary.mean_evaluations_to_target {
+                write!(f, ", mean evaluations to target {:.0}", evaluations)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::ones_builder;
+
+    fn space() -> ParameterSpace {
+        ParameterSpace::new()
+            .with_values("population_size", [10u32, 20])
+            .with_values("mutation", ["none", "bit flip"])
+    }
+
+    #[test]
+    fn enumerates_the_points() {
+        let grid = space().grid();
+
+        assert_eq!(grid.len(), 4);
+        assert_eq!(grid[0].to_string(), "population_size=10, mutation=none");
+        assert_eq!(grid[1].to_string(), "population_size=10, mutation=bit flip");
+        assert_eq!(grid[3].number("population_size"), Some(20.0));
+        assert_eq!(grid[3].choice("mutation"), Some("bit flip"));
+        assert_eq!(grid[3].number("mutation"), None);
+        assert_eq!(grid[3].choice("elitism"), None);
+
+        assert!(ParameterSpace::new().is_empty());
+        assert!(ParameterSpace::new().grid().is_empty());
+        assert!(space().with_values("elitism", Vec::<u32>::new()).is_empty());
+
+        let points = ParameterSweep::new(space(), 1).with_random_sample(3).with_seed(1).points();
+        assert_eq!(points.len(), 3);
+        assert!(points.iter().all(|point| grid.contains(point)));
+        assert!(!points[1..].contains(&points[0]) && points[1] != points[2]);
+
+        let points = ParameterSweep::new(space(), 1).with_random_sample(10).points();
+        assert_eq!(points.len(), 4);
+    }
+
+    #[test]
+    fn ranks_the_points() {
+        let table = ParameterSweep::new(space(), 4).with_target(20.0).run(|parameters| {
+            let builder = ones_builder(parameters.number("population_size").unwrap() as u32, 20)
+                .with_selection(TournamentSelection::new(3, 1.0))
+                .with_stop_condition(|best, iterations, _| best == 20.0 || iterations >= 100)
+                .with_seed(3);
+
+            match parameters.choice("mutation") {
+                Some("none") => builder.with_mutation(DoNothingMutation),
+                _ => builder.with_mutation(BitFlipMutation::default()),
+            }
+        })
+        .unwrap();
+
+        assert_eq!(table.rows.len(), 4);
+        assert_eq!(table.rows[0].parameters.choice("mutation"), Some("bit flip"));
+        assert!(table.rows[0].summary.mean >= table.rows[1].summary.mean);
+        assert_eq!(table.rows[0].summary.runs, 4);
+
+        let path = std::env::temp_dir().join("evolutionary_sweep_test.csv");
+        table.write_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(csv.starts_with("rank,population_size,mutation,runs,mean,median,std_dev,"));
+        assert_eq!(csv.lines().count(), 5);
+        assert!(table.to_string().starts_with("Ranked by Mean\n1. "));
+    }
+
+    #[test]
+    fn requires_a_shared_objective() {
+        let sweep = ParameterSweep::new(space(), 1).run(|parameters| {
+            let objective = match parameters.choice("mutation") {
+                Some("none") => Objective::Minimize,
+                _ => Objective::Maximize,
+            };
+
+            EvolutionBuilder::<Bin>::new(10, 20, GeneCod::Bin, ()).with_objective(objective)
+        });
+
+        assert_eq!(sweep.err().unwrap(), "The evolutions of a sweep should share the objective");
+    }
+
+    #[test]
+    fn rejects_the_spaces_without_points() {
+        let run = |space: ParameterSpace| {
+            ParameterSweep::new(space, 1)
+                .run(|_| -> EvolutionBuilder<Bin> { unreachable!() })
+                .err()
+                .unwrap()
+        };
+
+        assert_eq!(run(ParameterSpace::new()), "The parameter space has no parameters");
+        assert_eq!(
+            run(space().with_values("elitism", Vec::<u32>::new())),
+            "The parameter <elitism> has no values"
+        );
+    }
+}
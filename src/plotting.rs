@@ -1,106 +1,355 @@
+use std::error::Error;
+use std::path::PathBuf;
+
 use plotters::backend::BitMapBackend;
 use plotters::chart::ChartBuilder;
+use plotters::coord::ranged1d::{AsRangedCoord, Ranged, ValueFormatter};
+use plotters::coord::Shift;
 use plotters::data::Quartiles;
 use plotters::element::{Boxplot, PathElement};
 use plotters::prelude::*;
+use serde_derive::Deserialize;
+
+use crate::objective::Objective;
+
+/// The file format of a chart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartFormat {
+    Png,
+    Svg,
+}
+
+/// How the values are placed on the y axis of a chart.
+///
+/// - `Raw`: as they are.
+/// - `Normalized`: all the series together scaled from 0, the lowest value, to 1, the highest.
+/// - `NormalizedPerSeries`: each series scaled from 0 to 1 on its own, to compare the shape of
+///   measures of different scales.
+/// - `Log`: as they are, on a logarithmic axis. The values that aren't positive are left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ChartScale {
+    Raw,
+    Normalized,
+    NormalizedPerSeries,
+    Log,
+}
+
+/// A series recorded in the [`Metrics`](crate::metrics::Metrics) that a chart can show.
+/// `Percentiles` shows one line per configured percentile. The diversity measures are only shown
+/// when they were measured and the individual supports them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ChartSeries {
+    Best,
+    Average,
+    Worst,
+    Median,
+    Percentiles,
+    FitnessStdDev,
+    Entropy,
+    MeanDistance,
+    UniqueChromosomes,
+}
+
+/// # Chart Options
+///
+/// Where and how a chart is drawn: the output `path`, the `title`, the `size` in pixels, the
+/// `format`, the `scale` of the y axis and the `series` to show.
+///
+/// [`ChartOptions::new`] takes the format from the extension of the path, `.svg` or PNG
+/// otherwise, and shows the best and the average fitness in their raw values.
+///
+/// # Example
+///
+/// ```no_run
+/// use evolutionary::plotting::{ChartOptions, ChartScale, ChartSeries};
+///
+/// let options = ChartOptions::new("results/tsp.svg", "TSP")
+///     .with_size(1280, 720)
+///     .with_scale(ChartScale::Log)
+///     .with_series(vec![ChartSeries::Best, ChartSeries::Median, ChartSeries::Worst]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartOptions {
+    pub path: PathBuf,
+    pub title: String,
+    pub size: (u32, u32),
+    pub format: ChartFormat,
+    pub scale: ChartScale,
+    pub series: Vec<ChartSeries>,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("chart.png"),
+            title: String::new(),
+            size: (640, 480),
+            format: ChartFormat::Png,
+            scale: ChartScale::Raw,
+            series: vec![ChartSeries::Best, ChartSeries::Average],
+        }
+    }
+}
+
+impl ChartOptions {
+    pub fn new(path: impl Into<PathBuf>, title: impl Into<String>) -> Self {
+        let path = path.into();
+        let format = match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("svg") => ChartFormat::Svg,
+            _ => ChartFormat::Png,
+        };
+
+        Self {
+            path,
+            title: title.into(),
+            format,
+            ..Default::default()
+        }
+    }
 
-fn normalize(values: &Vec<f64>, max: f64) -> Vec<f64> {
-    let min = 0.0;
-    values.iter().map(|&v| (v - min) / (max - min)).collect()
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn with_format(mut self, format: ChartFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: ChartScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_series(mut self, series: Vec<ChartSeries>) -> Self {
+        self.series = series;
+        self
+    }
+}
+
+/// Scales the values from 0, at `min`, to 1, at `max`.
+fn normalize(values: &[f64], min: f64, max: f64) -> Vec<f64> {
+    let range = if max > min { max - min } else { 1.0 };
+    values.iter().map(|&v| (v - min) / range).collect()
+}
+
+fn bounds<'a>(values: impl IntoIterator<Item = &'a f64>) -> Option<(f64, f64)> {
+    values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .fold(None, |bounds, &v| match bounds {
+            None => Some((v, v)),
+            Some((min, max)) => Some((f64::min(min, v), f64::max(max, v))),
+        })
 }
 
+/// Plots the best and the average fitness over the generations, normalized.
 pub fn plot_chart(
-    best_fitness: &Vec<f64>,
-    average_fitness: &Vec<f64>,
-    path: &String,
-    test_name: &String,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let max = *average_fitness
-        .iter()
-        .chain(best_fitness)
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(&1.0);
-
-    let normalized_avg = normalize(&average_fitness, max);
-    let normalized_best = normalize(&best_fitness, max);
-
-    let min = normalized_avg
-        .iter()
-        .chain(&normalized_best)
-        .min_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(&0.0);
-
-    let y_axis_start = if *min > 0.5 { min } else { min };
-
-    let y_axis_start = y_axis_start - (y_axis_start * 0.1);
-
-    let root = BitMapBackend::new(&path, (640, 480)).into_drawing_area();
+    best_fitness: &[f64],
+    average_fitness: &[f64],
+    path: &str,
+    test_name: &str,
+    objective: Objective,
+) -> Result<(), Box<dyn Error>> {
+    let best_label = match objective {
+        Objective::Maximize => "Best Fitness",
+        Objective::Minimize => "Best Fitness (Lowest)",
+    };
+
+    plot_lines(
+        &[
+            (best_label.to_string(), best_fitness.to_vec()),
+            ("Average Fitness".to_string(), average_fitness.to_vec()),
+        ],
+        &ChartOptions::new(path, test_name).with_scale(ChartScale::Normalized),
+    )
+}
+
+/// Plots each of the labeled `series` as a line over the generations, as set by the `options`.
+/// The `series` of the options is ignored, as the lines are given.
+pub fn plot_lines(
+    series: &[(String, Vec<f64>)],
+    options: &ChartOptions,
+) -> Result<(), Box<dyn Error>> {
+    let series: Vec<(String, Vec<f64>)> = match options.scale {
+        ChartScale::Raw | ChartScale::Log => series.to_vec(),
+        ChartScale::Normalized => {
+            let (min, max) =
+                bounds(series.iter().flat_map(|(_, values)| values)).unwrap_or((0.0, 1.0));
+
+            series
+                .iter()
+                .map(|(label, values)| (label.clone(), normalize(values, min, max)))
+                .collect()
+        }
+        ChartScale::NormalizedPerSeries => series
+            .iter()
+            .map(|(label, values)| {
+                let (min, max) = bounds(values).unwrap_or((0.0, 1.0));
+                (label.clone(), normalize(values, min, max))
+            })
+            .collect(),
+    };
+
+    let generations = series.iter().map(|(_, values)| values.len()).max().unwrap_or(0);
+    let x_range = 0f64..generations.max(1) as f64;
+
+    let positive = |v: &&f64| options.scale != ChartScale::Log || **v > 0.0;
+    let (min, max) = bounds(series.iter().flat_map(|(_, values)| values).filter(positive))
+        .unwrap_or((1.0, 1.0));
+
+    if options.scale == ChartScale::Log {
+        draw_lines(&series, options, x_range, (min * 0.9..max * 1.1).log_scale())
+    } else {
+        let margin = if max > min { (max - min) * 0.05 } else { 1.0 };
+        draw_lines(&series, options, x_range, min - margin..max + margin)
+    }
+}
+
+/// Draws the lines on the backend of the format of the `options`.
+fn draw_lines<Y>(
+    series: &[(String, Vec<f64>)],
+    options: &ChartOptions,
+    x_range: std::ops::Range<f64>,
+    y_range: Y,
+) -> Result<(), Box<dyn Error>>
+where
+    Y: AsRangedCoord<Value = f64>,
+    Y::CoordDescType: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    match options.format {
+        ChartFormat::Png => draw_lines_on(
+            BitMapBackend::new(&options.path, options.size).into_drawing_area(),
+            series,
+            options,
+            x_range,
+            y_range,
+        ),
+        ChartFormat::Svg => draw_lines_on(
+            SVGBackend::new(&options.path, options.size).into_drawing_area(),
+            series,
+            options,
+            x_range,
+            y_range,
+        ),
+    }
+}
+
+fn draw_lines_on<DB, Y>(
+    root: DrawingArea<DB, Shift>,
+    series: &[(String, Vec<f64>)],
+    options: &ChartOptions,
+    x_range: std::ops::Range<f64>,
+    y_range: Y,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    Y: AsRangedCoord<Value = f64>,
+    Y::CoordDescType: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
     root.fill(&WHITE)?;
 
     let mut chart = ChartBuilder::on(&root)
-        .caption(format!("{}", test_name), ("sans-serif", 40))
+        .caption(&options.title, ("sans-serif", 40))
         .margin(10)
         .x_label_area_size(30)
-        .y_label_area_size(40)
-        .build_cartesian_2d(0f64..normalized_best.len() as f64, (y_axis_start)..1.05)?;
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_range, y_range)?;
 
     chart.configure_mesh().draw()?;
 
-    chart
-        .draw_series(LineSeries::new(
-            normalized_best
-                .iter()
-                .enumerate()
-                .map(|(x, y)| (x as f64, *y)),
-            &RED,
-        ))?
-        .label("Best Fitness")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+    for (index, (label, values)) in series.iter().enumerate() {
+        let color = Palette99::pick(index).to_rgba();
+        let log_scale = options.scale == ChartScale::Log;
 
-    chart
-        .draw_series(LineSeries::new(
-            normalized_avg
-                .iter()
-                .enumerate()
-                .map(|(x, y)| (x as f64, *y)),
-            &BLUE,
-        ))?
-        .label("Average Fitness")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
+        chart
+            .draw_series(LineSeries::new(
+                values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &y)| y.is_finite() && (!log_scale || y > 0.0))
+                    .map(|(x, &y)| (x as f64, y)),
+                color,
+            ))?
+            .label(label.as_str())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
 
     chart
         .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()?;
 
+    root.present()?;
+
     Ok(())
 }
 
-pub fn plot_boxplot(
-    quartiles: &Vec<Quartiles>,
-    labels: &Vec<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let root = BitMapBackend::new("boxplot.png", (640, 480)).into_drawing_area();
+/// Plots one box per label, with the `quartiles` of its values, to `boxplot.png`.
+#[deprecated(note = "use `plot_boxplot_with_options` to set the path, title, size and format")]
+pub fn plot_boxplot(quartiles: &[Quartiles], labels: &[String]) -> Result<(), Box<dyn Error>> {
+    plot_boxplot_with_options(
+        quartiles,
+        labels,
+        &ChartOptions::new("boxplot.png", "128 Queens Score"),
+    )
+}
+
+/// Plots one box per label, with the `quartiles` of its values, to the path and with the title,
+/// size and format of the `options`.
+pub fn plot_boxplot_with_options(
+    quartiles: &[Quartiles],
+    labels: &[String],
+    options: &ChartOptions,
+) -> Result<(), Box<dyn Error>> {
+    match options.format {
+        ChartFormat::Png => draw_boxplot(
+            BitMapBackend::new(&options.path, options.size).into_drawing_area(),
+            quartiles,
+            labels,
+            options,
+        ),
+        ChartFormat::Svg => draw_boxplot(
+            SVGBackend::new(&options.path, options.size).into_drawing_area(),
+            quartiles,
+            labels,
+            options,
+        ),
+    }
+}
+
+fn draw_boxplot<DB>(
+    root: DrawingArea<DB, Shift>,
+    quartiles: &[Quartiles],
+    labels: &[String],
+    options: &ChartOptions,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
     root.fill(&WHITE)?;
 
-    let min = quartiles
-        .iter()
-        .map(|q| q.values()[0])
-        .min_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap();
-    let max = quartiles
-        .iter()
-        .map(|q| q.values()[4])
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap();
+    let (min, max) = bounds(
+        quartiles
+            .iter()
+            .flat_map(|q| [q.values()[0], q.values()[4]])
+            .map(f64::from)
+            .collect::<Vec<f64>>()
+            .iter(),
+    )
+    .unwrap_or((0.0, 1.0));
+    let margin = if max > min { (max - min) * 0.05 } else { 1.0 };
 
     let mut chart = ChartBuilder::on(&root)
-        .caption("128 Queens Score", ("sans-serif", 40))
+        .caption(&options.title, ("sans-serif", 40))
         .margin(10)
         .x_label_area_size(30)
-        .y_label_area_size(40)
-        .build_cartesian_2d(labels.into_segmented(), min..max)?;
+        .y_label_area_size(60)
+        .build_cartesian_2d(labels.into_segmented(), (min - margin) as f32..(max + margin) as f32)?;
 
     chart.configure_mesh().draw()?;
 
@@ -111,5 +360,45 @@ pub fn plot_boxplot(
         )])?;
     }
 
+    root.present()?;
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_negative_values() {
+        assert_eq!(normalize(&[-4.0, -2.0, 0.0], -4.0, 0.0), vec![0.0, 0.5, 1.0]);
+        assert_eq!(bounds(&[f64::NAN, -1.0, 3.0]), Some((-1.0, 3.0)));
+    }
+
+    #[test]
+    fn plots_to_svg() {
+        let path = std::env::temp_dir().join("evolutionary_chart_test.svg");
+        let series = vec![
+            ("Best".to_string(), vec![-3.0, -1.0, 0.5, 2.0]),
+            ("Worst".to_string(), vec![-9.0, -6.0, -2.0, 1.0]),
+        ];
+
+        for scale in [ChartScale::Raw, ChartScale::Normalized, ChartScale::Log] {
+            let options = ChartOptions::new(&path, "Negative").with_scale(scale);
+            assert_eq!(options.format, ChartFormat::Svg);
+
+            plot_lines(&series, &options).unwrap();
+
+            let svg = std::fs::read_to_string(&path).unwrap();
+            assert!(svg.contains("Negative") && svg.contains("Worst"));
+        }
+
+        let options = ChartOptions::new(&path, "Scores");
+        let quartiles = vec![Quartiles::new(&[1.0, 2.0, 3.0]), Quartiles::new(&[-1.0, 0.0])];
+        let labels = ["a".to_string(), "b".to_string()];
+        plot_boxplot_with_options(&quartiles, &labels, &options).unwrap();
+
+        assert!(std::fs::read_to_string(&path).unwrap().contains("Scores"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
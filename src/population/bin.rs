@@ -1,11 +1,17 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
 
-use super::Individual;
+use super::{hash_genes, mean_locus_entropy, Individual};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bin {
     chromosome: Vec<bool>,
+    #[serde(with = "crate::checkpoint::float")]
     fitness: f64,
+    #[serde(default, with = "crate::checkpoint::float")]
+    violation: f64,
+    #[serde(default)]
+    dirty: bool,
 }
 
 impl Bin {
@@ -13,6 +19,8 @@ impl Bin {
         Self {
             chromosome,
             fitness: 0.0,
+            violation: 0.0,
+            dirty: true,
         }
     }
 }
@@ -21,12 +29,12 @@ impl Individual for Bin {
     type Gene = bool;
     type RangeType = ();
 
-    fn generate_member(dimension: u32, _: &Self::RangeType) -> Self {
-        let mut rng = thread_rng();
-
+    fn generate_member<R: Rng + ?Sized>(dimension: u32, _: &Self::RangeType, rng: &mut R) -> Self {
         Self {
             chromosome: (0..dimension).map(|_| rng.gen()).collect::<Vec<bool>>(),
             fitness: 0.0,
+            violation: 0.0,
+            dirty: true,
         }
     }
 
@@ -35,10 +43,12 @@ impl Individual for Bin {
     }
 
     fn get_mut_chromosome(&mut self) -> &mut Vec<Self::Gene> {
+        self.dirty = true;
         &mut self.chromosome
     }
 
     fn set_gene(&mut self, index: usize, value: Self::Gene) {
+        self.dirty = true;
         self.chromosome[index] = value;
     }
 
@@ -53,4 +63,38 @@ impl Individual for Bin {
     fn get_fitness(&self) -> f64 {
         self.fitness
     }
+
+    fn set_violation(&mut self, violation: f64) {
+        self.violation = violation;
+    }
+
+    fn get_violation(&self) -> f64 {
+        self.violation
+    }
+
+    fn chromosome_hash(&self) -> Option<u64> {
+        Some(hash_genes(&self.chromosome))
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn distance(&self, other: &Self) -> Option<f64> {
+        Some(
+            self.chromosome
+                .iter()
+                .zip(&other.chromosome)
+                .filter(|(a, b)| a != b)
+                .count() as f64,
+        )
+    }
+
+    fn locus_entropy(population: &[Self]) -> Option<f64> {
+        Some(mean_locus_entropy(population))
+    }
 }
@@ -0,0 +1,163 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use serde_derive::{Deserialize, Serialize};
+
+/// # Bounds
+///
+/// The domain of the genes of the [`Real`](super::Real) and [`Int`](super::Int) individuals, both
+/// ends included. It's either `Uniform`, the same `(min, max)` for every gene, or `PerGene`, one
+/// `(min, max)` for each gene, for problems where the variables have different domains. The bounds
+/// of each gene are shared, so every individual can hold its bounds without copying them.
+///
+/// A tuple or a `Vec` of tuples can be used wherever bounds are expected:
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// let uniform: Bounds<f64> = (0.0, 1.0).into();
+/// let per_gene: Bounds<f64> = vec![(0.0, 24.0), (0.0, 16.0)].into();
+///
+/// assert_eq!(uniform.get(1), (0.0, 1.0));
+/// assert_eq!(per_gene.get(1), (0.0, 16.0));
+/// assert_eq!(per_gene.clamp(0, 30.0), 24.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Bounds<N> {
+    Uniform(N, N),
+    PerGene(Arc<[(N, N)]>),
+}
+
+/// The numbers the [`Bounds`] are made of.
+pub trait BoundValue: Copy + PartialOrd + Display {
+    /// Whether the value is a finite number, neither infinite nor NaN.
+    fn is_finite(self) -> bool;
+}
+
+impl BoundValue for f64 {
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
+
+impl BoundValue for i64 {
+    fn is_finite(self) -> bool {
+        true
+    }
+}
+
+impl<N: BoundValue> Bounds<N> {
+    /// Returns the `(min, max)` of the gene at `index`.
+    pub fn get(&self, index: usize) -> (N, N) {
+        match self {
+            Bounds::Uniform(min, max) => (*min, *max),
+            Bounds::PerGene(bounds) => bounds[index],
+        }
+    }
+
+    /// Returns `value` limited to the bounds of the gene at `index`.
+    pub fn clamp(&self, index: usize, value: N) -> N {
+        let (min, max) = self.get(index);
+
+        if value < min {
+            min
+        } else if value > max {
+            max
+        } else {
+            value
+        }
+    }
+
+    /// Checks that there are bounds for each of the `dimension` genes and that none of them is
+    /// empty or infinite.
+    pub fn check(&self, dimension: u32) -> Result<(), String> {
+        let bounds = match self {
+            Bounds::Uniform(min, max) => vec![(*min, *max)],
+            Bounds::PerGene(bounds) => {
+                if bounds.len() != dimension as usize {
+                    return Err(format!(
+                        "Expected bounds for {} genes, got {}",
+                        dimension,
+                        bounds.len()
+                    ));
+                }
+                bounds.to_vec()
+            }
+        };
+
+        let invalid = |&(min, max): &(N, N)| {
+            !min.is_finite()
+                || !max.is_finite()
+                || !matches!(min.partial_cmp(&max), Some(Ordering::Less | Ordering::Equal))
+        };
+
+        match bounds.iter().position(invalid) {
+            Some(index) => Err(format!(
+                "Invalid bounds for the gene {}: ({}, {})",
+                index, bounds[index].0, bounds[index].1
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<N> From<(N, N)> for Bounds<N> {
+    fn from((min, max): (N, N)) -> Self {
+        Bounds::Uniform(min, max)
+    }
+}
+
+impl<N> From<Vec<(N, N)>> for Bounds<N> {
+    fn from(bounds: Vec<(N, N)>) -> Self {
+        Bounds::PerGene(bounds.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn generates_each_gene_within_its_bounds() {
+        let bounds: Bounds<i64> = vec![(0, 0), (10, 12), (-5, -3)].into();
+        let mut rng = EvolutionRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let individual = Int::generate_member(3, &bounds, &mut rng);
+
+            for (i, &gene) in individual.get_chromosome().iter().enumerate() {
+                let (min, max) = bounds.get(i);
+                assert!(min <= gene && gene <= max);
+            }
+        }
+    }
+
+    #[test]
+    fn checks_the_bounds() {
+        assert!(Bounds::Uniform(0.0, 1.0).check(5).is_ok());
+        assert!(Bounds::Uniform(1.0, 0.0).check(5).is_err());
+        assert!(Bounds::from(vec![(0.0, 1.0)]).check(2).is_err());
+        assert!(Bounds::from(vec![(0.0, 1.0), (f64::NAN, 1.0)]).check(2).is_err());
+        assert!(Bounds::Uniform(0.0, f64::INFINITY).check(2).is_err());
+        assert!(Bounds::from(vec![(0.0, 1.0), (f64::NEG_INFINITY, 1.0)]).check(2).is_err());
+    }
+
+    #[test]
+    fn individuals_share_the_bounds() {
+        let bounds: Bounds<f64> = vec![(0.0, 1.0); 100].into();
+        let mut rng = EvolutionRng::seed_from_u64(0);
+
+        let a = Real::generate_member(100, &bounds, &mut rng);
+        let b = Real::generate_member(100, &bounds, &mut rng);
+
+        match (&a.range, &b.range) {
+            (Bounds::PerGene(a), Bounds::PerGene(b)) => assert!(Arc::ptr_eq(a, b)),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -1,23 +1,32 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
 
+use rand::Rng;
 use serde_derive::Deserialize;
 
 mod bin;
+mod bounds;
 mod int;
 mod perm;
 mod real;
+mod tree;
 
 pub use bin::*;
+pub use bounds::*;
 pub use int::*;
 pub use perm::*;
 pub use real::*;
+pub use tree::*;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum GeneCod {
     Int,
     IntPerm,
     Bin,
     Real,
+    Tree,
 }
 
 /// Trait that must be implemented by a struct to be considered a individual.
@@ -26,6 +35,7 @@ pub enum GeneCod {
 ///
 /// ```
 /// use evolutionary::prelude::*;
+/// use rand::Rng;
 ///
 /// #[derive(Clone)]
 /// struct MyIndividual {
@@ -37,7 +47,7 @@ pub enum GeneCod {
 ///     type Gene = u64;
 ///     type RangeType = ();
 ///
-///     fn generate_member(dimension: u32, _: &Self::RangeType) -> Self {
+///     fn generate_member<R: Rng + ?Sized>(dimension: u32, _: &Self::RangeType, _: &mut R) -> Self {
 ///         Self {
 ///             chromosome: vec![0; dimension as usize],
 ///             fitness: 0.0,
@@ -73,11 +83,96 @@ pub trait Individual: 'static + Clone + Send + Sync {
     type Gene: Debug + Send + Sync + Copy;
     type RangeType: Send + Sync + Clone;
 
-    fn generate_member(dimension: u32, b: &Self::RangeType) -> Self;
+    /// Generates a random member with `dimension` genes, drawing every random value from `rng`.
+    fn generate_member<R: Rng + ?Sized>(dimension: u32, b: &Self::RangeType, rng: &mut R) -> Self;
+    /// Checks if the `range` can generate members with `dimension` genes. It's called when the
+    /// evolution is built.
+    fn check_range(_dimension: u32, _range: &Self::RangeType) -> Result<(), String> {
+        Ok(())
+    }
+    /// How far the genes are out of their bounds, summed. It's 0 for the individuals whose genes
+    /// are always within them.
+    fn bounds_violation(&self) -> f64 {
+        0.0
+    }
     fn get_chromosome(&self) -> &Vec<Self::Gene>;
     fn get_mut_chromosome(&mut self) -> &mut Vec<Self::Gene>;
     fn set_gene(&mut self, index: usize, value: Self::Gene);
     fn get_gene(&self, index: usize) -> Self::Gene;
     fn set_fitness(&mut self, fitness: f64);
     fn get_fitness(&self) -> f64;
+    /// Stores the total constraint violation of the individual, see
+    /// [`Constraints`](crate::Constraints). Individuals that don't store it are always feasible.
+    fn set_violation(&mut self, _violation: f64) {}
+    /// Total constraint violation of the individual, 0 when it's feasible.
+    fn get_violation(&self) -> f64 {
+        0.0
+    }
+    /// Hash of the chromosome, the key of the fitness cache. The evolution also uses it to tell
+    /// which offsprings the operators changed. Individuals that return `None`, the default, are
+    /// never cached.
+    fn chromosome_hash(&self) -> Option<u64> {
+        None
+    }
+    /// Whether the chromosome changed since the fitness was calculated. With the fitness cache,
+    /// the individuals that aren't dirty keep their fitness instead of being evaluated again.
+    /// Individuals that don't store it are always dirty.
+    fn is_dirty(&self) -> bool {
+        true
+    }
+    fn set_dirty(&mut self, _dirty: bool) {}
+    /// Distance between the chromosomes of two individuals, for the diversity metrics, see
+    /// [`Diversity`](crate::metrics::Diversity). It's `None`, the default, when the individual
+    /// has no distance defined.
+    fn distance(&self, _other: &Self) -> Option<f64> {
+        None
+    }
+    /// Shannon entropy, in bits, of the genes at each locus of the `population`, averaged over the
+    /// loci. It's 0 when all the individuals have the same genes and `None`, the default, when
+    /// it's not defined for the individual.
+    fn locus_entropy(_population: &[Self]) -> Option<f64> {
+        None
+    }
+}
+
+/// Mean Shannon entropy, in bits, of the genes at each locus of the `population`.
+pub(crate) fn mean_locus_entropy<T: Individual>(population: &[T]) -> f64
+where
+    T::Gene: Hash + Eq,
+{
+    let loci = population.first().map_or(0, |individual| individual.get_chromosome().len());
+    if loci == 0 {
+        return 0.0;
+    }
+
+    let total = population.len() as f64;
+    let entropy_sum: f64 = (0..loci)
+        .map(|locus| {
+            let mut counts: HashMap<T::Gene, usize> = HashMap::new();
+            for individual in population {
+                *counts.entry(individual.get_gene(locus)).or_default() += 1;
+            }
+
+            counts
+                .values()
+                .map(|&count| {
+                    let p = count as f64 / total;
+                    -p * p.log2()
+                })
+                .sum::<f64>()
+        })
+        .sum();
+
+    entropy_sum / loci as f64
+}
+
+/// Hashes the genes of a chromosome, for [`Individual::chromosome_hash`].
+pub(crate) fn hash_genes<H: Hash>(genes: impl IntoIterator<Item = H>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    for gene in genes {
+        gene.hash(&mut hasher);
+    }
+
+    hasher.finish()
 }
@@ -1,26 +1,38 @@
-use rand::{seq::SliceRandom, thread_rng};
+use std::collections::HashSet;
 
-use super::Individual;
+use rand::{seq::SliceRandom, Rng};
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+use super::{hash_genes, Individual};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IntPerm {
     pub chromosome: Vec<i64>,
+    #[serde(with = "crate::checkpoint::float")]
     fitness: f64,
+    #[serde(default, with = "crate::checkpoint::float")]
+    violation: f64,
+    #[serde(default)]
+    dirty: bool,
 }
 
 impl Individual for IntPerm {
     type Gene = i64;
     type RangeType = ();
 
-    fn generate_member(dimension: u32, _: &Self::RangeType) -> Self {
-        let mut rng = thread_rng();
-
+    fn generate_member<R: Rng + ?Sized>(
+        dimension: u32,
+        _: &Self::RangeType,
+        rng: &mut R,
+    ) -> Self {
         let mut member = (1..=dimension as i64).collect::<Vec<i64>>();
-        member.shuffle(&mut rng);
+        member.shuffle(rng);
 
         Self {
             chromosome: member,
             fitness: 0.0,
+            violation: 0.0,
+            dirty: true,
         }
     }
 
@@ -29,10 +41,12 @@ impl Individual for IntPerm {
     }
 
     fn get_mut_chromosome(&mut self) -> &mut Vec<Self::Gene> {
+        self.dirty = true;
         &mut self.chromosome
     }
 
     fn set_gene(&mut self, index: usize, value: Self::Gene) {
+        self.dirty = true;
         self.chromosome[index] = value;
     }
 
@@ -47,4 +61,41 @@ impl Individual for IntPerm {
     fn get_fitness(&self) -> f64 {
         self.fitness
     }
+
+    fn set_violation(&mut self, violation: f64) {
+        self.violation = violation;
+    }
+
+    fn get_violation(&self) -> f64 {
+        self.violation
+    }
+
+    fn chromosome_hash(&self) -> Option<u64> {
+        Some(hash_genes(&self.chromosome))
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn distance(&self, other: &Self) -> Option<f64> {
+        let adjacencies = |chromosome: &[i64]| -> HashSet<(i64, i64)> {
+            chromosome
+                .windows(2)
+                .map(|pair| (pair[0].min(pair[1]), pair[0].max(pair[1])))
+                .collect()
+        };
+
+        let other_adjacencies = adjacencies(&other.chromosome);
+
+        Some(
+            adjacencies(&self.chromosome)
+                .difference(&other_adjacencies)
+                .count() as f64,
+        )
+    }
 }
@@ -1,39 +1,69 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
 
-use super::Individual;
+use super::{hash_genes, Bounds, Individual};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Real {
     pub chromosome: Vec<f64>,
+    #[serde(with = "crate::checkpoint::float")]
     fitness: f64,
+    #[serde(default, with = "crate::checkpoint::float")]
+    violation: f64,
+    #[serde(default)]
+    dirty: bool,
     pub range: <Real as Individual>::RangeType,
 }
 
 impl Individual for Real {
     type Gene = f64;
-    type RangeType = (f64, f64);
-
-    fn generate_member(dimension: u32, (a, b): &Self::RangeType) -> Self {
-        let mut rng = thread_rng();
+    type RangeType = Bounds<f64>;
 
+    fn generate_member<R: Rng + ?Sized>(
+        dimension: u32,
+        range: &Self::RangeType,
+        rng: &mut R,
+    ) -> Self {
         Self {
-            chromosome: (0..dimension)
-                .map(|_| rng.gen_range(*a..=*b))
+            chromosome: (0..dimension as usize)
+                .map(|i| {
+                    let (min, max) = range.get(i);
+                    rng.gen_range(min..=max)
+                })
                 .collect::<Vec<f64>>(),
             fitness: 0.0,
-            range: (*a, *b),
+            violation: 0.0,
+            dirty: true,
+            range: range.clone(),
         }
     }
 
+    fn check_range(dimension: u32, range: &Self::RangeType) -> Result<(), String> {
+        range.check(dimension)
+    }
+
+    fn bounds_violation(&self) -> f64 {
+        self.chromosome
+            .iter()
+            .enumerate()
+            .map(|(i, &gene)| {
+                let (min, max) = self.range.get(i);
+                (min - gene).max(0.0) + (gene - max).max(0.0)
+            })
+            .sum()
+    }
+
     fn get_chromosome(&self) -> &Vec<Self::Gene> {
         &self.chromosome
     }
 
     fn get_mut_chromosome(&mut self) -> &mut Vec<Self::Gene> {
+        self.dirty = true;
         &mut self.chromosome
     }
 
     fn set_gene(&mut self, index: usize, value: Self::Gene) {
+        self.dirty = true;
         self.chromosome[index] = value;
     }
 
@@ -48,4 +78,35 @@ impl Individual for Real {
     fn get_fitness(&self) -> f64 {
         self.fitness
     }
+
+    fn set_violation(&mut self, violation: f64) {
+        self.violation = violation;
+    }
+
+    fn get_violation(&self) -> f64 {
+        self.violation
+    }
+
+    fn chromosome_hash(&self) -> Option<u64> {
+        Some(hash_genes(self.chromosome.iter().map(|gene| gene.to_bits())))
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn distance(&self, other: &Self) -> Option<f64> {
+        Some(
+            self.chromosome
+                .iter()
+                .zip(&other.chromosome)
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>()
+                .sqrt(),
+        )
+    }
 }
@@ -0,0 +1,452 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Debug, Display, Write};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
+
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+
+use super::{hash_genes, Individual};
+
+mod primitives;
+
+pub use primitives::*;
+
+/// The values a [`Tree`] can compute. It's implemented for every type that fits, like `f64`,
+/// `i64` or `bool`.
+pub trait TreeValue: Copy + Debug + Send + Sync + 'static {}
+
+impl<V: Copy + Debug + Send + Sync + 'static> TreeValue for V {}
+
+/// A node of a [`Tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Node<V> {
+    /// The function at `index` of the [`PrimitiveSet`], applied to the `arity` subtrees that
+    /// follow it.
+    Function { index: usize, arity: usize },
+    /// The input variable at `index` of the [`PrimitiveSet`].
+    Variable(usize),
+    /// A constant value.
+    Constant(V),
+}
+
+impl<V> Node<V> {
+    /// Number of children of the node, 0 for the terminals.
+    pub fn arity(&self) -> usize {
+        match self {
+            Node::Function { arity, .. } => *arity,
+            _ => 0,
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.arity() == 0
+    }
+}
+
+impl<V: TreeValue> Node<V> {
+    /// Hash of the node. The values have no `Hash`, so the constants are hashed by their `Debug`
+    /// output, which tells apart any two different numbers.
+    fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        match self {
+            Node::Function { index, arity } => (0u8, index, arity).hash(&mut hasher),
+            Node::Variable(index) => (1u8, index).hash(&mut hasher),
+            Node::Constant(value) => {
+                2u8.hash(&mut hasher);
+                // Writing to a hasher never fails.
+                let _ = write!(HashWriter(&mut hasher), "{:?}", value);
+            }
+        }
+
+        hasher.finish()
+    }
+}
+
+/// Feeds the text written to it to the hasher.
+struct HashWriter<'a>(&'a mut DefaultHasher);
+
+impl Write for HashWriter<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Maximum depth and size of the trees. The operators discard the offsprings that exceed them,
+/// keeping the parents instead, which keeps the bloat under control. Default is a depth of 17, as
+/// in Koza's experiments, and no size limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeLimits {
+    pub max_depth: usize,
+    pub max_size: usize,
+}
+
+impl Default for TreeLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 17,
+            max_size: usize::MAX,
+        }
+    }
+}
+
+impl TreeLimits {
+    /// Returns if the tree is within the limits.
+    pub fn allows<V>(&self, tree: &Tree<V>) -> bool {
+        tree.size() <= self.max_size && tree.depth() <= self.max_depth
+    }
+}
+
+/// The range of the [`Tree`] individuals, passed to the `EvolutionBuilder`. The initial
+/// population is created with the ramped half-and-half method: each tree gets a random depth from
+/// `min_depth` to the `dimension` of the evolution, and is generated with either the full or the
+/// grow method.
+#[derive(Clone, Debug)]
+pub struct TreeConfig<V> {
+    pub primitives: Arc<PrimitiveSet<V>>,
+    pub min_depth: usize,
+    pub limits: TreeLimits,
+}
+
+impl<V> TreeConfig<V> {
+    /// Creates the config with a `min_depth` of 2 and the default limits.
+    pub fn new(primitives: Arc<PrimitiveSet<V>>) -> Self {
+        Self {
+            primitives,
+            min_depth: 2,
+            limits: TreeLimits::default(),
+        }
+    }
+}
+
+/// # Tree
+///
+/// Individual for genetic programming: an expression tree built from the functions and terminals
+/// of a [`PrimitiveSet`]. The nodes are stored in prefix order, every function followed by its
+/// arguments' subtrees, so the chromosome is still a flat `Vec` of [`Node`]s.
+///
+/// Evaluate it from your `Fitness` with [`Tree::evaluate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tree<V> {
+    nodes: Vec<Node<V>>,
+    #[serde(with = "crate::checkpoint::float")]
+    fitness: f64,
+    #[serde(default, with = "crate::checkpoint::float")]
+    violation: f64,
+    #[serde(default)]
+    dirty: bool,
+}
+
+impl<V> Tree<V> {
+    /// Creates a tree from its nodes in prefix order. Every function must be followed by exactly
+    /// `arity` subtrees.
+    pub fn new(nodes: Vec<Node<V>>) -> Self {
+        Self {
+            nodes,
+            fitness: 0.0,
+            violation: 0.0,
+            dirty: true,
+        }
+    }
+
+    pub fn nodes(&self) -> &[Node<V>] {
+        &self.nodes
+    }
+
+    /// Number of nodes of the tree.
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Depth of the tree. A tree with a single node has depth 0.
+    pub fn depth(&self) -> usize {
+        depth(&self.nodes)
+    }
+
+    /// Returns the range of the nodes of the subtree rooted at the node `start`.
+    pub fn subtree(&self, start: usize) -> Range<usize> {
+        let mut missing = 1;
+        let mut end = start;
+
+        while missing > 0 {
+            missing += self.nodes[end].arity();
+            missing -= 1;
+            end += 1;
+        }
+
+        start..end
+    }
+
+    /// Replaces the nodes in `range`, which must be a whole subtree, with `subtree`.
+    pub fn replace_subtree(&mut self, range: Range<usize>, subtree: &[Node<V>])
+    where
+        V: Copy,
+    {
+        self.dirty = true;
+        self.nodes.splice(range, subtree.iter().copied());
+    }
+
+    /// Returns the index of a random node. With probability `function_bias` the node is a
+    /// function, if the tree has any, otherwise a terminal. Koza used 0.9, to avoid just swapping
+    /// leaves around.
+    pub fn random_node<R: Rng + ?Sized>(&self, function_bias: f64, rng: &mut R) -> usize {
+        let want_function = rng.gen_bool(function_bias);
+
+        let candidates: Vec<usize> = (0..self.nodes.len())
+            .filter(|&index| self.nodes[index].is_terminal() != want_function)
+            .collect();
+
+        if candidates.is_empty() {
+            rng.gen_range(0..self.nodes.len())
+        } else {
+            candidates[rng.gen_range(0..candidates.len())]
+        }
+    }
+}
+
+impl<V: TreeValue> Tree<V> {
+    /// Evaluates the tree, giving to the variables of the `primitives` the `variables` values.
+    pub fn evaluate(&self, primitives: &PrimitiveSet<V>, variables: &[V]) -> V {
+        let functions = primitives.functions();
+        let mut stack: Vec<V> = Vec::with_capacity(self.nodes.len());
+
+        // Going backwards, the arguments of every function are already on the stack, the first
+        // one on top.
+        for node in self.nodes.iter().rev() {
+            match *node {
+                Node::Function { index, arity } => {
+                    let start = stack.len() - arity;
+                    stack[start..].reverse();
+
+                    let value = (functions[index].apply)(&stack[start..]);
+
+                    stack.truncate(start);
+                    stack.push(value);
+                }
+                Node::Variable(index) => stack.push(variables[index]),
+                Node::Constant(value) => stack.push(value),
+            }
+        }
+
+        stack[0]
+    }
+
+    /// Returns the tree as an expression, like `add(x, mul(x, 2))`, using the names of the
+    /// `primitives`.
+    pub fn to_expression(&self, primitives: &PrimitiveSet<V>) -> String
+    where
+        V: Display,
+    {
+        let mut expression = String::new();
+        self.write_expression(0, primitives, &mut expression);
+        expression
+    }
+
+    fn write_expression(&self, start: usize, primitives: &PrimitiveSet<V>, out: &mut String)
+    where
+        V: Display,
+    {
+        match self.nodes[start] {
+            Node::Function { index, arity } => {
+                out.push_str(&primitives.functions()[index].name);
+                out.push('(');
+
+                let mut child = start + 1;
+                for argument in 0..arity {
+                    if argument > 0 {
+                        out.push_str(", ");
+                    }
+                    self.write_expression(child, primitives, out);
+                    child = self.subtree(child).end;
+                }
+
+                out.push(')');
+            }
+            Node::Variable(index) => out.push_str(&primitives.variables()[index]),
+            Node::Constant(value) => out.push_str(&value.to_string()),
+        }
+    }
+}
+
+fn depth<V>(nodes: &[Node<V>]) -> usize {
+    let mut max_depth = 0;
+    // Depth of the nodes still to be visited, the next one on top.
+    let mut pending = vec![0];
+
+    for node in nodes {
+        let depth = pending.pop().unwrap_or(0);
+        max_depth = max_depth.max(depth);
+
+        pending.extend(std::iter::repeat_n(depth + 1, node.arity()));
+    }
+
+    max_depth
+}
+
+impl<V: TreeValue> Individual for Tree<V> {
+    type Gene = Node<V>;
+    type RangeType = TreeConfig<V>;
+
+    fn generate_member<R: Rng + ?Sized>(
+        dimension: u32,
+        config: &Self::RangeType,
+        rng: &mut R,
+    ) -> Self {
+        let max_depth = (dimension as usize).min(config.limits.max_depth);
+        let min_depth = config.min_depth.min(max_depth);
+
+        let depth = rng.gen_range(min_depth..=max_depth);
+
+        let nodes = if rng.gen_bool(0.5) {
+            config.primitives.full(depth, rng)
+        } else {
+            config.primitives.grow(depth, rng)
+        };
+
+        Self::new(nodes)
+    }
+
+    fn get_chromosome(&self) -> &Vec<Self::Gene> {
+        &self.nodes
+    }
+
+    fn get_mut_chromosome(&mut self) -> &mut Vec<Self::Gene> {
+        self.dirty = true;
+        &mut self.nodes
+    }
+
+    fn set_gene(&mut self, index: usize, value: Self::Gene) {
+        self.dirty = true;
+        self.nodes[index] = value;
+    }
+
+    fn get_gene(&self, index: usize) -> Self::Gene {
+        self.nodes[index]
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+
+    fn get_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    fn set_violation(&mut self, violation: f64) {
+        self.violation = violation;
+    }
+
+    fn get_violation(&self) -> f64 {
+        self.violation
+    }
+
+    fn chromosome_hash(&self) -> Option<u64> {
+        Some(hash_genes(self.nodes.iter().map(Node::key)))
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    /// Number of positions of the prefix order with different nodes, counting the nodes of the
+    /// larger tree that the other one lacks.
+    fn distance(&self, other: &Self) -> Option<f64> {
+        let different = self
+            .nodes
+            .iter()
+            .zip(&other.nodes)
+            .filter(|(a, b)| a.key() != b.key())
+            .count();
+
+        Some((different + self.nodes.len().abs_diff(other.nodes.len())) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    fn primitives() -> PrimitiveSet<f64> {
+        PrimitiveSet::<f64>::new()
+            .with_function("add", 2, |args| args[0] + args[1])
+            .with_function("sub", 2, |args| args[0] - args[1])
+            .with_function("neg", 1, |args| -args[0])
+            .with_variable("x")
+            .with_variable("y")
+            .with_constant(2.0)
+    }
+
+    // sub(add(x, 2), neg(y))
+    fn tree() -> Tree<f64> {
+        Tree::new(vec![
+            Node::Function { index: 1, arity: 2 },
+            Node::Function { index: 0, arity: 2 },
+            Node::Variable(0),
+            Node::Constant(2.0),
+            Node::Function { index: 2, arity: 1 },
+            Node::Variable(1),
+        ])
+    }
+
+    #[test]
+    fn evaluates_in_argument_order() {
+        assert_eq!(tree().evaluate(&primitives(), &[3.0, 4.0]), 9.0);
+        assert_eq!(tree().to_expression(&primitives()), "sub(add(x, 2), neg(y))");
+    }
+
+    #[test]
+    fn measures_the_structure() {
+        let tree = tree();
+
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.subtree(0), 0..6);
+        assert_eq!(tree.subtree(1), 1..4);
+        assert_eq!(tree.subtree(4), 4..6);
+        assert_eq!(tree.subtree(5), 5..6);
+    }
+
+    #[test]
+    fn ramped_half_and_half_respects_the_depths() {
+        let config = TreeConfig::new(Arc::new(primitives()));
+        let mut rng = EvolutionRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let tree = Tree::generate_member(5, &config, &mut rng);
+
+            assert!(tree.depth() <= 5);
+            assert_eq!(tree.subtree(0), 0..tree.size());
+        }
+    }
+
+    #[test]
+    fn hashes_and_compares_the_nodes() {
+        let mut changed = tree();
+        changed.set_dirty(false);
+        changed.replace_subtree(4..6, &[Node::Constant(2.5)]);
+
+        assert!(changed.is_dirty());
+        assert_eq!(tree().chromosome_hash(), tree().chromosome_hash());
+        assert_ne!(tree().chromosome_hash(), changed.chromosome_hash());
+
+        // sub(add(x, 2), 2.5): the constant replaces the negation and y is left over.
+        assert_eq!(tree().distance(&changed), Some(2.0));
+        assert_eq!(changed.distance(&tree()), Some(2.0));
+        assert_eq!(tree().distance(&tree()), Some(0.0));
+
+        let mut other_constant = changed.clone();
+        other_constant.set_gene(4, Node::Constant(-2.5));
+        assert_ne!(changed.chromosome_hash(), other_constant.chromosome_hash());
+    }
+}
@@ -0,0 +1,211 @@
+use rand::Rng;
+
+use super::{Node, TreeValue};
+
+/// A function of a [`PrimitiveSet`]: its name, used to print the trees, the number of arguments
+/// and the function itself.
+#[derive(Debug, Clone)]
+pub struct PrimitiveFunction<V> {
+    pub name: String,
+    pub arity: usize,
+    pub apply: fn(&[V]) -> V,
+}
+
+/// # Primitive Set
+///
+/// The building blocks of the [`Tree`](super::Tree) individuals: the functions, which are the
+/// inner nodes, and the terminals, which are the leaves. The terminals can be input variables,
+/// constants, and an ephemeral random constant, which draws a new constant every time it's used.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// let primitives = PrimitiveSet::<f64>::new()
+///     .with_function("add", 2, |args| args[0] + args[1])
+///     .with_function("mul", 2, |args| args[0] * args[1])
+///     .with_function("neg", 1, |args| -args[0])
+///     .with_variable("x")
+///     .with_constant(1.0)
+///     .with_ephemeral_constant(|u| (u * 10.0).round() - 5.0);
+///
+/// let tree = Tree::new(vec![
+///     Node::Function { index: 0, arity: 2 },
+///     Node::Variable(0),
+///     Node::Constant(1.0),
+/// ]);
+///
+/// assert_eq!(tree.evaluate(&primitives, &[2.0]), 3.0);
+/// assert_eq!(tree.to_expression(&primitives), "add(x, 1)");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrimitiveSet<V> {
+    functions: Vec<PrimitiveFunction<V>>,
+    variables: Vec<String>,
+    constants: Vec<V>,
+    ephemeral: Option<fn(f64) -> V>,
+}
+
+impl<V> Default for PrimitiveSet<V> {
+    fn default() -> Self {
+        Self {
+            functions: Vec::new(),
+            variables: Vec::new(),
+            constants: Vec::new(),
+            ephemeral: None,
+        }
+    }
+}
+
+impl<V: TreeValue> PrimitiveSet<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a function with `arity` arguments. It's referenced by the trees by the order it was
+    /// added.
+    pub fn with_function(
+        mut self,
+        name: impl Into<String>,
+        arity: usize,
+        apply: fn(&[V]) -> V,
+    ) -> Self {
+        self.functions.push(PrimitiveFunction {
+            name: name.into(),
+            arity,
+            apply,
+        });
+        self
+    }
+
+    /// Adds an input variable. The variables receive the values passed to
+    /// [`Tree::evaluate`](super::Tree::evaluate) in the order they were added.
+    pub fn with_variable(mut self, name: impl Into<String>) -> Self {
+        self.variables.push(name.into());
+        self
+    }
+
+    /// Adds a constant terminal.
+    pub fn with_constant(mut self, value: V) -> Self {
+        self.constants.push(value);
+        self
+    }
+
+    /// Sets the ephemeral random constant. Every time it's chosen as a terminal, `generate`
+    /// receives a random number in `[0, 1)` and returns the value of the new constant.
+    pub fn with_ephemeral_constant(mut self, generate: fn(f64) -> V) -> Self {
+        self.ephemeral = Some(generate);
+        self
+    }
+
+    pub fn functions(&self) -> &Vec<PrimitiveFunction<V>> {
+        &self.functions
+    }
+
+    pub fn variables(&self) -> &Vec<String> {
+        &self.variables
+    }
+
+    fn terminals_count(&self) -> usize {
+        self.variables.len() + self.constants.len() + self.ephemeral.is_some() as usize
+    }
+
+    /// Returns a random terminal.
+    ///
+    /// # Panics
+    ///
+    /// If the set has no terminals, as no tree can be built without them.
+    pub fn random_terminal<R: Rng + ?Sized>(&self, rng: &mut R) -> Node<V> {
+        let count = self.terminals_count();
+        assert!(count > 0, "The primitive set has no terminals");
+
+        let choice = rng.gen_range(0..count);
+
+        if choice < self.variables.len() {
+            Node::Variable(choice)
+        } else if choice - self.variables.len() < self.constants.len() {
+            Node::Constant(self.constants[choice - self.variables.len()])
+        } else {
+            Node::Constant((self.ephemeral.unwrap())(rng.gen()))
+        }
+    }
+
+    /// Returns a random function, or `None` if the set has no functions.
+    pub fn random_function<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Node<V>> {
+        if self.functions.is_empty() {
+            return None;
+        }
+
+        let index = rng.gen_range(0..self.functions.len());
+
+        Some(Node::Function {
+            index,
+            arity: self.functions[index].arity,
+        })
+    }
+
+    /// Returns a random function with the given arity, or `None` if the set has none.
+    pub fn random_function_with_arity<R: Rng + ?Sized>(
+        &self,
+        arity: usize,
+        rng: &mut R,
+    ) -> Option<Node<V>> {
+        let candidates: Vec<usize> = (0..self.functions.len())
+            .filter(|&index| self.functions[index].arity == arity)
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        Some(Node::Function {
+            index: candidates[rng.gen_range(0..candidates.len())],
+            arity,
+        })
+    }
+
+    /// Generates a random tree, in prefix order, where every terminal is at `depth`.
+    pub fn full<R: Rng + ?Sized>(&self, depth: usize, rng: &mut R) -> Vec<Node<V>> {
+        let mut nodes = Vec::new();
+        self.generate(depth, true, rng, &mut nodes);
+        nodes
+    }
+
+    /// Generates a random tree, in prefix order, with terminals at any depth up to `depth`.
+    pub fn grow<R: Rng + ?Sized>(&self, depth: usize, rng: &mut R) -> Vec<Node<V>> {
+        let mut nodes = Vec::new();
+        self.generate(depth, false, rng, &mut nodes);
+        nodes
+    }
+
+    fn generate<R: Rng + ?Sized>(
+        &self,
+        depth: usize,
+        full: bool,
+        rng: &mut R,
+        nodes: &mut Vec<Node<V>>,
+    ) {
+        assert!(self.terminals_count() > 0, "The primitive set has no terminals");
+
+        let terminal_probability =
+            self.terminals_count() as f64 / (self.terminals_count() + self.functions.len()) as f64;
+
+        let function = if depth == 0 || (!full && rng.gen_bool(terminal_probability)) {
+            None
+        } else {
+            self.random_function(rng)
+        };
+
+        match function {
+            Some(node) => {
+                nodes.push(node);
+
+                for _ in 0..node.arity() {
+                    self.generate(depth - 1, full, rng, nodes);
+                }
+            }
+            None => nodes.push(self.random_terminal(rng)),
+        }
+    }
+}
@@ -0,0 +1,503 @@
+use rand::seq::SliceRandom;
+use rand::{RngCore, SeedableRng};
+use rayon::prelude::*;
+
+use crate::experiment_runner::ExperimentRunner;
+use crate::parameter_sweep::{ParameterSpace, Parameters};
+use crate::statistics::friedman_test;
+use crate::{
+    evolution_builder::EvolutionBuilder, fitness::Fitness, population::Individual,
+    rng::EvolutionRng,
+};
+
+/// # Racing Tuner
+///
+/// Tunes the parameters of an evolution by iterated racing, in the spirit of irace and F-Race.
+/// Each iteration samples configurations from a [`ParameterSpace`], the first ones at random and
+/// the next ones around the best configurations found so far, the elites, and races them: every
+/// configuration still in the race runs once on a problem instance, and once enough instances
+/// were run, the ones that a Friedman test finds to be worse than the best are eliminated. The
+/// survivors of the race become the elites of the next iteration.
+///
+/// The problem instances are fitness functions, set with [`RacingTuner::with_instance`], run one
+/// after the other. Without them, every race runs the fitness set by the closure that builds the
+/// evolution. Each instance is run with a new seed, shared by all the configurations.
+///
+/// The whole tuning runs at most `budget` evolutions, the same evolution run by the
+/// [`ExperimentRunner`], split evenly between the iterations.
+///
+/// # Example
+///
+/// ```no_run
+/// use evolutionary::prelude::*;
+/// # #[derive(Clone)]
+/// # struct SmallInstance;
+/// # impl Fitness<IntPerm> for SmallInstance {
+/// #    fn calculate_fitness(&self, individual: &IntPerm) -> f64 { 0.0 }
+/// # }
+/// # #[derive(Clone)]
+/// # struct LargeInstance;
+/// # impl Fitness<IntPerm> for LargeInstance {
+/// #    fn calculate_fitness(&self, individual: &IntPerm) -> f64 { 0.0 }
+/// # }
+///
+/// let space = ParameterSpace::new()
+///     .with_values("population_size", [50u32, 100, 200])
+///     .with_values("mutation_rate", [0.01, 0.02, 0.05, 0.1])
+///     .with_values("tournament_size", [2u32, 3, 4, 6])
+///     .with_values("crossover", ["pmx", "cycle"]);
+///
+/// let tuning = RacingTuner::new(space, 2000)
+///     .with_instance(SmallInstance)
+///     .with_instance(LargeInstance)
+///     .with_seed(42)
+///     .tune(|parameters| {
+///         let builder = EvolutionBuilder::new(
+///             parameters.number("population_size").unwrap() as u32,
+///             30,
+///             GeneCod::IntPerm,
+///             (),
+///         )
+///         .with_fitness(SmallInstance)
+///         .with_selection(TournamentSelection::new(
+///             parameters.number("tournament_size").unwrap() as usize,
+///             1.0,
+///         ))
+///         .with_mutation(SwapMutation::new(parameters.number("mutation_rate").unwrap()))
+///         .with_stop(StopCondition::MaxEvaluations(20_000));
+///
+///         match parameters.choice("crossover") {
+///             Some("pmx") => builder.with_crossover(PartiallyMappedCrossover::default()),
+///             _ => builder.with_crossover(CycleCrossover::default()),
+///         }
+///     })
+///     .unwrap();
+///
+/// println!("Elite: {}", tuning.elite);
+/// let mut evolution = tuning.builder.build().unwrap();
+/// ```
+pub struct RacingTuner<T: Individual> {
+    space: ParameterSpace,
+    budget: u32,
+    instances: Vec<Box<dyn Fitness<T>>>,
+    iterations: u32,
+    configurations: Option<usize>,
+    first_test: usize,
+    significance: f64,
+    elites: usize,
+    seed: Option<u64>,
+}
+
+/// A configuration that survived a race, with its mean rank over the instances of the race, 1
+/// being the best.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RacedConfiguration {
+    pub parameters: Parameters,
+    /// The number of instances the configuration ran in its last race.
+    pub instances: usize,
+    /// NaN when the race couldn't run any instance.
+    pub mean_rank: f64,
+}
+
+/// The result of a [`RacingTuner`].
+pub struct Tuning<T: Individual> {
+    /// The parameters of the best configuration.
+    pub elite: Parameters,
+    /// The evolution of the best configuration, as built by the closure passed to
+    /// [`RacingTuner::tune`].
+    pub builder: EvolutionBuilder<T>,
+    /// The elites of the last iteration, from the best.
+    pub elites: Vec<RacedConfiguration>,
+    /// The number of evolutions run.
+    pub runs: u32,
+}
+
+impl<T: Individual> RacingTuner<T> {
+    pub fn new(space: ParameterSpace, budget: u32) -> Self {
+        Self {
+            space,
+            budget,
+            instances: Vec::new(),
+            iterations: 3,
+            configurations: None,
+            first_test: 5,
+            significance: 0.05,
+            elites: 3,
+            seed: None,
+        }
+    }
+
+    /// Adds a problem instance the configurations are raced on.
+    pub fn with_instance<F: Fitness<T>>(mut self, fitness: F) -> Self {
+        self.instances.push(Box::new(fitness));
+        self
+    }
+
+    /// Sets the number of iterations of sampling and racing. Defaults to 3.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets the number of configurations raced in each iteration, elites included. Defaults to
+    /// the budget of the iteration divided by the instances run before the first test plus the
+    /// iteration, as irace does, so the later iterations race fewer configurations for longer.
+    pub fn with_configurations(mut self, configurations: usize) -> Self {
+        self.configurations = Some(configurations);
+        self
+    }
+
+    /// Sets the number of instances every configuration runs before the first elimination.
+    /// Defaults to 5.
+    pub fn with_first_test(mut self, instances: usize) -> Self {
+        self.first_test = instances;
+        self
+    }
+
+    /// Sets the significance level of the tests that eliminate the configurations. Defaults to
+    /// 0.05.
+    pub fn with_significance(mut self, significance: f64) -> Self {
+        self.significance = significance;
+        self
+    }
+
+    /// Sets the maximum number of configurations kept as elites between the iterations. Defaults
+    /// to 3.
+    pub fn with_elites(mut self, elites: usize) -> Self {
+        self.elites = elites;
+        self
+    }
+
+    /// Seeds the sampling of the configurations and the seeds of the instances.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Tunes the evolutions built by `build` for each configuration and returns the best one.
+    /// The configurations of a race run in parallel.
+    pub fn tune<F>(&self, build: F) -> Result<Tuning<T>, String>
+    where
+        F: Fn(&Parameters) -> EvolutionBuilder<T>,
+    {
+        self.space.check()?;
+        if self.first_test == 0 || self.elites == 0 {
+            return Err("The first test and the elites must be at least 1".to_string());
+        }
+        if (self.budget as usize) < 2 * self.first_test {
+            return Err(format!(
+                "A budget of {} runs can't race two configurations on {} instances",
+                self.budget, self.first_test
+            ));
+        }
+
+        let mut rng = self
+            .seed
+            .map_or_else(EvolutionRng::from_entropy, EvolutionRng::seed_from_u64);
+        let mut elites: Vec<RacedConfiguration> = Vec::new();
+        let (mut runs, mut instance) = (0, 0);
+
+        for iteration in 0..self.iterations {
+            let budget = ((self.budget - runs) / (self.iterations - iteration)) as usize;
+            let configurations = self
+                .configurations
+                .unwrap_or(budget / (self.first_test + iteration as usize))
+                .min(budget / self.first_test)
+                .min(self.space.len());
+            if configurations < 2 {
+                break;
+            }
+
+            let candidates = self.sample(configurations, &elites, iteration, &mut rng);
+            let race = self.race(candidates, budget, &build, &mut rng, &mut instance)?;
+
+            runs += race.runs;
+            if race.survivors.is_empty() {
+                break;
+            }
+            elites = race.survivors;
+            elites.truncate(self.elites);
+
+            if race.cancelled {
+                break;
+            }
+        }
+
+        let elite = elites
+            .first()
+            .ok_or("No configuration was raced")?
+            .parameters
+            .clone();
+
+        Ok(Tuning {
+            builder: build(&elite),
+            elite,
+            elites,
+            runs,
+        })
+    }
+
+    /// The elites and new configurations up to `configurations`. The first iteration samples
+    /// them at random and the next ones near an elite, chosen with a probability that decreases
+    /// with its rank. Later iterations keep more of the values of the elites.
+    fn sample(
+        &self,
+        configurations: usize,
+        elites: &[RacedConfiguration],
+        iteration: u32,
+        rng: &mut EvolutionRng,
+    ) -> Vec<Parameters> {
+        let mut candidates: Vec<Parameters> = elites
+            .iter()
+            .map(|elite| elite.parameters.clone())
+            .collect();
+        if elites.is_empty() {
+            return self.space.sample(configurations, rng);
+        }
+
+        let keep = 1.0 - 1.0 / (iteration as f64 + 1.0);
+        let ranks: Vec<usize> = (0..elites.len()).collect();
+
+        // Gives up on the duplicates after a while, as when the space is almost exhausted.
+        for _ in 0..configurations * 10 {
+            if candidates.len() >= configurations {
+                break;
+            }
+
+            let elite = ranks
+                .choose_weighted(rng, |&rank| elites.len() - rank)
+                .unwrap();
+            let candidate = self.space.neighbour(&elites[*elite].parameters, keep, rng);
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        candidates
+    }
+
+    /// Races the candidates for at most `budget` runs, one instance after the other, and returns
+    /// the survivors ranked from the best. Fails when the candidates don't share the objective, as
+    /// they're ranked by it, or when an evolution can't be built or fails to run.
+    fn race<F>(
+        &self,
+        candidates: Vec<Parameters>,
+        budget: usize,
+        build: &F,
+        rng: &mut EvolutionRng,
+        instance: &mut usize,
+    ) -> Result<Race, String>
+    where
+        F: Fn(&Parameters) -> EvolutionBuilder<T>,
+    {
+        let builders: Vec<EvolutionBuilder<T>> = candidates.iter().map(build).collect();
+
+        let objective = builders[0].objective();
+        if builders
+            .iter()
+            .any(|builder| builder.objective() != objective)
+        {
+            return Err("The evolutions of a tuning should share the objective".to_string());
+        }
+
+        let mut alive: Vec<usize> = (0..candidates.len()).collect();
+        // The fitness of every candidate on each instance, negated when maximizing so the
+        // lowest ranks are the best. NaN once the candidate was eliminated.
+        let mut results: Vec<Vec<f64>> = Vec::new();
+        let (mut runs, mut cancelled) = (0, false);
+
+        while alive.len() > 1 && runs + alive.len() <= budget {
+            let seed = rng.next_u64();
+            let fitness = (!self.instances.is_empty())
+                .then(|| &self.instances[*instance % self.instances.len()]);
+            *instance += 1;
+
+            let fitnesses: Result<Option<Vec<f64>>, String> = alive
+                .par_iter()
+                .map(|&candidate| {
+                    let mut builder = builders[candidate].clone().with_seed(seed);
+                    if let Some(fitness) = fitness {
+                        builder =
+                            builder.with_boxed_fitness(dyn_clone::clone_box(fitness.as_ref()));
+                    }
+
+                    let results = ExperimentRunner::run_evolutions(&builder, 1)?;
+                    Ok(results.last().map(|result| result.best_found.get_fitness()))
+                })
+                .collect::<Result<Vec<_>, String>>()
+                .map(|fitnesses| fitnesses.into_iter().collect());
+
+            // A run didn't start, as the cancellation token was cancelled.
+            let Some(fitnesses) = fitnesses? else {
+                cancelled = true;
+                break;
+            };
+            runs += alive.len();
+
+            let mut block = vec![f64::NAN; candidates.len()];
+            for (&candidate, fitness) in alive.iter().zip(fitnesses) {
+                block[candidate] = -objective.oriented(fitness);
+            }
+            results.push(block);
+
+            if results.len() >= self.first_test {
+                let test = friedman_test(&blocks_of(&results, &alive));
+
+                if test.p_value < self.significance {
+                    let best = best_of(&test.rank_sums);
+                    alive = alive
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| test.post_hoc(best, *i) >= self.significance)
+                        .map(|(_, &candidate)| candidate)
+                        .collect();
+                }
+            }
+        }
+
+        let mut survivors: Vec<RacedConfiguration> = if results.is_empty() {
+            alive
+                .iter()
+                .map(|&candidate| RacedConfiguration {
+                    parameters: candidates[candidate].clone(),
+                    instances: 0,
+                    mean_rank: f64::NAN,
+                })
+                .collect()
+        } else {
+            let test = friedman_test(&blocks_of(&results, &alive));
+            alive
+                .iter()
+                .zip(test.rank_sums)
+                .map(|(&candidate, rank_sum)| RacedConfiguration {
+                    parameters: candidates[candidate].clone(),
+                    instances: results.len(),
+                    mean_rank: rank_sum / results.len() as f64,
+                })
+                .collect()
+        };
+        survivors.sort_by(|a, b| a.mean_rank.total_cmp(&b.mean_rank));
+
+        Ok(Race {
+            survivors,
+            runs: runs as u32,
+            cancelled,
+        })
+    }
+}
+
+struct Race {
+    survivors: Vec<RacedConfiguration>,
+    runs: u32,
+    cancelled: bool,
+}
+
+/// The results of the `alive` candidates on each instance.
+fn blocks_of(results: &[Vec<f64>], alive: &[usize]) -> Vec<Vec<f64>> {
+    results
+        .iter()
+        .map(|block| alive.iter().map(|&candidate| block[candidate]).collect())
+        .collect()
+}
+
+fn best_of(rank_sums: &[f64]) -> usize {
+    (0..rank_sums.len())
+        .min_by(|&a, &b| rank_sums[a].total_cmp(&rank_sums[b]))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::{ones_builder, OnesFitness};
+
+    #[derive(Clone)]
+    struct ZerosFitness;
+
+    impl Fitness<Bin> for ZerosFitness {
+        fn calculate_fitness(&self, individual: &Bin) -> f64 {
+            individual
+                .get_chromosome()
+                .iter()
+                .filter(|&&gene| !gene)
+                .count() as f64
+        }
+    }
+
+    fn build(parameters: &Parameters) -> EvolutionBuilder<Bin> {
+        let builder = ones_builder(parameters.number("population_size").unwrap() as u32, 30)
+            .with_stop(StopCondition::MaxGenerations(40));
+
+        match parameters.choice("mutation") {
+            Some("none") => builder.with_mutation(DoNothingMutation),
+            _ => builder.with_mutation(BitFlipMutation::default()),
+        }
+    }
+
+    #[test]
+    fn finds_the_elite() {
+        let space = ParameterSpace::new()
+            .with_values("population_size", [4u32, 20])
+            .with_values("mutation", ["none", "bit flip"]);
+
+        let tuning = RacingTuner::new(space, 120)
+            .with_instance(OnesFitness)
+            .with_instance(ZerosFitness)
+            .with_iterations(2)
+            .with_seed(5)
+            .tune(build)
+            .unwrap();
+
+        assert_eq!(
+            tuning.elite.to_string(),
+            "population_size=20, mutation=bit flip"
+        );
+        assert_eq!(tuning.elites[0].parameters, tuning.elite);
+        assert!(tuning.elites.len() <= 3);
+        assert!(tuning.runs <= 120);
+        assert!(tuning.builder.build().is_ok());
+    }
+
+    #[test]
+    fn checks_the_settings() {
+        let space = ParameterSpace::new().with_values("mutation", ["none", "bit flip"]);
+
+        let error = |tuner: RacingTuner<Bin>| tuner.tune(build).err().unwrap();
+
+        assert_eq!(
+            error(RacingTuner::new(ParameterSpace::new(), 100)),
+            "The parameter space has no parameters"
+        );
+        assert_eq!(
+            error(RacingTuner::new(
+                space.clone().with_values("elitism", Vec::<u32>::new()),
+                100
+            )),
+            "The parameter <elitism> has no values"
+        );
+        assert_eq!(
+            error(RacingTuner::new(space, 9)),
+            "A budget of 9 runs can't race two configurations on 5 instances"
+        );
+    }
+
+    #[test]
+    fn requires_a_shared_objective() {
+        let space = ParameterSpace::new().with_values("mutation", ["none", "bit flip"]);
+
+        let tuning = RacingTuner::new(space, 100).tune(|parameters| {
+            let objective = match parameters.choice("mutation") {
+                Some("none") => Objective::Minimize,
+                _ => Objective::Maximize,
+            };
+
+            EvolutionBuilder::<Bin>::new(10, 20, GeneCod::Bin, ()).with_objective(objective)
+        });
+
+        assert_eq!(
+            tuning.err().unwrap(),
+            "The evolutions of a tuning should share the objective"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use rand::seq::index::sample;
+use serde_derive::Deserialize;
+
+use crate::context::Context;
+use crate::population::Individual;
+
+use super::Replacement;
+
+/// # Generation Gap Replacement
+///
+/// De Jong's generation gap: every generation, a fraction `gap` of the population is replaced by
+/// offsprings, at random positions. A `gap` of 1 is the generational replacement, and the smaller
+/// it is, the more the generations overlap.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenerationGapReplacement {
+    pub gap: f64,
+}
+
+impl Default for GenerationGapReplacement {
+    fn default() -> Self {
+        Self { gap: 0.5 }
+    }
+}
+
+impl<T: Individual> Replacement<T> for GenerationGapReplacement {
+    fn offspring_count(&self, population_size: usize) -> usize {
+        ((self.gap * population_size as f64).round() as usize).clamp(1, population_size)
+    }
+
+    fn replace(
+        &self,
+        mut population: Vec<T>,
+        _parents: Vec<T>,
+        offsprings: Vec<T>,
+        context: &mut Context<T>,
+    ) -> Vec<T> {
+        let positions = sample(context.rng, population.len(), offsprings.len());
+
+        for (index, offspring) in positions.into_iter().zip(offsprings) {
+            population[index] = offspring;
+        }
+
+        population
+    }
+
+    fn check(&self, _population_size: usize) -> Result<(), String> {
+        if self.gap > 0.0 && self.gap <= 1.0 {
+            Ok(())
+        } else {
+            Err(format!("The generation gap must be in (0, 1], got {}", self.gap))
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use crate::context::Context;
+use crate::population::Individual;
+
+use super::Replacement;
+
+/// # Generational Replacement
+///
+/// The offsprings replace the whole population every generation, and only the elitists of the
+/// evolution survive. It's the default replacement.
+#[derive(Clone, Default)]
+pub struct GenerationalReplacement;
+
+impl<T: Individual> Replacement<T> for GenerationalReplacement {
+    fn offspring_count(&self, population_size: usize) -> usize {
+        population_size
+    }
+
+    fn replace(
+        &self,
+        _population: Vec<T>,
+        _parents: Vec<T>,
+        offsprings: Vec<T>,
+        _context: &mut Context<T>,
+    ) -> Vec<T> {
+        offsprings
+    }
+}
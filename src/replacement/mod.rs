@@ -0,0 +1,128 @@
+use dyn_clone::DynClone;
+
+use crate::context::Context;
+use crate::population::Individual;
+
+mod generation_gap_replacement;
+mod generational_replacement;
+mod mu_comma_lambda_replacement;
+mod mu_plus_lambda_replacement;
+mod steady_state_replacement;
+
+pub use generation_gap_replacement::GenerationGapReplacement;
+pub use generational_replacement::GenerationalReplacement;
+pub use mu_comma_lambda_replacement::MuCommaLambdaReplacement;
+pub use mu_plus_lambda_replacement::MuPlusLambdaReplacement;
+pub use steady_state_replacement::{SteadyStateReplacement, SteadyStateTarget};
+
+/// Trait that defines how the offsprings of a generation make it into the population. You can
+/// implement your own replacement strategy by implementing this trait and passing it to
+/// [`EvolutionBuilder::with_replacement`](crate::EvolutionBuilder::with_replacement).
+///
+/// Each generation, the evolution selects `offspring_count` parents, crosses and mutates them into
+/// as many offsprings, calculates their fitness and calls `replace` to get the next population.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+///
+/// /// Keeps only the offsprings, like the `GenerationalReplacement`.
+/// #[derive(Clone)]
+/// struct OnlyOffsprings;
+///
+/// impl<T: Individual> Replacement<T> for OnlyOffsprings {
+///     fn offspring_count(&self, population_size: usize) -> usize {
+///         population_size
+///     }
+///
+///     fn replace(
+///         &self,
+///         _population: Vec<T>,
+///         _parents: Vec<T>,
+///         offsprings: Vec<T>,
+///         _context: &mut Context<T>,
+///     ) -> Vec<T> {
+///         offsprings
+///     }
+/// }
+/// ```
+pub trait Replacement<T: Individual>: 'static + DynClone + Send + Sync {
+    /// How many offsprings are created each generation for a population of `population_size`.
+    fn offspring_count(&self, population_size: usize) -> usize;
+
+    /// Returns the next population from the current `population` and the `offsprings`, which
+    /// already have their fitness. `parents[i]` is the selected individual that `offsprings[i]`
+    /// was created from. Compare the individuals with [`Context::compare`] and draw all the
+    /// randomness from `context.rng`.
+    fn replace(
+        &self,
+        population: Vec<T>,
+        parents: Vec<T>,
+        offsprings: Vec<T>,
+        context: &mut Context<T>,
+    ) -> Vec<T>;
+
+    /// Whether the best individuals always survive the replacement. When they do, the elitism of
+    /// the evolution isn't applied, since it would only add copies of them.
+    fn keeps_the_best(&self) -> bool {
+        false
+    }
+
+    /// Puts the `elitists`, the best individuals before the replacement, back into the next
+    /// `population` when the replacement doesn't keep the best. By default they replace the worst
+    /// individuals.
+    fn keep_elitists(&self, population: &mut Vec<T>, elitists: Vec<T>, context: &mut Context<T>) {
+        replace_worsts(population, elitists, context);
+    }
+
+    /// Checks if the replacement can be used with a population of `population_size`. It's called
+    /// when the evolution is built.
+    fn check(&self, _population_size: usize) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Replaces the worst individuals of the `population` with the `individuals`.
+pub(crate) fn replace_worsts<T: Individual>(
+    population: &mut [T],
+    individuals: Vec<T>,
+    context: &Context<T>,
+) {
+    let worsts = worst_indexes(population, individuals.len(), context);
+
+    for (index, individual) in worsts.into_iter().zip(individuals) {
+        population[index] = individual;
+    }
+}
+
+/// Returns the indexes of the `count` worst individuals of the `population`, from the worst. Of
+/// the equally bad ones, the last comes first.
+pub(crate) fn worst_indexes<T: Individual>(
+    population: &[T],
+    count: usize,
+    context: &Context<T>,
+) -> Vec<usize> {
+    let mut taken = vec![false; population.len()];
+
+    (0..count)
+        .map_while(|_| {
+            // Unlike sorting, finding the minimum doesn't need a total order, so a NaN fitness
+            // can't make it panic.
+            let worst = (0..population.len())
+                .rev()
+                .filter(|&index| !taken[index])
+                .min_by(|&a, &b| context.compare(&population[a], &population[b]))?;
+
+            taken[worst] = true;
+            Some(worst)
+        })
+        .collect()
+}
+
+/// Returns the indexes of the `population` from the best to the worst.
+pub(crate) fn ranked_indexes<T: Individual>(population: &[T], context: &Context<T>) -> Vec<usize> {
+    let mut indexes: Vec<usize> = (0..population.len()).collect();
+    indexes.sort_by(|&a, &b| context.compare(&population[b], &population[a]));
+    indexes
+}
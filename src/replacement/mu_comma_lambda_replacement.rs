@@ -0,0 +1,87 @@
+use serde_derive::Deserialize;
+
+use crate::context::Context;
+use crate::population::Individual;
+
+use super::{ranked_indexes, Replacement};
+
+/// # (μ, λ) Replacement
+///
+/// Every generation, `lambda` offsprings are created from the μ individuals of the population,
+/// and the best μ offsprings form the next population, so no individual lives more than one
+/// generation. `lambda` must be at least the population size.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MuCommaLambdaReplacement {
+    pub lambda: usize,
+}
+
+impl<T: Individual> Replacement<T> for MuCommaLambdaReplacement {
+    fn offspring_count(&self, _population_size: usize) -> usize {
+        self.lambda
+    }
+
+    fn replace(
+        &self,
+        population: Vec<T>,
+        _parents: Vec<T>,
+        offsprings: Vec<T>,
+        context: &mut Context<T>,
+    ) -> Vec<T> {
+        let ranking = ranked_indexes(&offsprings, context);
+
+        ranking
+            .into_iter()
+            .take(population.len())
+            .map(|index| offsprings[index].clone())
+            .collect()
+    }
+
+    fn check(&self, population_size: usize) -> Result<(), String> {
+        if self.lambda < population_size {
+            return Err(format!(
+                "The (μ, λ) replacement needs at least {} offsprings, got {}",
+                population_size, self.lambda
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    fn individuals(fitnesses: &[f64]) -> Vec<Bin> {
+        fitnesses
+            .iter()
+            .map(|&fitness| {
+                let mut individual = Bin::new(vec![true]);
+                individual.set_fitness(fitness);
+                individual
+            })
+            .collect()
+    }
+
+    fn fitnesses(population: &[Bin]) -> Vec<f64> {
+        population.iter().map(|individual| individual.get_fitness()).collect()
+    }
+
+    #[test]
+    fn keeps_the_best_offsprings() {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, Objective::Minimize);
+
+        let next = MuCommaLambdaReplacement { lambda: 4 }.replace(
+            individuals(&[0.0, 1.0, 2.0]),
+            individuals(&[1.0, 1.0, 1.0, 1.0]),
+            individuals(&[5.0, 7.0, 3.0, 4.0]),
+            &mut context,
+        );
+
+        assert_eq!(fitnesses(&next), vec![3.0, 4.0, 5.0]);
+    }
+}
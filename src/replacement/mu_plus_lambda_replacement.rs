@@ -0,0 +1,89 @@
+use serde_derive::Deserialize;
+
+use crate::context::Context;
+use crate::population::Individual;
+
+use super::{ranked_indexes, Replacement};
+
+/// # (μ + λ) Replacement
+///
+/// Every generation, `lambda` offsprings are created from the μ individuals of the population,
+/// and the best μ of the parents and offsprings together form the next population. The best
+/// individuals are never lost, so the elitism isn't needed.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MuPlusLambdaReplacement {
+    pub lambda: usize,
+}
+
+impl<T: Individual> Replacement<T> for MuPlusLambdaReplacement {
+    fn offspring_count(&self, _population_size: usize) -> usize {
+        self.lambda
+    }
+
+    fn replace(
+        &self,
+        population: Vec<T>,
+        _parents: Vec<T>,
+        offsprings: Vec<T>,
+        context: &mut Context<T>,
+    ) -> Vec<T> {
+        let mu = population.len();
+        let candidates: Vec<T> = population.into_iter().chain(offsprings).collect();
+
+        ranked_indexes(&candidates, context)
+            .into_iter()
+            .take(mu)
+            .map(|index| candidates[index].clone())
+            .collect()
+    }
+
+    fn keeps_the_best(&self) -> bool {
+        true
+    }
+
+    fn check(&self, _population_size: usize) -> Result<(), String> {
+        if self.lambda == 0 {
+            return Err("The (μ + λ) replacement needs at least one offspring".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    fn individuals(fitnesses: &[f64]) -> Vec<Bin> {
+        fitnesses
+            .iter()
+            .map(|&fitness| {
+                let mut individual = Bin::new(vec![true]);
+                individual.set_fitness(fitness);
+                individual
+            })
+            .collect()
+    }
+
+    fn fitnesses(population: &[Bin]) -> Vec<f64> {
+        population.iter().map(|individual| individual.get_fitness()).collect()
+    }
+
+    #[test]
+    fn keeps_the_best_of_parents_and_offsprings() {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, Objective::Minimize);
+
+        let next = MuPlusLambdaReplacement { lambda: 3 }.replace(
+            individuals(&[4.0, 1.0, 6.0]),
+            individuals(&[1.0, 1.0, 1.0]),
+            individuals(&[5.0, 0.0, 2.0]),
+            &mut context,
+        );
+
+        assert_eq!(fitnesses(&next), vec![0.0, 1.0, 2.0]);
+    }
+}
@@ -0,0 +1,227 @@
+use std::cmp::Ordering;
+
+use serde_derive::Deserialize;
+
+use crate::context::Context;
+use crate::population::Individual;
+
+use super::{ranked_indexes, replace_worsts, worst_indexes, Replacement};
+
+/// Which individuals of the population the offsprings of the [`SteadyStateReplacement`] replace.
+///
+/// - `Worst`: the worst individuals, as in GENITOR. It's the default.
+/// - `Oldest`: the individuals that have been in the population for the longest, which is kept
+///   from the oldest to the newest. The elitists it replaces are put back as the oldest ones, in
+///   place of the worst individuals.
+/// - `Parent`: each offspring replaces the parent it was created from, when it's at least as good
+///   as it. A parent is found in the population by its fitness and
+///   [`chromosome_hash`](Individual::chromosome_hash), so the individuals without a hash are
+///   never replaced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum SteadyStateTarget {
+    #[default]
+    Worst,
+    Oldest,
+    Parent,
+}
+
+/// # Steady-State Replacement
+///
+/// Only a few `offsprings` are created every generation, 2 by default, and they replace as many
+/// individuals of the population, chosen by the `target`. The rest of the population carries on
+/// to the next generation.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SteadyStateReplacement {
+    pub offsprings: usize,
+    pub target: SteadyStateTarget,
+}
+
+impl Default for SteadyStateReplacement {
+    fn default() -> Self {
+        Self {
+            offsprings: 2,
+            target: SteadyStateTarget::default(),
+        }
+    }
+}
+
+impl SteadyStateReplacement {
+    pub fn new(offsprings: usize, target: SteadyStateTarget) -> Self {
+        Self { offsprings, target }
+    }
+
+    /// What tells an individual apart in the population: its chromosome hash and its fitness.
+    fn key<T: Individual>(individual: &T) -> Option<(u64, u64)> {
+        individual
+            .chromosome_hash()
+            .map(|hash| (hash, individual.get_fitness().to_bits()))
+    }
+}
+
+impl<T: Individual> Replacement<T> for SteadyStateReplacement {
+    fn offspring_count(&self, _population_size: usize) -> usize {
+        self.offsprings
+    }
+
+    fn replace(
+        &self,
+        mut population: Vec<T>,
+        parents: Vec<T>,
+        offsprings: Vec<T>,
+        context: &mut Context<T>,
+    ) -> Vec<T> {
+        match self.target {
+            SteadyStateTarget::Worst => {
+                let worsts = ranked_indexes(&population, context).into_iter().rev();
+
+                for (index, offspring) in worsts.zip(offsprings) {
+                    population[index] = offspring;
+                }
+            }
+            SteadyStateTarget::Oldest => {
+                population.drain(..offsprings.len().min(population.len()));
+                population.extend(offsprings);
+            }
+            SteadyStateTarget::Parent => {
+                let mut keys: Vec<_> = population.iter().map(Self::key).collect();
+
+                for (parent, offspring) in parents.iter().zip(offsprings) {
+                    let Some(parent) = Self::key(parent) else {
+                        continue;
+                    };
+
+                    if let Some(index) = keys.iter().position(|&key| key == Some(parent)) {
+                        if context.compare(&offspring, &population[index]) != Ordering::Less {
+                            keys[index] = Self::key(&offspring);
+                            population[index] = offspring;
+                        }
+                    }
+                }
+            }
+        }
+
+        population
+    }
+
+    fn keeps_the_best(&self) -> bool {
+        self.target != SteadyStateTarget::Oldest
+    }
+
+    fn keep_elitists(&self, population: &mut Vec<T>, elitists: Vec<T>, context: &mut Context<T>) {
+        if self.target != SteadyStateTarget::Oldest {
+            return replace_worsts(population, elitists, context);
+        }
+
+        // The elitists that were replaced for being the oldest go back as the oldest ones, and as
+        // many of the worst individuals are dropped, so the population stays in order of age.
+        let keys: Vec<_> = population.iter().map(Self::key).collect();
+        let missing: Vec<T> = elitists
+            .into_iter()
+            .filter(|elitist| Self::key(elitist).is_none_or(|key| !keys.contains(&Some(key))))
+            .collect();
+
+        let mut worsts = worst_indexes(population, missing.len(), context);
+        worsts.sort_unstable_by(|a, b| b.cmp(a));
+        for index in worsts {
+            population.remove(index);
+        }
+
+        population.splice(0..0, missing);
+    }
+
+    fn check(&self, population_size: usize) -> Result<(), String> {
+        if self.offsprings == 0 || self.offsprings >= population_size {
+            return Err(format!(
+                "The steady-state replacement needs between 1 and {} offsprings, got {}",
+                population_size.saturating_sub(1),
+                self.offsprings
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+    use crate::testing::ones_builder;
+
+    fn individual(gene: bool, fitness: f64) -> Bin {
+        let mut individual = Bin::new(vec![gene]);
+        individual.set_fitness(fitness);
+        individual
+    }
+
+    fn replace(target: SteadyStateTarget) -> Vec<f64> {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        let population = vec![individual(true, 3.0), individual(false, 1.0), individual(true, 2.0)];
+        let parents = vec![individual(true, 3.0), individual(true, 2.0)];
+        let offsprings = vec![individual(false, 0.0), individual(true, 5.0)];
+
+        SteadyStateReplacement::new(2, target)
+            .replace(population, parents, offsprings, &mut context)
+            .iter()
+            .map(|individual| individual.get_fitness())
+            .collect()
+    }
+
+    #[test]
+    fn finds_the_parents_by_their_chromosome() {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        // The parent shares its fitness with another individual.
+        let population = vec![individual(false, 2.0), individual(true, 2.0)];
+        let parents = vec![individual(true, 2.0)];
+        let offsprings = vec![individual(false, 4.0)];
+
+        let population = SteadyStateReplacement::new(1, SteadyStateTarget::Parent)
+            .replace(population, parents, offsprings, &mut context);
+
+        assert_eq!(population[0].get_fitness(), 2.0);
+        assert_eq!(population[1].get_fitness(), 4.0);
+    }
+
+    #[test]
+    fn replaces_the_target_individuals() {
+        assert_eq!(replace(SteadyStateTarget::Worst), vec![3.0, 0.0, 5.0]);
+        assert_eq!(replace(SteadyStateTarget::Oldest), vec![2.0, 0.0, 5.0]);
+        assert_eq!(replace(SteadyStateTarget::Parent), vec![3.0, 1.0, 5.0]);
+    }
+
+    #[test]
+    fn keeps_the_elitists_in_order_of_age() {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        let oldest = SteadyStateReplacement::new(2, SteadyStateTarget::Oldest);
+        let mut population =
+            vec![individual(false, 2.0), individual(true, 0.0), individual(true, 5.0)];
+        let elitists = vec![individual(true, 3.0), individual(true, 5.0)];
+        oldest.keep_elitists(&mut population, elitists, &mut context);
+
+        let fitnesses: Vec<f64> = population.iter().map(Bin::get_fitness).collect();
+        assert_eq!(fitnesses, vec![3.0, 2.0, 5.0]);
+    }
+
+    #[test]
+    fn the_oldest_target_keeps_the_best() {
+        let mut evolution = ones_builder(20, 16)
+            .with_replacement(SteadyStateReplacement::new(4, SteadyStateTarget::Oldest))
+            .with_stop_condition(|_, iterations, _| iterations >= 50)
+            .with_seed(1)
+            .build()
+            .unwrap();
+        evolution.run().unwrap();
+
+        let bests = &evolution.metrics.best_fitnesses;
+        assert!(bests.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert_eq!(evolution.current_population().len(), 20);
+    }
+}
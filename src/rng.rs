@@ -0,0 +1,138 @@
+use rand::{Error, RngCore, SeedableRng};
+use serde_derive::{Deserialize, Serialize};
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// # Evolution RNG
+///
+/// The random number generator that drives an [`Evolution`](crate::Evolution). It is a SplitMix64
+/// generator, so its whole state is a single `u64` and two generators created with the same seed
+/// produce the same sequence of numbers.
+///
+/// It is passed to every `Selection`, `Crossover` and `Mutation` call. Sequential operators can use
+/// it directly as any [`rand::Rng`], while parallel operators should call [`EvolutionRng::streams`]
+/// once and take one stream per individual or chunk, so the result does not depend on how rayon
+/// schedules the work.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+/// use rand::{Rng, SeedableRng};
+///
+/// let mut a = EvolutionRng::seed_from_u64(42);
+/// let mut b = EvolutionRng::seed_from_u64(42);
+///
+/// assert_eq!(a.gen::<u64>(), b.gen::<u64>());
+///
+/// let (streams_a, streams_b) = (a.streams(), b.streams());
+/// assert_eq!(streams_a.get(7).gen::<f64>(), streams_b.get(7).gen::<f64>());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvolutionRng {
+    state: u64,
+}
+
+impl EvolutionRng {
+    /// Derives a new set of independent streams from this generator, advancing it by one step.
+    pub fn streams(&mut self) -> RngStreams {
+        RngStreams {
+            seed: self.next_u64(),
+        }
+    }
+}
+
+impl RngCore for EvolutionRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        mix(self.state)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for EvolutionRng {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self {
+            state: u64::from_le_bytes(seed),
+        }
+    }
+
+    fn seed_from_u64(state: u64) -> Self {
+        Self { state }
+    }
+}
+
+/// A set of random number streams derived from an [`EvolutionRng`]. The stream returned for a given
+/// index is always the same, which allows parallel iterators to draw random numbers
+/// deterministically. Each stream is an [`EvolutionRng`] too, so the numbers of a seed don't change
+/// with the version of `rand`.
+#[derive(Clone, Copy, Debug)]
+pub struct RngStreams {
+    seed: u64,
+}
+
+impl RngStreams {
+    /// Returns the random number generator of the stream `index`.
+    pub fn get(&self, index: usize) -> EvolutionRng {
+        EvolutionRng::seed_from_u64(mix(self.seed ^ mix(index as u64)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::EvolutionRng;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = EvolutionRng::seed_from_u64(7);
+        let mut b = EvolutionRng::seed_from_u64(7);
+
+        let a_values: Vec<u64> = (0..10).map(|_| a.gen()).collect();
+        let b_values: Vec<u64> = (0..10).map(|_| b.gen()).collect();
+
+        assert_eq!(a_values, b_values);
+    }
+
+    #[test]
+    fn streams_are_reproducible_and_distinct() {
+        let streams_a = EvolutionRng::seed_from_u64(7).streams();
+        let streams_b = EvolutionRng::seed_from_u64(7).streams();
+
+        assert_eq!(streams_a.get(3).gen::<u64>(), streams_b.get(3).gen::<u64>());
+        assert_ne!(streams_a.get(3).gen::<u64>(), streams_a.get(4).gen::<u64>());
+    }
+
+    #[test]
+    fn streams_are_stable() {
+        // The numbers of a seed are part of the reproducibility promise, they must never change.
+        let streams = EvolutionRng::seed_from_u64(7).streams();
+        let values: Vec<u64> = (0..3).map(|index| streams.get(index).gen()).collect();
+
+        assert_eq!(values, vec![17527383841289775211, 14400865145721558587, 13161640495508446026]);
+    }
+}
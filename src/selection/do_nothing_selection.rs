@@ -1,10 +1,17 @@
+use crate::context::Context;
 use crate::{Individual, Selection};
 
+use super::SelectionError;
+
 #[derive(Clone)]
 pub struct DoNothingSelection;
 
 impl<T: Individual> Selection<T> for DoNothingSelection {
-    fn get_mating_pool(&self, initial_population: &Vec<T>) -> Vec<T> {
-        initial_population.clone()
+    fn get_mating_pool(
+        &self,
+        initial_population: &[T],
+        _context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        Ok(initial_population.to_vec())
     }
 }
@@ -0,0 +1,255 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::objective::Objective;
+use crate::population::Individual;
+
+use super::SelectionError;
+
+/// # Fitness Scaling
+///
+/// Turns the fitness of the population into the weights used by the fitness-proportional
+/// selections, [`RouletteSelection`](super::RouletteSelection) and
+/// [`StochasticUniversalSamplingSelection`](super::StochasticUniversalSamplingSelection). The
+/// better individual, according to the objective, always gets the larger weight.
+///
+/// - `None`: the fitness itself is the weight, so it can't be negative. When minimizing, the
+///   fitness is windowed, as a cost can't be used as a weight directly.
+/// - `Windowing`: subtracts the fitness of the worst individual, so the weights start at 0 and
+///   negative fitness is supported.
+/// - `Linear { c }`: Goldberg's linear scaling of the windowed fitness. The average keeps its
+///   weight and the best gets `c` times it, usually from 1.2 to 2.0.
+/// - `Sigma { c }`: the weight is how much the fitness is above `average - c * std_dev`, or 0 if
+///   it's below. `c` is usually 2.0.
+/// - `Boltzmann { temperature }`: the weight is `exp(fitness / temperature)`. The lower the
+///   temperature, the stronger the selection pressure.
+///
+/// When every weight ends up 0, as in a population where all the individuals have the same
+/// fitness, they are all selected with the same probability.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum FitnessScaling {
+    #[default]
+    None,
+    Windowing,
+    Linear { c: f64 },
+    Sigma { c: f64 },
+    Boltzmann { temperature: f64 },
+}
+
+impl FitnessScaling {
+    /// Returns the selection weight of each individual of the population, in the same order.
+    pub fn weights<T: Individual>(
+        &self,
+        population: &[T],
+        objective: Objective,
+    ) -> Result<Vec<f64>, SelectionError> {
+        if population.is_empty() {
+            return Err(SelectionError::EmptyPopulation);
+        }
+
+        let fitnesses: Vec<f64> = population
+            .iter()
+            .map(|individual| individual.get_fitness())
+            .collect();
+
+        if let Some(&fitness) = fitnesses.iter().find(|fitness| !fitness.is_finite()) {
+            return Err(SelectionError::InvalidFitness(fitness));
+        }
+
+        // From here on the larger the better, whatever the objective.
+        let oriented: Vec<f64> = fitnesses.iter().map(|&f| objective.oriented(f)).collect();
+
+        let weights = match *self {
+            FitnessScaling::None if objective == Objective::Maximize => {
+                if let Some(&fitness) = fitnesses.iter().find(|&&fitness| fitness < 0.0) {
+                    return Err(SelectionError::NegativeFitness(fitness));
+                }
+                fitnesses
+            }
+            FitnessScaling::None | FitnessScaling::Windowing => windowed(&oriented),
+            FitnessScaling::Linear { c } => linear(&windowed(&oriented), c)?,
+            FitnessScaling::Sigma { c } => sigma(&oriented, c)?,
+            FitnessScaling::Boltzmann { temperature } => boltzmann(&oriented, temperature)?,
+        };
+
+        if weights.iter().all(|&weight| weight == 0.0) {
+            return Ok(vec![1.0; weights.len()]);
+        }
+
+        Ok(weights)
+    }
+}
+
+fn windowed(fitnesses: &[f64]) -> Vec<f64> {
+    let worst = fitnesses.iter().copied().fold(f64::INFINITY, f64::min);
+
+    fitnesses.iter().map(|fitness| fitness - worst).collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn linear(fitnesses: &[f64], c: f64) -> Result<Vec<f64>, SelectionError> {
+    if !(c > 1.0 && c.is_finite()) {
+        return Err(SelectionError::InvalidParameter(format!(
+            "The linear scaling factor must be greater than 1, got {}",
+            c
+        )));
+    }
+
+    let avg = mean(fitnesses);
+    let max = fitnesses.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min = fitnesses.iter().copied().fold(f64::INFINITY, f64::min);
+
+    if max == avg {
+        return Ok(vec![1.0; fitnesses.len()]);
+    }
+
+    // Keeps the average and maps the best to `c * avg`, unless it makes the worst negative, in
+    // which case the worst is mapped to 0 instead.
+    let (a, b) = if min > (c * avg - max) / (c - 1.0) {
+        let delta = max - avg;
+        ((c - 1.0) * avg / delta, avg * (max - c * avg) / delta)
+    } else {
+        let delta = avg - min;
+        (avg / delta, -min * avg / delta)
+    };
+
+    Ok(fitnesses.iter().map(|f| (a * f + b).max(0.0)).collect())
+}
+
+fn sigma(fitnesses: &[f64], c: f64) -> Result<Vec<f64>, SelectionError> {
+    if !(c >= 0.0 && c.is_finite()) {
+        return Err(SelectionError::InvalidParameter(format!(
+            "The sigma scaling factor must be non-negative, got {}",
+            c
+        )));
+    }
+
+    let avg = mean(fitnesses);
+    let std_dev = (fitnesses.iter().map(|f| (f - avg).powi(2)).sum::<f64>()
+        / fitnesses.len() as f64)
+        .sqrt();
+
+    let base = avg - c * std_dev;
+
+    Ok(fitnesses.iter().map(|f| (f - base).max(0.0)).collect())
+}
+
+fn boltzmann(fitnesses: &[f64], temperature: f64) -> Result<Vec<f64>, SelectionError> {
+    if !(temperature > 0.0 && temperature.is_finite()) {
+        return Err(SelectionError::InvalidParameter(format!(
+            "The Boltzmann temperature must be positive, got {}",
+            temperature
+        )));
+    }
+
+    // Shifting by the best doesn't change the proportions, but avoids overflowing `exp`.
+    let best = fitnesses.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    Ok(fitnesses
+        .iter()
+        .map(|f| ((f - best) / temperature).exp())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn population(fitnesses: &[f64]) -> Vec<Bin> {
+        fitnesses
+            .iter()
+            .map(|&fitness| {
+                let mut individual = Bin::new(vec![true]);
+                individual.set_fitness(fitness);
+                individual
+            })
+            .collect()
+    }
+
+    fn weights(scaling: FitnessScaling, fitnesses: &[f64], objective: Objective) -> Vec<f64> {
+        scaling.weights(&population(fitnesses), objective).unwrap()
+    }
+
+    #[test]
+    fn none_rejects_negative_fitness() {
+        let result = FitnessScaling::None.weights(&population(&[1.0, -2.0]), Objective::Maximize);
+
+        assert_eq!(result, Err(SelectionError::NegativeFitness(-2.0)));
+    }
+
+    #[test]
+    fn rejects_nan_fitness() {
+        let result =
+            FitnessScaling::Windowing.weights(&population(&[1.0, f64::NAN]), Objective::Maximize);
+
+        assert!(matches!(result, Err(SelectionError::InvalidFitness(_))));
+    }
+
+    #[test]
+    fn all_zero_weights_are_uniform() {
+        let weights = weights(FitnessScaling::None, &[0.0, 0.0, 0.0], Objective::Maximize);
+
+        assert_eq!(weights, vec![1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn windowing_supports_negative_fitness_and_minimization() {
+        let fitnesses = [-3.0, -1.0, 2.0];
+
+        assert_eq!(
+            weights(FitnessScaling::Windowing, &fitnesses, Objective::Maximize),
+            vec![0.0, 2.0, 5.0]
+        );
+        assert_eq!(
+            weights(FitnessScaling::Windowing, &fitnesses, Objective::Minimize),
+            vec![5.0, 3.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn linear_keeps_the_average() {
+        let fitnesses = [1.0, 2.0, 3.0, 10.0];
+        let scaled = weights(FitnessScaling::Linear { c: 1.5 }, &fitnesses, Objective::Maximize);
+
+        let windowed_avg = (0.0 + 1.0 + 2.0 + 9.0) / 4.0;
+        let scaled_avg = scaled.iter().sum::<f64>() / 4.0;
+
+        assert!((scaled_avg - windowed_avg).abs() < 1e-9);
+        assert!(scaled.iter().all(|&weight| weight >= 0.0));
+        assert!(scaled[3] > scaled[2] && scaled[2] > scaled[1]);
+    }
+
+    #[test]
+    fn sigma_and_boltzmann_prefer_the_best() {
+        let fitnesses = [-10.0, -5.0, -1.0];
+
+        for scaling in [
+            FitnessScaling::Sigma { c: 2.0 },
+            FitnessScaling::Boltzmann { temperature: 2.0 },
+        ] {
+            let max = weights(scaling, &fitnesses, Objective::Maximize);
+            let min = weights(scaling, &fitnesses, Objective::Minimize);
+
+            assert!(max[2] > max[1] && max[1] > max[0]);
+            assert!(min[0] > min[1] && min[1] > min[2]);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let population = population(&[1.0, 2.0]);
+
+        for scaling in [
+            FitnessScaling::Linear { c: 1.0 },
+            FitnessScaling::Sigma { c: -1.0 },
+            FitnessScaling::Boltzmann { temperature: 0.0 },
+        ] {
+            assert!(matches!(
+                scaling.weights(&population, Objective::Maximize),
+                Err(SelectionError::InvalidParameter(_))
+            ));
+        }
+    }
+}
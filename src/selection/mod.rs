@@ -1,14 +1,21 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
 use dyn_clone::DynClone;
+use rand::seq::SliceRandom;
 
+use crate::context::Context;
 use crate::population::Individual;
 
 mod do_nothing_selection;
+mod fitness_scaling;
 mod roulette_selection;
 mod tournament_selection;
 mod rank_selection;
 mod stochastic_universal_sampling_selection;
 
 pub use do_nothing_selection::DoNothingSelection;
+pub use fitness_scaling::FitnessScaling;
 pub use roulette_selection::RouletteSelection;
 pub use tournament_selection::TournamentSelection;
 pub use rank_selection::RankSelection;
@@ -26,13 +33,124 @@ pub use stochastic_universal_sampling_selection::StochasticUniversalSamplingSele
 /// struct DoNothingSelection;
 ///
 /// impl<T: Individual> Selection<T> for DoNothingSelection {
-///    fn get_mating_pool(&self, initial_population: &Vec<T>) -> Vec<T> {
-///       initial_population.clone()
+///    fn get_mating_pool(
+///        &self,
+///        initial_population: &[T],
+///        _context: &mut Context<T>,
+///    ) -> Result<Vec<T>, SelectionError> {
+///       Ok(initial_population.to_vec())
 ///   }
 /// }
 /// ```
 pub trait Selection<T: Individual>: 'static + DynClone + Send + Sync {
     /// Method that performs the selection. It receives a reference to the initial population and
-    /// should return a new population.
-    fn get_mating_pool(&self, initial_population: &Vec<T>) -> Vec<T>;
+    /// should return a new population. The better individuals, according to `context.objective`,
+    /// should be favoured. All the randomness must come from `context.rng` so the evolution can be
+    /// reproduced from its seed. When the population can't be selected from, for example because
+    /// of an invalid fitness, it returns a [`SelectionError`] instead of panicking.
+    fn get_mating_pool(
+        &self,
+        initial_population: &[T],
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError>;
+
+    /// Checks the parameters of the selection, like the tournament size. It's called when the
+    /// evolution is built, so an invalid parameter is reported instead of failing during the run.
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Selects `count` individuals, for the replacements that create fewer or more offsprings
+    /// than the population size. By default, it calls [`Selection::get_mating_pool`] as many
+    /// times as needed and picks `count` of the selected individuals at random. Override it when
+    /// the selection can choose any number of individuals.
+    fn select(
+        &self,
+        population: &[T],
+        count: usize,
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        let mut mating_pool = self.get_mating_pool(population, context)?;
+
+        if mating_pool.len() == count {
+            return Ok(mating_pool);
+        }
+
+        while !mating_pool.is_empty() && mating_pool.len() < count {
+            mating_pool.extend(self.get_mating_pool(population, context)?);
+        }
+
+        mating_pool.shuffle(context.rng);
+        mating_pool.truncate(count);
+
+        Ok(mating_pool)
+    }
+}
+
+/// The reasons a [`Selection`] can fail.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectionError {
+    /// The population to select from is empty.
+    EmptyPopulation,
+    /// An individual has a NaN or infinite fitness.
+    InvalidFitness(f64),
+    /// An individual has a negative fitness, which can't be used as a selection weight. Use a
+    /// [`FitnessScaling`] that supports it, like `FitnessScaling::Windowing`.
+    NegativeFitness(f64),
+    /// A parameter of the selection is out of its valid range.
+    InvalidParameter(String),
+}
+
+impl Display for SelectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelectionError::EmptyPopulation => write!(f, "Can't select from an empty population"),
+            SelectionError::InvalidFitness(fitness) => {
+                write!(f, "Can't select individuals with an invalid fitness: {}", fitness)
+            }
+            SelectionError::NegativeFitness(fitness) => write!(
+                f,
+                "Can't use the negative fitness {} as a selection weight, use a fitness scaling",
+                fitness
+            ),
+            SelectionError::InvalidParameter(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for SelectionError {}
+
+
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    fn select<S: Selection<Bin>>(selection: S, count: usize) -> usize {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        let population: Vec<Bin> = (0..10)
+            .map(|fitness| {
+                let mut individual = Bin::new(vec![true]);
+                individual.set_fitness(fitness as f64 + 1.0);
+                individual
+            })
+            .collect();
+
+        selection.select(&population, count, &mut context).unwrap().len()
+    }
+
+    #[test]
+    fn selects_as_many_as_asked() {
+        for count in [0, 3, 10, 25] {
+            assert_eq!(select(TournamentSelection::default(), count), count);
+            assert_eq!(select(RouletteSelection::default(), count), count);
+            assert_eq!(select(RankSelection, count), count);
+            assert_eq!(select(StochasticUniversalSamplingSelection::default(), count), count);
+            assert_eq!(select(DoNothingSelection, count), count);
+        }
+    }
 }
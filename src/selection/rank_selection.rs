@@ -1,39 +1,73 @@
+use crate::constraints::{stochastic_ranking, ConstraintHandling};
+use crate::context::Context;
 use crate::{Individual, Selection};
-use rand::thread_rng;
 use rayon::prelude::*;
-use std::cmp::Ordering;
 use rand::distributions::{Distribution, WeightedIndex};
 
+use super::SelectionError;
+
 /// # Rank Selection
 ///
 /// It creates an ranking of the individuals based on their fitness, where the works takes 1, the next 2, and follows
 /// linearly so that the best receives `n` (number of individuals in the population). Then it chooses the individuals
-/// with probability based on the ranking value.
+/// with probability based on the ranking value. The worst and the best are taken from the objective of the
+/// evolution, or from the stochastic ranking when it's the constraint handling.
 #[derive(Clone, Default)]
 pub struct RankSelection;
 
 impl<T: Individual> Selection<T> for RankSelection {
-    fn get_mating_pool(&self, initial_population: &Vec<T>) -> Vec<T> {
-        let mut sorted_population = initial_population.clone();
-        sorted_population.par_sort_by(|a, b| {
-            a.get_fitness()
-                .partial_cmp(&b.get_fitness())
-                .unwrap_or(Ordering::Equal)
-        });
+    fn get_mating_pool(
+        &self,
+        initial_population: &[T],
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        self.select(initial_population, initial_population.len(), context)
+    }
+
+    fn select(
+        &self,
+        population: &[T],
+        count: usize,
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        if population.is_empty() {
+            return Err(SelectionError::EmptyPopulation);
+        }
+
+        let sorted_population = match context.constraint_handling {
+            Some(ConstraintHandling::StochasticRanking { pf }) => {
+                let ranking =
+                    stochastic_ranking(population, context.objective, pf, context.rng);
+
+                // From the worst to the best, as the weights.
+                ranking
+                    .into_iter()
+                    .rev()
+                    .map(|index| population[index].clone())
+                    .collect()
+            }
+            _ => {
+                let mut sorted_population = population.to_vec();
+                let context = &*context;
+                sorted_population.par_sort_by(|a, b| context.compare(a, b));
+                sorted_population
+            }
+        };
 
         let population_size = sorted_population.len();
         let weights: Vec<_> = (1..=population_size).map(|rank| rank).collect();
 
         let dist = WeightedIndex::new(&weights).unwrap();
 
-        (0..population_size)
+        let streams = context.rng.streams();
+
+        Ok((0..count)
             .into_par_iter()
-            .map_init(
-                || thread_rng(),
-                |mut rng, _| {
+            .map(|i| {
+                let mut rng = streams.get(i);
                 let index = dist.sample(&mut rng);
                 sorted_population[index].clone()
             })
-            .collect()
+            .collect())
     }
 }
@@ -1,26 +1,50 @@
-use rand::{seq::SliceRandom, thread_rng};
-use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rand::distributions::{Distribution, WeightedIndex};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use serde_derive::Deserialize;
 
+use crate::context::Context;
 use crate::population::Individual;
 
-use super::Selection;
+use super::{FitnessScaling, Selection, SelectionError};
 
-#[derive(Clone, Default)]
-pub struct RouletteSelection;
+/// # Roulette Selection
+///
+/// Fitness-proportional selection: each individual is chosen with probability proportional to its
+/// weight, given by the `scaling` of the fitness. Default scaling is `FitnessScaling::None`.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RouletteSelection {
+    pub scaling: FitnessScaling,
+}
 
 impl<T: Individual> Selection<T> for RouletteSelection {
-    fn get_mating_pool(&self, initial_population: &Vec<T>) -> Vec<T> {
-        initial_population
-            .par_iter()
-            .map_init(
-                || thread_rng(),
-                |mut rng, _| {
-                    initial_population
-                        .choose_weighted(&mut rng, |individual| individual.get_fitness())
-                        .unwrap()
-                        .clone()
-                },
-            )
-            .collect()
+    fn get_mating_pool(
+        &self,
+        initial_population: &[T],
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        self.select(initial_population, initial_population.len(), context)
+    }
+
+    fn select(
+        &self,
+        population: &[T],
+        count: usize,
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        let weights = self.scaling.weights(population, context.objective)?;
+        let dist = WeightedIndex::new(&weights)
+            .map_err(|_| SelectionError::InvalidFitness(weights.iter().sum()))?;
+
+        let streams = context.rng.streams();
+
+        Ok((0..count)
+            .into_par_iter()
+            .map(|index| {
+                let mut rng = streams.get(index);
+
+                population[dist.sample(&mut rng)].clone()
+            })
+            .collect())
     }
 }
@@ -1,37 +1,72 @@
+use crate::context::Context;
 use crate::{Individual, Selection};
-use rand::{thread_rng, Rng};
+use rand::Rng;
+use serde_derive::Deserialize;
 use rayon::iter::ParallelIterator;
 use rayon::prelude::IntoParallelIterator;
 
-#[derive(Clone, Default)]
-pub struct StochasticUniversalSamplingSelection;
+use super::{FitnessScaling, SelectionError};
+
+/// # Stochastic Universal Sampling
+///
+/// Fitness-proportional selection that spins the roulette only once, with `n` equally spaced
+/// pointers, which reduces the bias of choosing each individual independently. The weight of each
+/// individual is given by the `scaling` of the fitness. Default scaling is `FitnessScaling::None`.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StochasticUniversalSamplingSelection {
+    pub scaling: FitnessScaling,
+}
 
 impl<T: Individual> Selection<T> for StochasticUniversalSamplingSelection {
-    fn get_mating_pool(&self, initial_population: &Vec<T>) -> Vec<T> {
-        let total_fitness: f64 = initial_population.iter().map(|ind| ind.get_fitness()).sum();
-        let population_size = initial_population.len();
-        let pointer_spacing = total_fitness / population_size as f64;
+    fn get_mating_pool(
+        &self,
+        initial_population: &[T],
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        self.select(initial_population, initial_population.len(), context)
+    }
+
+    fn select(
+        &self,
+        population: &[T],
+        count: usize,
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        let weights = self.scaling.weights(population, context.objective)?;
+        let total_weight: f64 = weights.iter().sum();
+        let population_size = population.len();
+
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let pointer_spacing = total_weight / count as f64;
+
+        if !pointer_spacing.is_finite() {
+            return Err(SelectionError::InvalidFitness(total_weight));
+        }
 
-        let start_point = thread_rng().gen_range(0.0..pointer_spacing);
+        let start_point = context.rng.gen_range(0.0..pointer_spacing);
 
-        let pointers: Vec<_> = (0..population_size)
+        let pointers: Vec<_> = (0..count)
             .into_par_iter()
             .map(|i| start_point + i as f64 * pointer_spacing)
             .collect();
 
-        let mut selected_individuals = Vec::new();
-        let mut cumulative_fitness = 0.0;
+        let mut selected_individuals = Vec::with_capacity(count);
+        let mut cumulative_weight = weights[0];
         let mut individual_idx = 0;
 
         // TODO: find a way to parallelize this part
         for pointer in pointers {
-            while cumulative_fitness < pointer && individual_idx < population_size {
-                cumulative_fitness += initial_population[individual_idx].get_fitness();
+            while cumulative_weight <= pointer && individual_idx < population_size - 1 {
                 individual_idx += 1;
+                cumulative_weight += weights[individual_idx];
             }
-            selected_individuals.push(initial_population[individual_idx - 1].clone());
+            selected_individuals.push(population[individual_idx].clone());
         }
 
-        selected_individuals
+        Ok(selected_individuals)
     }
 }
@@ -1,17 +1,45 @@
-use rand::{random, seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, Rng};
+use serde_derive::Deserialize;
 
+use crate::context::Context;
 use crate::population::Individual;
 
-use super::Selection;
+use super::{Selection, SelectionError};
 
 use rayon::prelude::*;
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct TournamentSelection {
     k: usize,
     kp: f64,
 }
 
+impl TournamentSelection {
+    /// `k` individuals, at least 2, compete in each tournament. The best of them wins with
+    /// probability `kp`, between 0 and 1, otherwise the second best does. The parameters are
+    /// checked when the evolution is built, and when selecting, which returns a
+    /// [`SelectionError::InvalidParameter`] if they're out of range.
+    pub fn new(k: usize, kp: f64) -> Self {
+        Self { k, kp }
+    }
+
+    fn check_parameters(&self) -> Result<(), String> {
+        if self.k < 2 {
+            return Err(format!("The tournament size must be at least 2, got {}", self.k));
+        }
+
+        if !(0.0..=1.0).contains(&self.kp) {
+            return Err(format!(
+                "The probability of the best winning the tournament must be in [0, 1], got {}",
+                self.kp
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 impl Default for TournamentSelection {
     fn default() -> Self {
         TournamentSelection { k: 2, kp: 1.0 }
@@ -19,27 +47,96 @@ impl Default for TournamentSelection {
 }
 
 impl<T: Individual> Selection<T> for TournamentSelection {
-    fn get_mating_pool(&self, initial_population: &Vec<T>) -> Vec<T> {
-        initial_population
-            .par_iter()
-            .map_init(
-                || thread_rng(),
-                |mut rng, _| {
-                    let mut tournament = initial_population
-                        .choose_multiple(&mut rng, self.k)
-                        .cloned()
-                        .collect::<Vec<T>>();
-
-                    tournament
-                        .sort_by(|a, b| b.get_fitness().partial_cmp(&a.get_fitness()).unwrap());
-
-                    if random::<f64>() <= self.kp {
-                        tournament[0].clone()
-                    } else {
-                        tournament[1].clone()
-                    }
-                },
-            )
-            .collect()
+    fn get_mating_pool(
+        &self,
+        initial_population: &[T],
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        self.select(initial_population, initial_population.len(), context)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        self.check_parameters()
+    }
+
+    fn select(
+        &self,
+        population: &[T],
+        count: usize,
+        context: &mut Context<T>,
+    ) -> Result<Vec<T>, SelectionError> {
+        if population.is_empty() {
+            return Err(SelectionError::EmptyPopulation);
+        }
+
+        self.check_parameters().map_err(SelectionError::InvalidParameter)?;
+
+        let streams = context.rng.streams();
+        let context = &*context;
+
+        Ok((0..count)
+            .into_par_iter()
+            .map(|index| {
+                let mut rng = streams.get(index);
+
+                let mut tournament = population
+                    .choose_multiple(&mut rng, self.k)
+                    .cloned()
+                    .collect::<Vec<T>>();
+
+                tournament.sort_by(|a, b| context.compare(b, a));
+
+                // A population of one can only hold a tournament of one.
+                if rng.gen::<f64>() <= self.kp || tournament.len() < 2 {
+                    tournament[0].clone()
+                } else {
+                    tournament[1].clone()
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::prelude::*;
+
+    fn select(
+        selection: TournamentSelection,
+        population: &[Bin],
+    ) -> Result<Vec<Bin>, SelectionError> {
+        let mut rng = EvolutionRng::seed_from_u64(0);
+        let mut context = Context::new(&mut rng, Objective::Maximize);
+
+        selection.get_mating_pool(population, &mut context)
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let population = vec![Bin::new(vec![true]), Bin::new(vec![false])];
+
+        for selection in [
+            TournamentSelection::new(1, 1.0),
+            TournamentSelection::new(2, -0.1),
+            TournamentSelection::new(2, 1.5),
+            TournamentSelection::new(2, f64::NAN),
+        ] {
+            assert!(matches!(
+                select(selection, &population),
+                Err(SelectionError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn selects_from_a_population_of_one() {
+        let population = vec![Bin::new(vec![true])];
+
+        let pool = select(TournamentSelection::new(3, 0.0), &population).unwrap();
+
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool[0].get_chromosome(), &vec![true]);
     }
 }
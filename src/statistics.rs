@@ -0,0 +1,463 @@
+//! Statistics to compare the results of evolutions, used by the
+//! [`ExperimentSuite`](crate::experiment_suite::ExperimentSuite) and the
+//! [`RacingTuner`](crate::racing::RacingTuner).
+
+use crate::metrics::percentile;
+
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+pub fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    percentile(&sorted, 50.0)
+}
+
+/// Sample standard deviation of the values, 0 when there are less than two.
+pub fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = mean(values);
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+
+    variance.sqrt()
+}
+
+/// The result of a [`mann_whitney_u`] test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RankSumTest {
+    /// The U statistic of the first sample.
+    pub u: f64,
+    /// The standardized U, negative when the first sample tends to be lower.
+    pub z: f64,
+    /// The two-sided p-value, from the normal approximation.
+    pub p_value: f64,
+}
+
+/// Mann-Whitney U test, also known as the Wilcoxon rank-sum test, of whether the values of one
+/// sample tend to be higher or lower than the ones of the other. It makes no assumption about
+/// their distribution, which suits the final fitness of evolutions.
+///
+/// The p-value is two-sided and comes from the normal approximation with continuity and ties
+/// corrections, which is accurate from about 10 values per sample. It's NaN when a sample is
+/// empty.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::statistics::mann_whitney_u;
+///
+/// let test = mann_whitney_u(&[1.0, 2.0, 3.0, 4.0, 5.0], &[6.0, 7.0, 8.0, 9.0, 10.0]);
+///
+/// assert_eq!(test.u, 0.0);
+/// assert!(test.p_value < 0.05);
+/// ```
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> RankSumTest {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    if a.is_empty() || b.is_empty() {
+        return RankSumTest {
+            u: f64::NAN,
+            z: f64::NAN,
+            p_value: f64::NAN,
+        };
+    }
+
+    let mut values: Vec<(f64, bool)> = a
+        .iter()
+        .map(|&v| (v, true))
+        .chain(b.iter().map(|&v| (v, false)))
+        .collect();
+    values.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // Tied values share the average of their ranks.
+    let (mut rank_sum, mut ties_correction) = (0.0, 0.0);
+    let mut start = 0;
+    while start < values.len() {
+        let end = start
+            + values[start..]
+                .iter()
+                .take_while(|v| v.0 == values[start].0)
+                .count();
+        let ties = (end - start) as f64;
+        let rank = (start + end + 1) as f64 / 2.0;
+
+        rank_sum += rank * values[start..end].iter().filter(|v| v.1).count() as f64;
+        ties_correction += ties.powi(3) - ties;
+        start = end;
+    }
+
+    let n = n1 + n2;
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let sigma = (n1 * n2 / 12.0 * ((n + 1.0) - ties_correction / (n * (n - 1.0)))).sqrt();
+
+    if sigma == 0.0 {
+        return RankSumTest {
+            u,
+            z: 0.0,
+            p_value: 1.0,
+        };
+    }
+
+    // The continuity correction moves U toward the mean, but never past it.
+    let difference = u - mean;
+    let z = difference.signum() * (difference.abs() - 0.5).max(0.0) / sigma;
+    let p_value = erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0);
+
+    RankSumTest { u, z, p_value }
+}
+
+/// The result of a [`friedman_test`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FriedmanTest {
+    /// The Friedman statistic, corrected for ties.
+    pub statistic: f64,
+    /// The p-value, from the chi-squared distribution with one degree of freedom less than the
+    /// number of treatments.
+    pub p_value: f64,
+    /// The sum over the blocks of the ranks of each treatment, the lowest value ranked 1.
+    pub rank_sums: Vec<f64>,
+    blocks: usize,
+    squared_ranks: f64,
+}
+
+/// Friedman test of whether some of the treatments tend to rank lower or higher than the others
+/// across the blocks, as configurations of an evolution evaluated on several problem instances.
+/// Each block holds one value per treatment, and the values are ranked within their block.
+///
+/// When it's significant, [`FriedmanTest::post_hoc`] tells which pairs of treatments differ.
+/// It's NaN with less than two blocks or two treatments.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::statistics::friedman_test;
+///
+/// let blocks = [[1.0, 2.0, 3.0], [1.0, 3.0, 2.0], [1.0, 2.0, 3.0], [1.0, 2.0, 3.0]];
+/// let test = friedman_test(&blocks);
+///
+/// assert_eq!(test.rank_sums, vec![4.0, 9.0, 11.0]);
+/// assert!(test.p_value < 0.05);
+/// ```
+pub fn friedman_test<B: AsRef<[f64]>>(blocks: &[B]) -> FriedmanTest {
+    let treatments = blocks.first().map_or(0, |block| block.as_ref().len());
+    let (b, k) = (blocks.len() as f64, treatments as f64);
+
+    let mut rank_sums = vec![0.0; treatments];
+    let mut squared_ranks = 0.0;
+    for block in blocks {
+        for (treatment, rank) in ranks(block.as_ref()).into_iter().enumerate() {
+            rank_sums[treatment] += rank;
+            squared_ranks += rank * rank;
+        }
+    }
+
+    let mut test = FriedmanTest {
+        statistic: f64::NAN,
+        p_value: f64::NAN,
+        rank_sums,
+        blocks: blocks.len(),
+        squared_ranks,
+    };
+    if blocks.len() < 2 || treatments < 2 {
+        return test;
+    }
+
+    let expected = b * k * (k + 1.0).powi(2) / 4.0;
+    if squared_ranks == expected {
+        // Every block is a tie.
+        test.statistic = 0.0;
+        test.p_value = 1.0;
+        return test;
+    }
+
+    let deviations = test.rank_sums.iter().map(|r| r * r).sum::<f64>() - b * expected;
+    test.statistic = (k - 1.0) * deviations / (squared_ranks - expected);
+    test.p_value = regularized_gamma_q((k - 1.0) / 2.0, test.statistic / 2.0);
+
+    test
+}
+
+impl FriedmanTest {
+    /// The two-sided p-value of Conover's post hoc test of whether the treatments `i` and `j`
+    /// differ, from the Student's t-distribution. It's only meaningful when the Friedman test is
+    /// significant.
+    pub fn post_hoc(&self, i: usize, j: usize) -> f64 {
+        let (b, k) = (self.blocks as f64, self.rank_sums.len() as f64);
+        let expected = b * k * (k + 1.0).powi(2) / 4.0;
+        let difference = (self.rank_sums[i] - self.rank_sums[j]).abs();
+
+        let degrees_of_freedom = (b - 1.0) * (k - 1.0);
+        let variance = 2.0 * b * (self.squared_ranks - expected) / degrees_of_freedom
+            * (1.0 - self.statistic / (b * (k - 1.0)));
+
+        if variance.is_nan() {
+            return f64::NAN;
+        }
+        // Every block ranks the treatments the same, so any difference is certain.
+        if variance <= 0.0 {
+            return if difference == 0.0 { 1.0 } else { 0.0 };
+        }
+
+        let t = difference / variance.sqrt();
+        let x = degrees_of_freedom / (degrees_of_freedom + t * t);
+
+        regularized_beta(x, degrees_of_freedom / 2.0, 0.5)
+    }
+}
+
+/// The ranks of the values, from 1 for the lowest, tied values sharing the average of their
+/// ranks.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let value = values[order[start]];
+        let end = start
+            + order[start..]
+                .iter()
+                .take_while(|&&i| values[i] == value)
+                .count();
+        for &i in &order[start..end] {
+            ranks[i] = (start + end + 1) as f64 / 2.0;
+        }
+        start = end;
+    }
+
+    ranks
+}
+
+/// Complementary error function, with a relative error below 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = [
+        -1.26551223,
+        1.00002368,
+        0.37409196,
+        0.09678418,
+        -0.18628806,
+        0.27886807,
+        -1.13520398,
+        1.48851587,
+        -0.82215223,
+        0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |sum, coefficient| coefficient + t * sum);
+    let result = t * (-z * z + polynomial).exp();
+
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+/// Natural logarithm of the gamma function of a positive `x`, by the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    let coefficients = [
+        676.5203681218851,
+        -1259.1392167224028,
+        771.3234287776531,
+        -176.6150291621406,
+        12.507343278686905,
+        -0.13857109526572012,
+        9.984369578019572e-6,
+        1.5056327351493116e-7,
+    ];
+
+    let x = x - 1.0;
+    let sum = coefficients
+        .iter()
+        .enumerate()
+        .fold(0.9999999999998099, |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    let t = x + 7.5;
+
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+const EPSILON: f64 = 1e-15;
+const TINY: f64 = 1e-300;
+
+/// Upper regularized incomplete gamma function Q(a, x), by its series below `a + 1` and by its
+/// continued fraction above.
+fn regularized_gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let front = (-x + a * x.ln() - ln_gamma(a)).exp();
+
+    if x < a + 1.0 {
+        let (mut term, mut sum) = (1.0 / a, 1.0 / a);
+        for n in 1..500 {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+
+        (1.0 - front * sum).max(0.0)
+    } else {
+        let mut b = x + 1.0 - a;
+        let (mut c, mut d) = (1.0 / TINY, 1.0 / b);
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = non_zero(an * d + b).recip();
+            c = non_zero(b + an / c);
+            h *= d * c;
+            if (d * c - 1.0).abs() < EPSILON {
+                break;
+            }
+        }
+
+        front * h
+    }
+}
+
+/// Regularized incomplete beta function I_x(a, b), by its continued fraction.
+fn regularized_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    // The continued fraction converges quickly on this side, use the symmetry on the other.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    let mut c = 1.0;
+    let mut d = non_zero(1.0 - (a + b) * x / (a + 1.0)).recip();
+    let mut h = d;
+    for m in 1..500 {
+        let m = m as f64;
+
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = non_zero(1.0 + even * d).recip();
+        c = non_zero(1.0 + even / c);
+        h *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = non_zero(1.0 + odd * d).recip();
+        c = non_zero(1.0 + odd / c);
+        h *= d * c;
+
+        if (d * c - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    h
+}
+
+fn non_zero(value: f64) -> f64 {
+    if value.abs() < TINY {
+        TINY
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_the_values() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+        assert_eq!(mean(&values), 5.0);
+        assert_eq!(median(&values), 4.5);
+        assert!((std_dev(&values) - 2.13809).abs() < 1e-5);
+        assert_eq!(std_dev(&[1.0]), 0.0);
+    }
+
+    #[test]
+    fn compares_the_samples() {
+        // The p-values of the normal approximation with the continuity correction.
+        let test = mann_whitney_u(&[1.0, 2.0, 3.0, 4.0, 5.0], &[6.0, 7.0, 8.0, 9.0, 10.0]);
+        assert_eq!(test.u, 0.0);
+        assert!(test.z < 0.0);
+        assert!((test.p_value - 0.012185).abs() < 1e-5);
+
+        let test = mann_whitney_u(&[1.0, 2.0, 2.0, 3.0, 5.0], &[2.0, 3.0, 4.0, 4.0, 6.0]);
+        assert_eq!(test.u, 6.5);
+        assert!((test.p_value - 0.241844).abs() < 1e-5);
+
+        assert_eq!(mann_whitney_u(&[1.0, 1.0], &[1.0]).p_value, 1.0);
+        assert_eq!(
+            mann_whitney_u(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]).p_value,
+            1.0
+        );
+        assert_eq!(mann_whitney_u(&[1.0], &[1.0, 2.0]).p_value, 1.0);
+        assert!(mann_whitney_u(&[], &[1.0]).p_value.is_nan());
+    }
+
+    #[test]
+    fn ranks_the_treatments() {
+        // Every block ranks the treatments the same.
+        let blocks = vec![vec![1.0, 2.0, 3.0]; 4];
+        let test = friedman_test(&blocks);
+        assert_eq!(test.statistic, 8.0);
+        // The chi-squared distribution with 2 degrees of freedom has the survival exp(-x / 2).
+        assert!((test.p_value - (-4.0f64).exp()).abs() < 1e-10);
+        assert_eq!(test.post_hoc(0, 2), 0.0);
+
+        let blocks = [
+            [1.0, 2.0, 2.0],
+            [3.0, 1.0, 2.0],
+            [1.0, 3.0, 2.0],
+            [1.0, 2.0, 3.0],
+        ];
+        let test = friedman_test(&blocks);
+        assert_eq!(test.rank_sums, vec![6.0, 8.5, 9.5]);
+        assert!(test.p_value > 0.05);
+        assert!(test.post_hoc(0, 2) < test.post_hoc(0, 1));
+        assert_eq!(test.post_hoc(1, 1), 1.0);
+
+        assert_eq!(friedman_test(&[[1.0, 1.0], [2.0, 2.0]]).p_value, 1.0);
+        assert!(friedman_test(&[[1.0, 2.0]]).p_value.is_nan());
+    }
+
+    #[test]
+    fn approximates_the_distributions() {
+        assert!((ln_gamma(5.0) - 24.0f64.ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-12);
+
+        for x in [0.5, 3.0, 10.0] {
+            assert!((regularized_gamma_q(1.0, x) - (-x).exp()).abs() < 1e-12);
+        }
+
+        // The two-sided p-values of the t-distribution with 1 and 2 degrees of freedom.
+        for t in [0.5f64, 2.0, 10.0] {
+            let cauchy = 1.0 - 2.0 / std::f64::consts::PI * t.atan();
+            assert!((regularized_beta(1.0 / (1.0 + t * t), 0.5, 0.5) - cauchy).abs() < 1e-10);
+
+            let two = 1.0 - t / (2.0 + t * t).sqrt();
+            assert!((regularized_beta(2.0 / (2.0 + t * t), 1.0, 0.5) - two).abs() < 1e-10);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{evolution::StopConditionFn, metrics::Metrics};
+
+/// # Stop Condition
+///
+/// When an [`Evolution`](crate::Evolution) stops, checked against its [`Metrics`] after every
+/// generation. Set it with
+/// [`EvolutionBuilder::with_stop`](crate::EvolutionBuilder::with_stop).
+///
+/// - `MaxGenerations(n)`: after `n` generations.
+/// - `MaxEvaluations(n)`: once the fitness was calculated `n` times, see
+///   [`Metrics::evaluations`]. Comparing evolutions at the same budget of evaluations is fairer
+///   than at the same number of generations.
+/// - `TimeLimit(duration)`: once the evolution ran for `duration`. It's checked between the
+///   generations, so a generation that started before the limit is finished.
+/// - `TargetFitness(fitness)`: once the best fitness is at least as good as `fitness`.
+/// - `Stagnation(n)`: after `n` generations without improving the best fitness.
+/// - `DiversityCollapse(threshold)`: once the standard deviation of the fitness of the population
+///   drops to `threshold`, as the population converged.
+/// - `Any` and `All`: when any or all of the conditions are met.
+/// - `Custom`: a closure that receives the metrics.
+///
+/// # Example
+///
+/// ```
+/// use evolutionary::prelude::*;
+/// use std::time::Duration;
+///
+/// let stop = StopCondition::TargetFitness(10.0)
+///     .or(StopCondition::MaxEvaluations(50_000))
+///     .or(StopCondition::TimeLimit(Duration::from_secs(60)));
+/// ```
+#[derive(Clone)]
+pub enum StopCondition {
+    MaxGenerations(u32),
+    MaxEvaluations(u64),
+    TimeLimit(Duration),
+    TargetFitness(f64),
+    Stagnation(u32),
+    DiversityCollapse(f64),
+    Any(Vec<StopCondition>),
+    All(Vec<StopCondition>),
+    Custom(Arc<dyn Fn(&Metrics) -> bool + Send + Sync>),
+}
+
+impl StopCondition {
+    /// Met when any of the `conditions` is.
+    pub fn any(conditions: impl IntoIterator<Item = StopCondition>) -> Self {
+        StopCondition::Any(conditions.into_iter().collect())
+    }
+
+    /// Met when all of the `conditions` are.
+    pub fn all(conditions: impl IntoIterator<Item = StopCondition>) -> Self {
+        StopCondition::All(conditions.into_iter().collect())
+    }
+
+    /// Met when the closure returns `true` for the metrics of the evolution.
+    pub fn custom<F: Fn(&Metrics) -> bool + 'static + Send + Sync>(f: F) -> Self {
+        StopCondition::Custom(Arc::new(f))
+    }
+
+    /// Met when this condition or the `other` is.
+    pub fn or(self, other: StopCondition) -> Self {
+        match self {
+            StopCondition::Any(mut conditions) => {
+                conditions.push(other);
+                StopCondition::Any(conditions)
+            }
+            condition => StopCondition::Any(vec![condition, other]),
+        }
+    }
+
+    /// Met when both this condition and the `other` are.
+    pub fn and(self, other: StopCondition) -> Self {
+        match self {
+            StopCondition::All(mut conditions) => {
+                conditions.push(other);
+                StopCondition::All(conditions)
+            }
+            condition => StopCondition::All(vec![condition, other]),
+        }
+    }
+
+    /// Returns if the evolution with these `metrics` should stop.
+    pub fn is_met(&self, metrics: &Metrics) -> bool {
+        match self {
+            StopCondition::MaxGenerations(generations) => metrics.iterations >= *generations,
+            StopCondition::MaxEvaluations(evaluations) => metrics.evaluations >= *evaluations,
+            StopCondition::TimeLimit(limit) => metrics.elapsed() >= *limit,
+            StopCondition::TargetFitness(target) => metrics
+                .best_so_far()
+                .is_some_and(|best| !metrics.objective().is_better(*target, best)),
+            StopCondition::Stagnation(generations) => {
+                metrics.gens_without_improvement >= *generations
+            }
+            StopCondition::DiversityCollapse(threshold) => metrics
+                .fitness_std_devs
+                .last()
+                .is_some_and(|std_dev| std_dev <= threshold),
+            StopCondition::Any(conditions) => {
+                conditions.iter().any(|condition| condition.is_met(metrics))
+            }
+            StopCondition::All(conditions) => {
+                conditions.iter().all(|condition| condition.is_met(metrics))
+            }
+            StopCondition::Custom(f) => f(metrics),
+        }
+    }
+}
+
+/// For the closures of [`EvolutionBuilder::with_stop_condition`](crate::EvolutionBuilder), which
+/// receive the best fitness of the last generation, the number of generations and the number of
+/// generations without improvement.
+impl From<StopConditionFn> for StopCondition {
+    fn from(f: StopConditionFn) -> Self {
+        StopCondition::custom(move |metrics| {
+            let best_fitness = metrics.best_fitnesses.last().copied().unwrap_or(f64::NAN);
+
+            f(best_fitness, metrics.iterations, metrics.gens_without_improvement)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objective::Objective;
+
+    fn metrics(best_fitnesses: &[f64], std_dev: f64) -> Metrics {
+        let mut metrics = Metrics::with_objective(Objective::Minimize);
+
+        for &best_fitness in best_fitnesses {
+            metrics.record(best_fitness, best_fitness);
+        }
+        metrics.fitness_std_devs.push(std_dev);
+        metrics.evaluations = 100 * best_fitnesses.len() as u64;
+
+        metrics
+    }
+
+    #[test]
+    fn checks_the_metrics() {
+        let metrics = metrics(&[5.0, 3.0, 3.0, 3.0], 0.5);
+
+        assert!(StopCondition::MaxGenerations(4).is_met(&metrics));
+        assert!(!StopCondition::MaxGenerations(5).is_met(&metrics));
+        assert!(StopCondition::MaxEvaluations(400).is_met(&metrics));
+        assert!(!StopCondition::MaxEvaluations(401).is_met(&metrics));
+        assert!(StopCondition::TargetFitness(3.0).is_met(&metrics));
+        assert!(!StopCondition::TargetFitness(2.0).is_met(&metrics));
+        assert!(StopCondition::Stagnation(2).is_met(&metrics));
+        assert!(!StopCondition::Stagnation(3).is_met(&metrics));
+        assert!(StopCondition::DiversityCollapse(0.5).is_met(&metrics));
+        assert!(!StopCondition::DiversityCollapse(0.1).is_met(&metrics));
+        assert!(!StopCondition::TimeLimit(Duration::from_secs(60)).is_met(&metrics));
+    }
+
+    #[test]
+    fn combines_the_conditions() {
+        let metrics = metrics(&[5.0, 3.0], 0.5);
+        let (met, not_met) = (StopCondition::MaxGenerations(2), StopCondition::Stagnation(1));
+
+        assert!(met.clone().or(not_met.clone()).is_met(&metrics));
+        assert!(!met.clone().and(not_met.clone()).is_met(&metrics));
+        assert!(StopCondition::all([met.clone(), met.clone()]).is_met(&metrics));
+        assert!(!StopCondition::any([not_met.clone(), not_met]).is_met(&metrics));
+
+        let closure: StopConditionFn =
+            Arc::new(|best, iterations, _| best == 3.0 && iterations == 2);
+        assert!(StopCondition::from(closure).is_met(&metrics));
+    }
+}
@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+/// Counts the genes that are set, so the best individual has all of them.
+#[derive(Clone)]
+pub(crate) struct OnesFitness;
+
+impl Fitness<Bin> for OnesFitness {
+    fn calculate_fitness(&self, individual: &Bin) -> f64 {
+        individual
+            .get_chromosome()
+            .iter()
+            .filter(|&&gene| gene)
+            .count() as f64
+    }
+}
+
+/// A binary evolution maximizing [`OnesFitness`] with the default operators. The tests add the
+/// stop condition and replace the operators they compare.
+pub(crate) fn ones_builder(population_size: u32, dimension: u32) -> EvolutionBuilder<Bin> {
+    EvolutionBuilder::new(population_size, dimension, GeneCod::Bin, ())
+        .with_fitness(OnesFitness)
+        .with_selection(TournamentSelection::default())
+        .with_crossover(NPointsCrossover::default())
+        .with_mutation(BitFlipMutation::default())
+}
@@ -15,6 +15,15 @@ pub fn within_range(range: (f64, f64), l: f64, d: f64) -> f64 {
     (range.0 + (a / b) * d).floor()
 }
 
+/// Checks that the operator parameter `name` is a probability, in `[0, 1]`.
+pub(crate) fn check_probability(name: &str, probability: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&probability) {
+        Ok(())
+    } else {
+        Err(format!("The {} must be in [0, 1], got {}", name, probability))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::utils::{convert_bin, within_range};